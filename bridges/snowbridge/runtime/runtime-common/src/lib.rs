@@ -62,11 +62,11 @@ where
 	fn handle_fee(fees: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
 		let token_location = FeeAssetLocation::get();
 
-		// Check the reason to see if this export is for snowbridge.
+		// Check the reason to see if this export is for snowbridge, however the fee is paid.
 		if !matches!(
-			reason,
-			FeeReason::Export { network: bridged_network, ref destination }
-				if bridged_network == EthereumNetwork::get() && destination == &Here
+			reason.charged_for(),
+			FeeReason::Export { network: bridged_network, destination }
+				if *bridged_network == EthereumNetwork::get() && destination == &Here
 		) {
 			return fees
 		}
@@ -111,6 +111,29 @@ fn handle_fee_success() {
 	assert_eq!(result, local_fee)
 }
 
+#[test]
+fn handle_fee_paid_with_pay_fees_success() {
+	let fee: Assets = Asset::from((Location::parent(), 10_u128)).into();
+	let ctx = XcmContext {
+		origin: Some(Location::new(1, Parachain(1000))),
+		message_id: XcmHash::default(),
+		topic: None,
+	};
+	let export = FeeReason::Export { network: EthereumNetwork::get(), destination: Here };
+	let reason = FeeReason::PayFees { charged_for: Box::new(export) };
+	let result = XcmExportFeeToSibling::<
+		u128,
+		u64,
+		TokenLocation,
+		EthereumNetwork,
+		MockAssetTransactor,
+		MockOkOutboundQueue,
+	>::handle_fee(fee, Some(&ctx), reason);
+	let local_fee = Asset::from((Location::parent(), MockOkOutboundQueue::local_fee())).into();
+	// assert only local fee left
+	assert_eq!(result, local_fee)
+}
+
 #[test]
 fn handle_fee_success_but_not_for_ethereum() {
 	let fee: Assets = Asset::from((Location::parent(), 10_u128)).into();
@@ -1540,6 +1540,31 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl assets_common::runtime_api::FeePaymentAssetsApi<Block> for Runtime {
+		fn query_acceptable_payment_assets(xcm_version: xcm::Version) -> Result<Vec<VersionedAssetId>, assets_common::runtime_api::FungiblesAccessError> {
+			use assets_common::fungible_conversion::{acceptable_payment_assets, sufficient_asset_ids};
+			let native_token = xcm_config::TokenLocation::get();
+			let swappable = assets_common::PoolAdapter::<Runtime>::get_assets_in_pool_with(native_token.clone())
+				.map_err(|()| assets_common::runtime_api::FungiblesAccessError::PoolAssetsReadFailed)?;
+			acceptable_payment_assets(
+				xcm_version,
+				// the native token, sufficient assets and assets swappable against the native token
+				core::iter::once(AssetId(native_token))
+					.chain(sufficient_asset_ids::<
+						Runtime,
+						TrustBackedAssetsInstance,
+						AssetIdForTrustBackedAssetsConvert<TrustBackedAssetsPalletLocation, xcm::v5::Location>,
+					>())
+					.chain(sufficient_asset_ids::<
+						Runtime,
+						ForeignAssetsInstance,
+						xcm_builder::WithLatestLocationConverter<xcm::v5::Location>,
+					>())
+					.chain(swappable),
+			)
+		}
+	}
+
 	impl xcm_runtime_apis::fees::XcmPaymentApi<Block> for Runtime {
 		fn query_acceptable_payment_assets(xcm_version: xcm::Version) -> Result<Vec<VersionedAssetId>, XcmPaymentApiError> {
 			let native_token = xcm_config::TokenLocation::get();
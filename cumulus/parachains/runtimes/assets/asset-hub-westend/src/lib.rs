@@ -1810,6 +1810,42 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl assets_common::runtime_api::FeePaymentAssetsApi<Block> for Runtime {
+		fn query_acceptable_payment_assets(xcm_version: xcm::Version) -> Result<Vec<VersionedAssetId>, assets_common::runtime_api::FungiblesAccessError> {
+			use assets_common::fungible_conversion::{acceptable_payment_assets, sufficient_asset_ids};
+			let native_token = xcm_config::WestendLocation::get();
+			let swappable = assets_common::PoolAdapter::<Runtime>::get_assets_in_pool_with(native_token.clone())
+				.map_err(|()| assets_common::runtime_api::FungiblesAccessError::PoolAssetsReadFailed)?;
+			acceptable_payment_assets(
+				xcm_version,
+				// the native token, sufficient assets and assets swappable against the native token
+				core::iter::once(AssetId(native_token))
+					.chain(sufficient_asset_ids::<
+						Runtime,
+						TrustBackedAssetsInstance,
+						AssetIdForTrustBackedAssetsConvert<TrustBackedAssetsPalletLocation, xcm::v5::Location>,
+					>())
+					.chain(sufficient_asset_ids::<
+						Runtime,
+						ForeignAssetsInstance,
+						xcm_builder::WithLatestLocationConverter<xcm::v5::Location>,
+					>())
+					.chain(swappable),
+			)
+		}
+	}
+
+	impl assets_common::runtime_api::AssetLocationApi<Block> for Runtime {
+		fn location_to_asset_id(location: xcm::VersionedLocation) -> Option<assets_common::runtime_api::AssetIdKind> {
+			assets_common::fungible_conversion::location_to_asset_id::<
+				Balance,
+				TrustBackedAssetsConvertedConcreteId,
+				ForeignAssetsConvertedConcreteId,
+				PoolAssetsConvertedConcreteId,
+			>(location)
+		}
+	}
+
 	impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
 		fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
 			ParachainSystem::collect_collation_info(header)
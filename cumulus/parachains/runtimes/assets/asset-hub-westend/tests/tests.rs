@@ -823,6 +823,93 @@ fn test_asset_xcm_take_first_trader_not_possible_for_non_sufficient_assets() {
 		});
 }
 
+#[test]
+fn acceptable_fee_assets_checks_assets_and_pools() {
+	use assets_common::matching::{AcceptableFeeAssets, IsPooledWith, IsSufficientAsset};
+	type Acceptable = AcceptableFeeAssets<
+		WestendLocation,
+		IsSufficientAsset<Runtime, TrustBackedAssetsInstance, AssetIdForTrustBackedAssetsConvert>,
+		IsPooledWith<Runtime, WestendLocation>,
+	>;
+
+	ExtBuilder::<Runtime>::default()
+		.with_collators(vec![AccountId::from(ALICE)])
+		.with_session_keys(vec![(
+			AccountId::from(ALICE),
+			AccountId::from(ALICE),
+			SessionKeys { aura: AuraId::from(sp_core::sr25519::Public::from_raw(ALICE)) },
+		)])
+		.build()
+		.execute_with(|| {
+			let bob: AccountId = SOME_ASSET_ADMIN.into();
+			let asset_location =
+				|id: u32| AssetId(AssetIdForTrustBackedAssetsConvert::convert_back(&id).unwrap());
+
+			// a sufficient, a non-sufficient and a non-sufficient but pooled asset
+			assert_ok!(<Assets as Create<_>>::create(1, bob.clone(), true, 10));
+			assert_ok!(<Assets as Create<_>>::create(2, bob.clone(), false, 10));
+			assert_ok!(<Assets as Create<_>>::create(3, bob.clone(), false, 10));
+			assert_ok!(Balances::mint_into(&bob, 200 * UNITS));
+			assert_ok!(AssetConversion::create_pool(
+				RuntimeHelper::origin_of(bob.clone()),
+				Box::new(WestendLocation::get()),
+				Box::new(asset_location(3).0),
+			));
+
+			assert!(Acceptable::is_acceptable_fee_asset(&AssetId(WestendLocation::get())));
+			assert!(Acceptable::is_acceptable_fee_asset(&asset_location(1)));
+			assert!(!Acceptable::is_acceptable_fee_asset(&asset_location(2)));
+			assert!(Acceptable::is_acceptable_fee_asset(&asset_location(3)));
+
+			// unknown assets are rejected
+			assert!(!Acceptable::is_acceptable_fee_asset(&asset_location(4)));
+		});
+}
+
+#[test]
+fn location_to_asset_id_api_works() {
+	use asset_hub_westend_runtime::xcm_config::{
+		ForeignAssetsConvertedConcreteId, PoolAssetsConvertedConcreteId, PoolAssetsPalletLocation,
+		TrustBackedAssetsConvertedConcreteId,
+	};
+	use assets_common::runtime_api::{
+		runtime_decl_for_asset_location_api::AssetLocationApi, AssetIdKind,
+	};
+	use xcm_executor::traits::MatchesFungibles;
+
+	let in_pallet = |pallet: Location, index: u128| {
+		let mut location = pallet;
+		location.push_interior(GeneralIndex(index)).unwrap();
+		location
+	};
+	let trust_backed = in_pallet(TrustBackedAssetsPalletLocation::get(), 1);
+	let pool = in_pallet(PoolAssetsPalletLocation::get(), 2);
+	let foreign = Location::new(1, [Parachain(1234), GeneralIndex(12345)]);
+
+	// the API agrees with the converters of the asset transactors
+	assert_eq!(
+		Runtime::location_to_asset_id(trust_backed.clone().into()),
+		Some(AssetIdKind::TrustBacked(1))
+	);
+	assert_eq!(
+		TrustBackedAssetsConvertedConcreteId::matches_fungibles(&(trust_backed, 1).into()),
+		Ok((1, 1))
+	);
+	assert_eq!(
+		Runtime::location_to_asset_id(foreign.clone().into()),
+		Some(AssetIdKind::Foreign(foreign.clone().into()))
+	);
+	assert_eq!(
+		ForeignAssetsConvertedConcreteId::matches_fungibles(&(foreign.clone(), 1).into()),
+		Ok((foreign, 1))
+	);
+	assert_eq!(Runtime::location_to_asset_id(pool.clone().into()), Some(AssetIdKind::Pool(2)));
+	assert_eq!(PoolAssetsConvertedConcreteId::matches_fungibles(&(pool, 1).into()), Ok((2, 1)));
+
+	// the relay chain token is not stored as any of these assets
+	assert_eq!(Runtime::location_to_asset_id(Location::parent().into()), None);
+}
+
 #[test]
 fn test_assets_balances_api_works() {
 	use assets_common::runtime_api::runtime_decl_for_fungibles_api::FungiblesApi;
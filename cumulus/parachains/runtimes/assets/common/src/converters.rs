@@ -0,0 +1,1649 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`MatchesFungibles`] converters which wrap or complement the
+//! [`xcm_builder::MatchedConvertedConcreteId`] based converters.
+
+use alloc::vec::Vec;
+use codec::{DecodeAll, FullCodec};
+use core::{fmt::Debug, marker::PhantomData};
+use frame_support::{
+	storage::StorageMap,
+	traits::{tokens::fungibles, Contains, Get},
+};
+use sp_runtime::traits::{BlockNumberProvider, CheckedMul, CheckedSub, MaybeEquivalence, TryMorph};
+use xcm::latest::{
+	Asset, AssetId as XcmAssetId, Assets, Fungibility, Junction, Location, NetworkId,
+};
+use xcm_builder::{MatchedConvertedConcreteId, StartsWith};
+use xcm_executor::traits::{Error as MatchError, MatchesFungibles};
+
+/// Converter which checks that the balance matched by `Inner` is at least the `min_balance` of
+/// the asset in `Assets`.
+///
+/// Depositing less than `min_balance` of an asset fails later during execution, this converter
+/// surfaces the failure already when matching the asset.
+pub struct MinBalanceCheckedConvertedConcreteId<Inner, Assets, AccountId>(
+	PhantomData<(Inner, Assets, AccountId)>,
+);
+impl<
+		AssetId: Clone,
+		Balance: PartialOrd + Debug,
+		Inner: MatchesFungibles<AssetId, Balance>,
+		Assets: fungibles::Inspect<AccountId, AssetId = AssetId, Balance = Balance>,
+		AccountId,
+	> MatchesFungibles<AssetId, Balance>
+	for MinBalanceCheckedConvertedConcreteId<Inner, Assets, AccountId>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (asset_id, amount) = Inner::matches_fungibles(a)?;
+		let min_balance = Assets::minimum_balance(asset_id.clone());
+		if amount < min_balance {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"MinBalanceCheckedConvertedConcreteId asset: {a:?} is below min_balance: {min_balance:?}",
+			);
+			return Err(MatchError::AssetIdConversionFailed)
+		}
+		Ok((asset_id, amount))
+	}
+}
+
+/// Converter which normalizes any asset location contained in `Aliases` to the `Canonical`
+/// location before delegating to `Inner`, so that all aliases match the same asset id.
+///
+/// Locations not contained in `Aliases` are passed to `Inner` as they are.
+pub struct AliasedConvertedConcreteId<Canonical, Aliases, Inner>(
+	PhantomData<(Canonical, Aliases, Inner)>,
+);
+impl<
+		AssetId,
+		Balance,
+		Canonical: Get<Location>,
+		Aliases: Contains<Location>,
+		Inner: MatchesFungibles<AssetId, Balance>,
+	> MatchesFungibles<AssetId, Balance> for AliasedConvertedConcreteId<Canonical, Aliases, Inner>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		if !Aliases::contains(&a.id.0) {
+			return Inner::matches_fungibles(a)
+		}
+		let canonical = Asset { id: XcmAssetId(Canonical::get()), fun: a.fun.clone() };
+		log::trace!(
+			target: "xcm::matches_fungibles",
+			"AliasedConvertedConcreteId asset: {a:?} normalized to: {canonical:?}",
+		);
+		Inner::matches_fungibles(&canonical)
+	}
+}
+
+/// Converter which matches fungible assets whose location ends in a [`Junction::GeneralKey`] and
+/// decodes the key bytes into the asset id using `Decoder`.
+///
+/// The whole key must be consumed by decoding, keys with trailing bytes are not matched.
+///
+/// Note that any location ending in a `GeneralKey` is accepted, so this should be combined with a
+/// check of the location prefix, e.g. in a [`xcm_builder::MatchedConvertedConcreteId`] or a
+/// filtering transactor.
+pub struct GeneralKeyDecodingConvertedConcreteId<Decoder, Balance>(PhantomData<(Decoder, Balance)>);
+impl<Decoder: DecodeAll, Balance: TryFrom<u128>> MatchesFungibles<Decoder, Balance>
+	for GeneralKeyDecodingConvertedConcreteId<Decoder, Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(Decoder, Balance), MatchError> {
+		let (amount, (length, data)) = match (&a.fun, a.id.0.last()) {
+			(Fungibility::Fungible(amount), Some(Junction::GeneralKey { length, data })) =>
+				(amount, (*length as usize, data)),
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let key = data.get(..length).ok_or(MatchError::AssetIdConversionFailed)?;
+		let asset_id = Decoder::decode_all(&mut &key[..]).map_err(|e| {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"GeneralKeyDecodingConvertedConcreteId asset: {a:?} key cannot be decoded: {e:?}",
+			);
+			MatchError::AssetIdConversionFailed
+		})?;
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((asset_id, amount))
+	}
+}
+
+/// Index of the local assets registered under a symbol.
+pub trait AssetsBySymbol<AssetId> {
+	/// Get the ids of all assets registered with `symbol`.
+	fn assets_by_symbol(symbol: &[u8]) -> Vec<AssetId>;
+}
+
+/// Converter which matches fungible assets at `Prefix` followed by a [`Junction::GeneralKey`]
+/// holding the asset symbol, e.g. `USDT`, and resolves the symbol to the local asset id using
+/// `Index`.
+///
+/// Unknown symbols are not handled. Symbols registered for more than one asset are rejected as
+/// ambiguous.
+pub struct SymbolConvertedConcreteId<Prefix, Index, AssetId, Balance>(
+	PhantomData<(Prefix, Index, AssetId, Balance)>,
+);
+impl<
+		Prefix: Get<Location>,
+		Index: AssetsBySymbol<AssetId>,
+		AssetId: Debug,
+		Balance: TryFrom<u128>,
+	> MatchesFungibles<AssetId, Balance>
+	for SymbolConvertedConcreteId<Prefix, Index, AssetId, Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (amount, symbol) = match (&a.fun, a.id.0.match_and_split(&Prefix::get())) {
+			(Fungibility::Fungible(amount), Some(Junction::GeneralKey { length, data })) =>
+				(amount, data.get(..*length as usize).ok_or(MatchError::AssetIdConversionFailed)?),
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let mut asset_ids = Index::assets_by_symbol(symbol);
+		let asset_id = match asset_ids.len() {
+			0 => return Err(MatchError::AssetNotHandled),
+			1 => asset_ids.remove(0),
+			_ => {
+				log::trace!(
+					target: "xcm::matches_fungibles",
+					"SymbolConvertedConcreteId asset: {a:?} has ambiguous symbol: {asset_ids:?}",
+				);
+				return Err(MatchError::AssetIdConversionFailed)
+			},
+		};
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((asset_id, amount))
+	}
+}
+
+/// Table of local asset ids which were migrated to a new id, e.g. a storage map populated by
+/// governance.
+pub trait AssetIdRemapping<AssetId> {
+	/// Get the id that replaced `asset_id`, if it was remapped.
+	fn remapped(asset_id: &AssetId) -> Option<AssetId>;
+}
+
+/// Converter which resolves the asset id using `Inner` and then replaces it with the new id from
+/// `RemapTable`, if the asset was migrated.
+///
+/// This keeps in-flight messages that still reference the old id from being rejected.
+pub struct RemappingConvertedConcreteId<Inner, RemapTable>(PhantomData<(Inner, RemapTable)>);
+impl<
+		AssetId: Debug,
+		Balance,
+		Inner: MatchesFungibles<AssetId, Balance>,
+		RemapTable: AssetIdRemapping<AssetId>,
+	> MatchesFungibles<AssetId, Balance> for RemappingConvertedConcreteId<Inner, RemapTable>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (asset_id, amount) = Inner::matches_fungibles(a)?;
+		match RemapTable::remapped(&asset_id) {
+			Some(new_asset_id) => {
+				log::trace!(
+					target: "xcm::matches_fungibles",
+					"RemappingConvertedConcreteId asset: {a:?} remapped from {asset_id:?} to {new_asset_id:?}",
+				);
+				Ok((new_asset_id, amount))
+			},
+			None => Ok((asset_id, amount)),
+		}
+	}
+}
+
+/// Converter which matches fungible assets located exactly at `PalletLocation`, e.g.
+/// `[PalletInstance(13)]` with no [`Junction::GeneralIndex`], to the fixed `AssetId`.
+///
+/// Useful for pallets which manage a single asset that needs no index. Locations of assets
+/// inside the pallet are not handled.
+pub struct PalletInstanceConvertedConcreteId<PalletLocation, AssetId>(
+	PhantomData<(PalletLocation, AssetId)>,
+);
+impl<PalletLocation: Get<Location>, Id, AssetId: Get<Id>, Balance: TryFrom<u128>>
+	MatchesFungibles<Id, Balance> for PalletInstanceConvertedConcreteId<PalletLocation, AssetId>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(Id, Balance), MatchError> {
+		let amount = match (&a.fun, &a.id.0) {
+			(Fungibility::Fungible(amount), location) if *location == PalletLocation::get() =>
+				amount,
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((AssetId::get(), amount))
+	}
+}
+
+/// Converter which matches fungible assets at `Prefix` followed by a
+/// [`Junction::GeneralIndex`] and checks that the index fits into `AssetId`.
+///
+/// Indices beyond the range of `AssetId`, e.g. above `u32::MAX` for trust-backed assets, are
+/// rejected with [`MatchError::AssetIdConversionFailed`] and logged with the offending index, so
+/// clients sending out-of-range ids can be spotted. As any sender controls the locations, the log
+/// is at debug level.
+pub struct GeneralIndexCheckedConvertedConcreteId<Prefix, AssetId, Balance>(
+	PhantomData<(Prefix, AssetId, Balance)>,
+);
+impl<Prefix: Get<Location>, AssetId: TryFrom<u128>, Balance: TryFrom<u128>>
+	MatchesFungibles<AssetId, Balance>
+	for GeneralIndexCheckedConvertedConcreteId<Prefix, AssetId, Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (amount, index) = match (&a.fun, a.id.0.match_and_split(&Prefix::get())) {
+			(Fungibility::Fungible(amount), Some(Junction::GeneralIndex(index))) => (amount, index),
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let asset_id = AssetId::try_from(*index)
+			.map_err(|_| general_index_out_of_range::<AssetId>(a, *index))?;
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((asset_id, amount))
+	}
+}
+
+/// Report a [`Junction::GeneralIndex`] which doesn't fit into `AssetId`.
+fn general_index_out_of_range<AssetId>(a: &Asset, index: u128) -> MatchError {
+	log::debug!(
+		target: "xcm::matches_fungibles",
+		"GeneralIndexCheckedConvertedConcreteId asset: {a:?} has index {index} out of range of {}",
+		core::any::type_name::<AssetId>(),
+	);
+	MatchError::AssetIdConversionFailed
+}
+
+/// Converter which matches fungible assets at any location contained in `Members` to the same
+/// `CanonicalId`, e.g. to make wrapped and native representations of a token bridged across
+/// networks fungible for fee purposes.
+///
+/// # Warning
+///
+/// All members are treated as the same asset, so this must only be used for locations which are
+/// genuinely backed 1:1 by the same underlying token. Otherwise a cheaper or unbacked member can
+/// be used in place of the more valuable ones.
+pub struct UnifiedFungibleConvertedConcreteId<Members, CanonicalId, Balance>(
+	PhantomData<(Members, CanonicalId, Balance)>,
+);
+impl<AssetId, Members: Contains<Location>, CanonicalId: Get<AssetId>, Balance: TryFrom<u128>>
+	MatchesFungibles<AssetId, Balance>
+	for UnifiedFungibleConvertedConcreteId<Members, CanonicalId, Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let amount = match &a.fun {
+			Fungibility::Fungible(amount) if Members::contains(&a.id.0) => amount,
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((CanonicalId::get(), amount))
+	}
+}
+
+/// Converter which matches foreign assets with `Primary` and, if that fails, with each of the
+/// `Fallbacks` matchers in order, returning the first successful match.
+///
+/// This tolerates equivalent location encodings used by different sender versions. `Fallbacks`
+/// is usually a tuple of matchers. If none of the matchers succeeds, the error of `Primary` is
+/// returned.
+pub struct ForeignAssetsFallbackConvertedConcreteId<Primary, Fallbacks>(
+	PhantomData<(Primary, Fallbacks)>,
+);
+impl<
+		AssetId,
+		Balance,
+		Primary: MatchesFungibles<AssetId, Balance>,
+		Fallbacks: MatchesFungibles<AssetId, Balance>,
+	> MatchesFungibles<AssetId, Balance>
+	for ForeignAssetsFallbackConvertedConcreteId<Primary, Fallbacks>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let error = match Primary::matches_fungibles(a) {
+			Ok(matched) => return Ok(matched),
+			Err(error) => error,
+		};
+		Fallbacks::matches_fungibles(a)
+			.inspect(|_| {
+				log::trace!(
+					target: "xcm::matches_fungibles",
+					"ForeignAssetsFallbackConvertedConcreteId asset: {a:?} matched by fallback \
+					after primary failed with: {error:?}",
+				)
+			})
+			.map_err(|_| error)
+	}
+}
+
+/// Converter which rejects assets whose location has more than `MaxParents` parents before
+/// delegating to `Inner`.
+///
+/// E.g. `MaxParents = 1` only matches local and relay chain assets, but never assets of other
+/// consensus systems.
+pub struct ParentsBoundConvertedConcreteId<MaxParents, Inner>(PhantomData<(MaxParents, Inner)>);
+impl<AssetId, Balance, MaxParents: Get<u8>, Inner: MatchesFungibles<AssetId, Balance>>
+	MatchesFungibles<AssetId, Balance> for ParentsBoundConvertedConcreteId<MaxParents, Inner>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		if a.id.0.parent_count() > MaxParents::get() {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"ParentsBoundConvertedConcreteId asset: {a:?} has more than {} parents",
+				MaxParents::get(),
+			);
+			return Err(MatchError::AssetNotHandled)
+		}
+		Inner::matches_fungibles(a)
+	}
+}
+
+/// Converter which matches fungible assets of other consensus systems to ids namespaced by their
+/// network.
+///
+/// The location must start with a [`Junction::GlobalConsensus`] and end with a
+/// [`Junction::GeneralIndex`], which are converted to the `(NetworkId, u128)` id. This keeps
+/// assets with the same index under different networks apart, which a flat id space would
+/// conflate.
+pub struct NamespacedForeignConvertedConcreteId<Balance>(PhantomData<Balance>);
+impl<Balance: TryFrom<u128>> MatchesFungibles<(NetworkId, u128), Balance>
+	for NamespacedForeignConvertedConcreteId<Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<((NetworkId, u128), Balance), MatchError> {
+		let amount = match a.fun {
+			Fungibility::Fungible(amount) => amount,
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let interior = a.id.0.interior();
+		let asset_id = match (interior.first(), interior.last()) {
+			(Some(Junction::GlobalConsensus(network)), Some(Junction::GeneralIndex(index)))
+				if interior.len() >= 2 =>
+				(*network, *index),
+			_ => {
+				log::trace!(
+					target: "xcm::matches_fungibles",
+					"NamespacedForeignConvertedConcreteId asset: {a:?} is not a namespaced foreign asset",
+				);
+				return Err(MatchError::AssetNotHandled)
+			},
+		};
+		let amount =
+			Balance::try_from(amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((asset_id, amount))
+	}
+}
+
+/// Converter which maps the ids matched by `Inner` back to logical ids by subtracting `Offset`,
+/// e.g. when pool tokens share a `pallet_assets` instance with other assets by storing their ids
+/// shifted by an offset.
+///
+/// Ids below `Offset` don't belong to the shifted partition and are rejected with
+/// [`MatchError::AssetIdConversionFailed`].
+pub struct OffsetAssetIdConvertedConcreteId<Inner, Offset>(PhantomData<(Inner, Offset)>);
+impl<
+		AssetId: CheckedSub + Debug,
+		Balance,
+		Inner: MatchesFungibles<AssetId, Balance>,
+		Offset: Get<AssetId>,
+	> MatchesFungibles<AssetId, Balance> for OffsetAssetIdConvertedConcreteId<Inner, Offset>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (asset_id, amount) = Inner::matches_fungibles(a)?;
+		let Some(logical_id) = asset_id.checked_sub(&Offset::get()) else {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"OffsetAssetIdConvertedConcreteId asset: {a:?} has id {asset_id:?} below offset {:?}",
+				Offset::get(),
+			);
+			return Err(MatchError::AssetIdConversionFailed)
+		};
+		Ok((logical_id, amount))
+	}
+}
+
+/// Converter which stops matching the assets of `Inner` until the block `FrozenUntil`, e.g. to
+/// temporarily stop accepting a foreign asset in an emergency without deploying a deny-list.
+///
+/// While the current block, as reported by `Now`, is before `FrozenUntil` all assets are
+/// rejected with [`MatchError::AssetNotHandled`]. From `FrozenUntil` on, matching is delegated
+/// to `Inner`.
+pub struct TimeFrozenConvertedConcreteId<Inner, FrozenUntil, Now>(
+	PhantomData<(Inner, FrozenUntil, Now)>,
+);
+impl<
+		AssetId,
+		Balance,
+		Inner: MatchesFungibles<AssetId, Balance>,
+		FrozenUntil: Get<Now::BlockNumber>,
+		Now: BlockNumberProvider,
+	> MatchesFungibles<AssetId, Balance> for TimeFrozenConvertedConcreteId<Inner, FrozenUntil, Now>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let frozen_until = FrozenUntil::get();
+		if Now::current_block_number() < frozen_until {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"TimeFrozenConvertedConcreteId asset: {a:?} is frozen until block {frozen_until:?}",
+			);
+			return Err(MatchError::AssetNotHandled)
+		}
+		Inner::matches_fungibles(a)
+	}
+}
+
+/// Converter which resolves the location of a fungible asset to its id through the
+/// location-to-id mappings stored in `Registry`, e.g. by an asset registry pallet.
+///
+/// Unlike the converters matching on a fixed location layout, the set of matched assets is
+/// governed on-chain. Locations without a registered id are not handled.
+pub struct RegistryBackedConvertedConcreteId<Registry, Balance>(PhantomData<(Registry, Balance)>);
+impl<
+		AssetId: FullCodec,
+		Registry: StorageMap<Location, AssetId, Query = Option<AssetId>>,
+		Balance: TryFrom<u128>,
+	> MatchesFungibles<AssetId, Balance> for RegistryBackedConvertedConcreteId<Registry, Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (amount, location) = match (&a.fun, &a.id.0) {
+			(Fungibility::Fungible(amount), location) => (amount, location),
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let Some(asset_id) = Registry::get(location) else {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"RegistryBackedConvertedConcreteId asset: {a:?} is not registered",
+			);
+			return Err(MatchError::AssetNotHandled)
+		};
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((asset_id, amount))
+	}
+}
+
+/// Canonical order of the junctions of asset locations whose order doesn't change the asset
+/// they denote, used by [`CanonicalizingConvertedConcreteId`].
+pub trait JunctionOrder {
+	/// Rank of `junction` in the canonical order, or `None` if `junction` is never moved.
+	///
+	/// Within each run of consecutive ranked junctions, the junctions are sorted by rank, keeping
+	/// the relative order of junctions with the same rank. Junctions without a rank stay in place
+	/// and separate the runs.
+	fn rank(junction: &Junction) -> Option<u8>;
+}
+
+/// No junction is reordered.
+impl JunctionOrder for () {
+	fn rank(_: &Junction) -> Option<u8> {
+		None
+	}
+}
+
+/// [`JunctionOrder`] which puts each [`Junction::PalletInstance`] in front of the asset key
+/// junctions ([`Junction::GeneralIndex`] and [`Junction::GeneralKey`]) directly next to it.
+///
+/// E.g. `[Parachain(1000), GeneralIndex(1), PalletInstance(50)]` is ordered as
+/// `[Parachain(1000), PalletInstance(50), GeneralIndex(1)]`, while `[GeneralIndex(1),
+/// Parachain(1000), PalletInstance(50)]` is left untouched. Only suited for chains which never
+/// nest pallets under asset keys, so that both orders denote the same asset.
+pub struct PalletInstanceBeforeKeys;
+impl JunctionOrder for PalletInstanceBeforeKeys {
+	fn rank(junction: &Junction) -> Option<u8> {
+		match junction {
+			Junction::PalletInstance(_) => Some(0),
+			Junction::GeneralIndex(_) | Junction::GeneralKey { .. } => Some(1),
+			_ => None,
+		}
+	}
+}
+
+/// Converter which brings the location of an asset into canonical form before delegating to
+/// `Inner`, so that equivalent locations encoded differently match the same asset.
+///
+/// Two normalizations are applied:
+/// - the junctions ranked by `Order` are sorted into its canonical order, see [`JunctionOrder`].
+///   Which orders are equivalent depends on how the chain lays out its assets, so `Order` must only
+///   reorder junctions whose order never distinguishes two assets of the chain. With `Order = ()`
+///   no junction is reordered.
+/// - the bytes of a [`Junction::GeneralKey`] beyond its `length` carry no meaning and are zeroed.
+pub struct CanonicalizingConvertedConcreteId<Order, Inner>(PhantomData<(Order, Inner)>);
+impl<AssetId, Balance, Order: JunctionOrder, Inner: MatchesFungibles<AssetId, Balance>>
+	MatchesFungibles<AssetId, Balance> for CanonicalizingConvertedConcreteId<Order, Inner>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let mut canonical = a.clone();
+		let junctions = canonical.id.0.interior_mut().as_slice_mut();
+		let mut start = 0;
+		while start < junctions.len() {
+			let end = junctions[start..]
+				.iter()
+				.position(|junction| Order::rank(junction).is_none())
+				.map_or(junctions.len(), |offset| start + offset);
+			// stable, so the relative order of junctions with the same rank is kept
+			junctions[start..end].sort_by_key(Order::rank);
+			start = end + 1;
+		}
+		for junction in junctions {
+			if let Junction::GeneralKey { length, data } = junction {
+				let length = (*length as usize).min(data.len());
+				data[length..].fill(0);
+			}
+		}
+		if canonical != *a {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"CanonicalizingConvertedConcreteId asset: {a:?} matched as: {canonical:?}",
+			);
+		}
+		Inner::matches_fungibles(&canonical)
+	}
+}
+
+/// Converter which matches the native asset at `NativeLocation` to `NativeId` with a single
+/// location comparison and delegates all other assets to `Inner`.
+///
+/// The native asset is by far the most common asset, so this gives it a fast path in front of
+/// heavier matchers, e.g. `NativeFirstConvertedConcreteId<Native, NativeId, (Foreign, Trusted)>`.
+pub struct NativeFirstConvertedConcreteId<NativeLocation, NativeId, Inner>(
+	PhantomData<(NativeLocation, NativeId, Inner)>,
+);
+impl<
+		AssetId,
+		Balance: TryFrom<u128>,
+		NativeLocation: Get<Location>,
+		NativeId: Get<AssetId>,
+		Inner: MatchesFungibles<AssetId, Balance>,
+	> MatchesFungibles<AssetId, Balance>
+	for NativeFirstConvertedConcreteId<NativeLocation, NativeId, Inner>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		if a.id.0 != NativeLocation::get() {
+			return Inner::matches_fungibles(a)
+		}
+		let Fungibility::Fungible(amount) = a.fun else { return Err(MatchError::AssetNotHandled) };
+		let amount =
+			Balance::try_from(amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((NativeId::get(), amount))
+	}
+}
+
+/// Converter which matches fungible assets at `PalletLocation` followed by a
+/// [`Junction::GeneralIndex`] for the collection and a [`Junction::GeneralKey`] for the item
+/// within it, producing the composite id `(collection, item)`.
+///
+/// The key bytes are turned into the item id with `Decoder`, e.g. `Identity` to use them as-is.
+/// Locations with any other junctions after `PalletLocation` are not matched, so unlike the
+/// `GeneralIndex` based converters, items of the same collection are not collapsed into one id.
+pub struct CompositeIdConvertedConcreteId<PalletLocation, Decoder>(
+	PhantomData<(PalletLocation, Decoder)>,
+);
+impl<
+		PalletLocation: Get<Location>,
+		Decoder: TryMorph<Vec<u8>, Outcome = Vec<u8>>,
+		Balance: TryFrom<u128>,
+	> MatchesFungibles<(u32, Vec<u8>), Balance>
+	for CompositeIdConvertedConcreteId<PalletLocation, Decoder>
+{
+	fn matches_fungibles(a: &Asset) -> Result<((u32, Vec<u8>), Balance), MatchError> {
+		let pallet_location = PalletLocation::get();
+		let amount = match &a.fun {
+			Fungibility::Fungible(amount)
+				if a.id.0.starts_with(&pallet_location) &&
+					a.id.0.len() == pallet_location.len() + 2 =>
+				amount,
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let suffix = &a.id.0.interior().as_slice()[pallet_location.interior().len()..];
+		let (collection, key) = match suffix {
+			[Junction::GeneralIndex(collection), Junction::GeneralKey { length, data }] =>
+				(*collection, data.get(..*length as usize)),
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let collection =
+			u32::try_from(collection).map_err(|_| MatchError::AssetIdConversionFailed)?;
+		let key = key.ok_or(MatchError::AssetIdConversionFailed)?;
+		let item = Decoder::try_morph(key.to_vec()).map_err(|_| {
+			log::trace!(
+				target: "xcm::matches_fungibles",
+				"CompositeIdConvertedConcreteId asset: {a:?} key cannot be decoded",
+			);
+			MatchError::AssetIdConversionFailed
+		})?;
+		let amount =
+			Balance::try_from(*amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok(((collection, item), amount))
+	}
+}
+
+/// [`MatchesFungibles`] converters which can match a whole [`Assets`] bundle at once, sharing the
+/// work which doesn't depend on the individual asset.
+pub trait MatchesFungiblesBatch<AssetId, Balance>: MatchesFungibles<AssetId, Balance> {
+	/// Match all `assets`.
+	///
+	/// The results are in the order of `assets` and equal to the results of
+	/// [`MatchesFungibles::matches_fungibles`] for each asset, including the errors of assets
+	/// which are not matched.
+	fn matches_fungibles_batch(assets: &Assets) -> Vec<Result<(AssetId, Balance), MatchError>>;
+}
+
+/// Resolves the prefix of [`StartsWith`] once for the whole bundle.
+impl<
+		AssetId: Clone,
+		Balance: Clone,
+		Prefix: Get<Location>,
+		ConvertAssetId: MaybeEquivalence<Location, AssetId>,
+		ConvertBalance: MaybeEquivalence<u128, Balance>,
+	> MatchesFungiblesBatch<AssetId, Balance>
+	for MatchedConvertedConcreteId<
+		AssetId,
+		Balance,
+		StartsWith<Prefix>,
+		ConvertAssetId,
+		ConvertBalance,
+	>
+{
+	fn matches_fungibles_batch(assets: &Assets) -> Vec<Result<(AssetId, Balance), MatchError>> {
+		let prefix = Prefix::get();
+		assets
+			.inner()
+			.iter()
+			.map(|a| {
+				let (amount, id) = match (&a.fun, &a.id) {
+					(Fungibility::Fungible(amount), XcmAssetId(id)) if id.starts_with(&prefix) =>
+						(amount, id),
+					_ => return Err(MatchError::AssetNotHandled),
+				};
+				let what =
+					ConvertAssetId::convert(id).ok_or(MatchError::AssetIdConversionFailed)?;
+				let amount = ConvertBalance::convert(amount)
+					.ok_or(MatchError::AmountToBalanceConversionFailed)?;
+				Ok((what, amount))
+			})
+			.collect()
+	}
+}
+
+/// Converter which matches the relay chain native asset, `Location::parent()`, to the local asset
+/// `WrappedId` it is wrapped as.
+///
+/// Use this when relay tokens are teleported in and held as a local asset, e.g. of a
+/// `pallet_assets` instance. When they are instead reserve transferred and kept under their own
+/// location, e.g. as a foreign asset, use a converter matching the location directly.
+pub struct WrappedRelayConvertedConcreteId<WrappedId, Balance>(PhantomData<(WrappedId, Balance)>);
+impl<AssetId, WrappedId: Get<AssetId>, Balance: TryFrom<u128>> MatchesFungibles<AssetId, Balance>
+	for WrappedRelayConvertedConcreteId<WrappedId, Balance>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let amount = match (&a.fun, a.id.0.unpack()) {
+			(Fungibility::Fungible(amount), (1, [])) => *amount,
+			_ => return Err(MatchError::AssetNotHandled),
+		};
+		let amount =
+			Balance::try_from(amount).map_err(|_| MatchError::AmountToBalanceConversionFailed)?;
+		Ok((WrappedId::get(), amount))
+	}
+}
+
+/// Provides the factor the balance of an asset is scaled by, e.g. a storage map of the
+/// precisions of the assets.
+pub trait BalanceScale<AssetId, Balance> {
+	/// Get the factor the balance of `asset_id` is multiplied by.
+	///
+	/// Assets whose balances are not scaled return one.
+	fn scale_of(asset_id: &AssetId) -> Balance;
+}
+
+/// Converter which resolves the asset using `Inner` and then multiplies its balance by the
+/// factor of the asset from `ScaleProvider`.
+///
+/// This normalizes assets of different precisions to a common one, e.g. with a factor of
+/// `10^12` for a 6-decimal asset and of one for an 18-decimal asset, both are matched with 18
+/// decimals. Balances which overflow when scaled are rejected.
+pub struct ScaledBalanceConvertedConcreteId<Inner, ScaleProvider>(
+	PhantomData<(Inner, ScaleProvider)>,
+);
+impl<
+		AssetId: Debug,
+		Balance: CheckedMul,
+		Inner: MatchesFungibles<AssetId, Balance>,
+		ScaleProvider: BalanceScale<AssetId, Balance>,
+	> MatchesFungibles<AssetId, Balance> for ScaledBalanceConvertedConcreteId<Inner, ScaleProvider>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (asset_id, amount) = Inner::matches_fungibles(a)?;
+		match amount.checked_mul(&ScaleProvider::scale_of(&asset_id)) {
+			Some(amount) => Ok((asset_id, amount)),
+			None => {
+				log::trace!(
+					target: "xcm::matches_fungibles",
+					"ScaledBalanceConvertedConcreteId asset: {a:?} of {asset_id:?} overflows when scaled",
+				);
+				Err(MatchError::AmountToBalanceConversionFailed)
+			},
+		}
+	}
+}
+
+/// Converter which resolves assets whose location ends with [`Junction::OnlyChild`] as the asset
+/// at the parent location, delegating to `Inner`.
+///
+/// Some bridged or nested asset schemes append `OnlyChild` to the location of an asset, which
+/// neither the trust-backed nor the foreign assets matchers handle. Locations without a trailing
+/// `OnlyChild` are delegated unchanged.
+pub struct OnlyChildStrippingConvertedConcreteId<Inner>(PhantomData<Inner>);
+impl<AssetId, Balance, Inner: MatchesFungibles<AssetId, Balance>> MatchesFungibles<AssetId, Balance>
+	for OnlyChildStrippingConvertedConcreteId<Inner>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let (parent, Some(Junction::OnlyChild)) = a.id.0.clone().split_last_interior() else {
+			return Inner::matches_fungibles(a)
+		};
+		log::trace!(
+			target: "xcm::matches_fungibles",
+			"OnlyChildStrippingConvertedConcreteId asset: {a:?} matched as the asset at: {parent:?}",
+		);
+		Inner::matches_fungibles(&Asset { id: XcmAssetId(parent), fun: a.fun.clone() })
+	}
+}
+
+/// Converter which only delegates to `Inner` assets whose location has a
+/// [`Junction::PalletInstance`] contained in `AllowedInstances`.
+///
+/// On chains with several asset pallets under the same parent, this restricts `Inner` to the
+/// given pallet instances. Locations without a `PalletInstance` junction are not handled.
+pub struct PalletInstanceAllowListConvertedConcreteId<AllowedInstances, Inner>(
+	PhantomData<(AllowedInstances, Inner)>,
+);
+impl<
+		AssetId,
+		Balance,
+		AllowedInstances: Contains<u8>,
+		Inner: MatchesFungibles<AssetId, Balance>,
+	> MatchesFungibles<AssetId, Balance>
+	for PalletInstanceAllowListConvertedConcreteId<AllowedInstances, Inner>
+{
+	fn matches_fungibles(a: &Asset) -> Result<(AssetId, Balance), MatchError> {
+		let instance = a.id.0.interior().iter().find_map(|junction| match junction {
+			Junction::PalletInstance(instance) => Some(*instance),
+			_ => None,
+		});
+		match instance {
+			Some(instance) if AllowedInstances::contains(&instance) => Inner::matches_fungibles(a),
+			_ => {
+				log::trace!(
+					target: "xcm::matches_fungibles",
+					"PalletInstanceAllowListConvertedConcreteId asset: {a:?} has no allowed pallet instance",
+				);
+				Err(MatchError::AssetNotHandled)
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::TrustBackedAssetsConvertedConcreteId;
+	use frame_support::traits::{
+		tokens::{DepositConsequence, Fortitude, Preservation, Provenance, WithdrawConsequence},
+		Equals,
+	};
+	use xcm::latest::prelude::*;
+
+	/// Mock `fungibles::Inspect` where every asset has a `min_balance` of `asset id * 10`.
+	pub(crate) struct MockAssets;
+	impl fungibles::Inspect<u64> for MockAssets {
+		type AssetId = u32;
+		type Balance = u128;
+
+		fn total_issuance(_: u32) -> u128 {
+			unimplemented!()
+		}
+
+		fn minimum_balance(asset: u32) -> u128 {
+			asset as u128 * 10
+		}
+
+		fn total_balance(_: u32, _: &u64) -> u128 {
+			unimplemented!()
+		}
+
+		fn balance(_: u32, _: &u64) -> u128 {
+			unimplemented!()
+		}
+
+		fn reducible_balance(_: u32, _: &u64, _: Preservation, _: Fortitude) -> u128 {
+			unimplemented!()
+		}
+
+		fn can_deposit(_: u32, _: &u64, _: u128, _: Provenance) -> DepositConsequence {
+			unimplemented!()
+		}
+
+		fn can_withdraw(_: u32, _: &u64, _: u128) -> WithdrawConsequence<u128> {
+			unimplemented!()
+		}
+
+		fn asset_exists(_: u32) -> bool {
+			unimplemented!()
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub TrustBackedAssetsPalletLocation: Location = Location::new(0, [PalletInstance(50)]);
+	}
+
+	type TrustBackedConvert =
+		TrustBackedAssetsConvertedConcreteId<TrustBackedAssetsPalletLocation, u128>;
+
+	#[test]
+	fn min_balance_checked_converted_concrete_id_works() {
+		type Convert = MinBalanceCheckedConvertedConcreteId<TrustBackedConvert, MockAssets, u64>;
+
+		let asset = |amount: u128| -> Asset {
+			(Location::new(0, [PalletInstance(50), GeneralIndex(100)]), amount).into()
+		};
+
+		// above and exactly at min balance
+		assert_eq!(Convert::matches_fungibles(&asset(1001)), Ok((100, 1001)));
+		assert_eq!(Convert::matches_fungibles(&asset(1000)), Ok((100, 1000)));
+
+		// below min balance
+		assert_eq!(
+			Convert::matches_fungibles(&asset(999)),
+			Err(MatchError::AssetIdConversionFailed)
+		);
+
+		// errors of the inner converter are propagated
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(77), GeneralIndex(100)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn aliased_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub Canonical: Location = Location::new(0, [PalletInstance(50), GeneralIndex(100)]);
+			pub Reanchored: Location =
+				Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(100)]);
+			pub Legacy: Location = Location::new(0, [PalletInstance(51), GeneralIndex(1)]);
+		}
+		type Convert = AliasedConvertedConcreteId<
+			Canonical,
+			(Equals<Reanchored>, Equals<Legacy>),
+			TrustBackedConvert,
+		>;
+
+		// canonical location and both aliases match the same asset id
+		assert_eq!(Convert::matches_fungibles(&(Canonical::get(), 1000).into()), Ok((100, 1000)));
+		assert_eq!(Convert::matches_fungibles(&(Reanchored::get(), 1000).into()), Ok((100, 1000)));
+		assert_eq!(Convert::matches_fungibles(&(Legacy::get(), 1000).into()), Ok((100, 1000)));
+
+		// other locations are not normalized
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(51), GeneralIndex(2)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn general_key_decoding_converted_concrete_id_works() {
+		type Convert = GeneralKeyDecodingConvertedConcreteId<(u8, u32), u128>;
+
+		let asset = |key: &[u8]| -> Asset {
+			let mut data = [0u8; 32];
+			data[..key.len()].copy_from_slice(key);
+			let key = GeneralKey { length: key.len() as u8, data };
+			(Location::new(2, [GlobalConsensus(Ethereum { chain_id: 1 }), key]), 1000).into()
+		};
+
+		// valid key
+		assert_eq!(Convert::matches_fungibles(&asset(&[7, 1, 0, 0, 0])), Ok(((7, 1), 1000)));
+
+		// undecodable keys, too short and with trailing bytes
+		assert_eq!(
+			Convert::matches_fungibles(&asset(&[7, 1])),
+			Err(MatchError::AssetIdConversionFailed)
+		);
+		assert_eq!(
+			Convert::matches_fungibles(&asset(&[7, 1, 0, 0, 0, 0])),
+			Err(MatchError::AssetIdConversionFailed)
+		);
+
+		// location not ending in a `GeneralKey`
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50), GeneralIndex(1)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn symbol_converted_concrete_id_works() {
+		struct TestSymbols;
+		impl AssetsBySymbol<u32> for TestSymbols {
+			fn assets_by_symbol(symbol: &[u8]) -> Vec<u32> {
+				match symbol {
+					b"USDT" => vec![1984],
+					b"USDC" => vec![1337, 1338],
+					_ => vec![],
+				}
+			}
+		}
+		type Convert =
+			SymbolConvertedConcreteId<TrustBackedAssetsPalletLocation, TestSymbols, u32, u128>;
+
+		let asset = |symbol: &[u8]| -> Asset {
+			let mut data = [0u8; 32];
+			data[..symbol.len()].copy_from_slice(symbol);
+			let key = GeneralKey { length: symbol.len() as u8, data };
+			(Location::new(0, [PalletInstance(50), key]), 1000).into()
+		};
+
+		// unique symbol
+		assert_eq!(Convert::matches_fungibles(&asset(b"USDT")), Ok((1984, 1000)));
+
+		// unknown symbol
+		assert_eq!(Convert::matches_fungibles(&asset(b"DOT")), Err(MatchError::AssetNotHandled));
+
+		// ambiguous symbol
+		assert_eq!(
+			Convert::matches_fungibles(&asset(b"USDC")),
+			Err(MatchError::AssetIdConversionFailed)
+		);
+	}
+
+	#[test]
+	fn remapping_converted_concrete_id_works() {
+		struct TestRemapTable;
+		impl AssetIdRemapping<u32> for TestRemapTable {
+			fn remapped(asset_id: &u32) -> Option<u32> {
+				match asset_id {
+					100 => Some(200),
+					_ => None,
+				}
+			}
+		}
+		type Convert = RemappingConvertedConcreteId<TrustBackedConvert, TestRemapTable>;
+
+		let asset = |index: u128| -> Asset {
+			(Location::new(0, [PalletInstance(50), GeneralIndex(index)]), 1000).into()
+		};
+
+		// migrated id is replaced
+		assert_eq!(Convert::matches_fungibles(&asset(100)), Ok((200, 1000)));
+
+		// other ids pass through unchanged
+		assert_eq!(Convert::matches_fungibles(&asset(101)), Ok((101, 1000)));
+
+		// errors of the inner converter are propagated
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(77), GeneralIndex(100)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn pallet_instance_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub PalletLocation: Location = Location::new(0, [PalletInstance(13)]);
+			pub const PalletAssetId: u32 = 13;
+		}
+		type Convert = PalletInstanceConvertedConcreteId<PalletLocation, PalletAssetId>;
+
+		// bare pallet location
+		assert_eq!(
+			<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(
+				&(Location::new(0, [PalletInstance(13)]), 1000).into()
+			),
+			Ok((13, 1000))
+		);
+
+		// indexed location inside the pallet
+		assert_eq!(
+			<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(
+				&(Location::new(0, [PalletInstance(13), GeneralIndex(1)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+
+		// other pallet
+		assert_eq!(
+			<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn general_index_checked_converted_concrete_id_works() {
+		type Convert =
+			GeneralIndexCheckedConvertedConcreteId<TrustBackedAssetsPalletLocation, u32, u128>;
+
+		let asset = |index: u128| -> Asset {
+			(Location::new(0, [PalletInstance(50), GeneralIndex(index)]), 1000).into()
+		};
+
+		// index within range
+		assert_eq!(Convert::matches_fungibles(&asset(u32::MAX as u128)), Ok((u32::MAX, 1000)));
+
+		// index exceeding `u32::MAX`
+		assert_eq!(
+			Convert::matches_fungibles(&asset(u32::MAX as u128 + 1)),
+			Err(MatchError::AssetIdConversionFailed)
+		);
+
+		// other locations are not handled
+		assert_eq!(
+			Convert::matches_fungibles(&(Location::new(0, [PalletInstance(50)]), 1000).into()),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn unified_fungible_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub Native: Location = Location::new(1, [GlobalConsensus(Ethereum { chain_id: 1 })]);
+			pub Wrapped: Location =
+				Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(1)]);
+			pub Bridged: Location = Location::new(2, [GlobalConsensus(Kusama), Parachain(1000)]);
+			pub const CanonicalId: u32 = 1;
+		}
+		type Convert = UnifiedFungibleConvertedConcreteId<
+			(Equals<Native>, Equals<Wrapped>, Equals<Bridged>),
+			CanonicalId,
+			u128,
+		>;
+
+		// all members map to the same id
+		for member in [Native::get(), Wrapped::get(), Bridged::get()] {
+			assert_eq!(
+				<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(&(member, 1000).into()),
+				Ok((1, 1000))
+			);
+		}
+
+		// non-member is not handled
+		assert_eq!(
+			<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(
+				&(Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(2)]), 1000)
+					.into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn foreign_assets_fallback_converted_concrete_id_works() {
+		/// Matches the asset at `AssetLocation` with id `Id`.
+		struct MatchLocation<AssetLocation, Id>(PhantomData<(AssetLocation, Id)>);
+		impl<AssetLocation: Get<Location>, Id: Get<u32>> MatchesFungibles<u32, u128>
+			for MatchLocation<AssetLocation, Id>
+		{
+			fn matches_fungibles(a: &Asset) -> Result<(u32, u128), MatchError> {
+				match (&a.fun, &a.id.0) {
+					(Fungible(amount), location) if *location == AssetLocation::get() =>
+						Ok((Id::get(), *amount)),
+					_ => Err(MatchError::AssetNotHandled),
+				}
+			}
+		}
+
+		frame_support::parameter_types! {
+			pub Current: Location = Location::new(2, [GlobalConsensus(Kusama), Parachain(1000)]);
+			pub Legacy: Location = Location::new(
+				2,
+				[GlobalConsensus(ByGenesis([1; 32])), Parachain(1000)],
+			);
+			pub Older: Location = Location::new(1, [Parachain(1000)]);
+			pub const PrimaryId: u32 = 1;
+			pub const LegacyId: u32 = 2;
+			pub const OlderId: u32 = 3;
+		}
+		type Convert = ForeignAssetsFallbackConvertedConcreteId<
+			MatchLocation<Current, PrimaryId>,
+			(MatchLocation<Legacy, LegacyId>, MatchLocation<Older, OlderId>),
+		>;
+
+		// primary matches
+		assert_eq!(Convert::matches_fungibles(&(Current::get(), 1000).into()), Ok((1, 1000)));
+
+		// only a fallback matches
+		assert_eq!(Convert::matches_fungibles(&(Legacy::get(), 1000).into()), Ok((2, 1000)));
+		assert_eq!(Convert::matches_fungibles(&(Older::get(), 1000).into()), Ok((3, 1000)));
+
+		// nothing matches
+		assert_eq!(
+			Convert::matches_fungibles(&(Location::new(1, [Parachain(2000)]), 1000).into()),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn parents_bound_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub const MaxParents: u8 = 1;
+			pub const AssetId: u32 = 1;
+		}
+		type Convert = ParentsBoundConvertedConcreteId<
+			MaxParents,
+			UnifiedFungibleConvertedConcreteId<frame_support::traits::Everything, AssetId, u128>,
+		>;
+		let matches = |parents: u8| {
+			<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(
+				&(Location::new(parents, [Parachain(1000)]), 1000).into(),
+			)
+		};
+
+		// up to `MaxParents` parents are matched
+		assert_eq!(matches(0), Ok((1, 1000)));
+		assert_eq!(matches(1), Ok((1, 1000)));
+
+		// more than `MaxParents` parents are rejected
+		assert_eq!(matches(2), Err(MatchError::AssetNotHandled));
+	}
+
+	#[test]
+	fn namespaced_foreign_converted_concrete_id_works() {
+		type Convert = NamespacedForeignConvertedConcreteId<u128>;
+
+		// the same index under two networks yields distinct ids
+		let kusama_asset =
+			Location::new(2, [GlobalConsensus(Kusama), Parachain(1000), GeneralIndex(7)]);
+		let polkadot_asset =
+			Location::new(2, [GlobalConsensus(Polkadot), Parachain(1000), GeneralIndex(7)]);
+		assert_eq!(
+			Convert::matches_fungibles(&(kusama_asset, 1000).into()),
+			Ok(((Kusama, 7), 1000))
+		);
+		assert_eq!(
+			Convert::matches_fungibles(&(polkadot_asset, 1000).into()),
+			Ok(((Polkadot, 7), 1000))
+		);
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(2, [GlobalConsensus(Kusama), GeneralIndex(7)]), 1000).into()
+			),
+			Ok(((Kusama, 7), 1000))
+		);
+
+		// no network or no index
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(1, [Parachain(1000), GeneralIndex(7)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(2, [GlobalConsensus(Kusama), Parachain(1000)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn offset_asset_id_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub const Offset: u32 = 1_000_000;
+		}
+		type Convert = OffsetAssetIdConvertedConcreteId<TrustBackedConvert, Offset>;
+		let matches = |id: u128| {
+			<Convert as MatchesFungibles<u32, u128>>::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50), GeneralIndex(id)]), 1000).into(),
+			)
+		};
+
+		assert_eq!(matches(1_000_000), Ok((0, 1000)));
+		assert_eq!(matches(1_000_001), Ok((1, 1000)));
+
+		// ids below the offset underflow
+		assert_eq!(matches(999_999), Err(MatchError::AssetIdConversionFailed));
+		assert_eq!(matches(0), Err(MatchError::AssetIdConversionFailed));
+	}
+
+	#[test]
+	fn time_frozen_converted_concrete_id_works() {
+		thread_local! {
+			static BLOCK_NUMBER: core::cell::Cell<u32> = core::cell::Cell::new(0);
+		}
+		struct TestBlockNumber;
+		impl BlockNumberProvider for TestBlockNumber {
+			type BlockNumber = u32;
+
+			fn current_block_number() -> u32 {
+				BLOCK_NUMBER.with(|b| b.get())
+			}
+		}
+		frame_support::parameter_types! {
+			pub const FrozenUntil: u32 = 10;
+		}
+		type Convert =
+			TimeFrozenConvertedConcreteId<TrustBackedConvert, FrozenUntil, TestBlockNumber>;
+		let matches_at = |block: u32| {
+			BLOCK_NUMBER.with(|b| b.set(block));
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50), GeneralIndex(1)]), 1000).into(),
+			)
+		};
+
+		// frozen
+		assert_eq!(matches_at(0), Err(MatchError::AssetNotHandled));
+		assert_eq!(matches_at(9), Err(MatchError::AssetNotHandled));
+
+		// the freeze expired
+		assert_eq!(matches_at(10), Ok((1, 1000)));
+		assert_eq!(matches_at(11), Ok((1, 1000)));
+	}
+
+	#[test]
+	fn registry_backed_converted_concrete_id_works() {
+		#[frame_support::storage_alias]
+		type AssetRegistry = StorageMap<Registry, frame_support::Blake2_128Concat, Location, u32>;
+		type Convert = RegistryBackedConvertedConcreteId<AssetRegistry, u128>;
+
+		sp_io::TestExternalities::default().execute_with(|| {
+			let registered = Location::new(1, [Parachain(2000), GeneralIndex(7)]);
+			AssetRegistry::insert(&registered, 42);
+
+			// registered location
+			assert_eq!(Convert::matches_fungibles(&(registered, 1000).into()), Ok((42, 1000)));
+
+			// unregistered location
+			assert_eq!(
+				Convert::matches_fungibles(
+					&(Location::new(1, [Parachain(2000), GeneralIndex(8)]), 1000).into()
+				),
+				Err(MatchError::AssetNotHandled)
+			);
+		});
+	}
+
+	#[test]
+	fn canonicalizing_converted_concrete_id_works() {
+		fn key(bytes: &[u8], padding: u8) -> Junction {
+			let mut data = [padding; 32];
+			data[..bytes.len()].copy_from_slice(bytes);
+			GeneralKey { length: bytes.len() as u8, data }
+		}
+
+		frame_support::parameter_types! {
+			pub KeyedAsset: Location = Location::new(0, [PalletInstance(50), key(&[1, 2], 0)]);
+		}
+
+		/// Matches the location of `KeyedAsset` exactly.
+		struct KeyedConvert;
+		impl MatchesFungibles<u32, u128> for KeyedConvert {
+			fn matches_fungibles(a: &Asset) -> Result<(u32, u128), MatchError> {
+				match (&a.id.0, &a.fun) {
+					(location, Fungibility::Fungible(amount)) if *location == KeyedAsset::get() =>
+						Ok((1, *amount)),
+					_ => Err(MatchError::AssetNotHandled),
+				}
+			}
+		}
+
+		type Convert = CanonicalizingConvertedConcreteId<PalletInstanceBeforeKeys, KeyedConvert>;
+		let location = |junctions: Vec<Junction>| {
+			let mut location = Location::here();
+			for junction in junctions {
+				location.push_interior(junction).unwrap();
+			}
+			location
+		};
+		let matches = |junctions: Vec<Junction>| {
+			Convert::matches_fungibles(&(location(junctions), 1000).into())
+		};
+
+		// canonical encoding
+		assert_eq!(matches(vec![PalletInstance(50), key(&[1, 2], 0)]), Ok((1, 1000)));
+
+		// the bytes of the key beyond its length are ignored
+		assert_eq!(matches(vec![PalletInstance(50), key(&[1, 2], 0xff)]), Ok((1, 1000)));
+
+		// the bytes of the key within its length are significant
+		assert_eq!(
+			matches(vec![PalletInstance(50), key(&[1, 3], 0)]),
+			Err(MatchError::AssetNotHandled)
+		);
+		assert_eq!(
+			matches(vec![PalletInstance(50), key(&[1, 2, 0], 0)]),
+			Err(MatchError::AssetNotHandled)
+		);
+
+		// the pallet instance and the key are reordered, so both orders resolve to the same id
+		assert_eq!(matches(vec![key(&[1, 2], 0), PalletInstance(50)]), Ok((1, 1000)));
+		assert_eq!(matches(vec![key(&[1, 2], 0xff), PalletInstance(50)]), Ok((1, 1000)));
+
+		// junctions without a rank are not moved and separate the reordered runs
+		assert_eq!(
+			matches(vec![key(&[1, 2], 0), Parachain(1000), PalletInstance(50)]),
+			Err(MatchError::AssetNotHandled)
+		);
+
+		// without a configured order, junctions are not reordered
+		type Unordered = CanonicalizingConvertedConcreteId<(), KeyedConvert>;
+		assert_eq!(
+			Unordered::matches_fungibles(
+				&(location(vec![key(&[1, 2], 0), PalletInstance(50)]), 1000).into()
+			),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn pallet_instance_before_keys_keeps_relative_order_of_keys() {
+		type Convert = CanonicalizingConvertedConcreteId<PalletInstanceBeforeKeys, KeyOrderConvert>;
+
+		frame_support::parameter_types! {
+			pub OrderedAsset: Location =
+				Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(1), GeneralIndex(2)]);
+		}
+
+		/// Matches the location of `OrderedAsset` exactly.
+		struct KeyOrderConvert;
+		impl MatchesFungibles<u32, u128> for KeyOrderConvert {
+			fn matches_fungibles(a: &Asset) -> Result<(u32, u128), MatchError> {
+				match (&a.id.0, &a.fun) {
+					(location, Fungibility::Fungible(amount))
+						if *location == OrderedAsset::get() =>
+						Ok((2, *amount)),
+					_ => Err(MatchError::AssetNotHandled),
+				}
+			}
+		}
+
+		let matches = |junctions: [Junction; 4]| {
+			Convert::matches_fungibles(&(Location::new(1, junctions), 10).into())
+		};
+		assert_eq!(
+			matches([Parachain(1000), GeneralIndex(1), GeneralIndex(2), PalletInstance(50)]),
+			Ok((2, 10))
+		);
+		assert_eq!(
+			matches([Parachain(1000), GeneralIndex(1), PalletInstance(50), GeneralIndex(2)]),
+			Ok((2, 10))
+		);
+		// the keys keep their relative order
+		assert_eq!(
+			matches([Parachain(1000), GeneralIndex(2), PalletInstance(50), GeneralIndex(1)]),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn native_first_converted_concrete_id_works() {
+		thread_local! {
+			static INNER_CALLS: core::cell::Cell<u32> = core::cell::Cell::new(0);
+		}
+
+		/// Counts the calls before delegating to `TrustBackedConvert`.
+		struct CountingConvert;
+		impl MatchesFungibles<u32, u128> for CountingConvert {
+			fn matches_fungibles(a: &Asset) -> Result<(u32, u128), MatchError> {
+				INNER_CALLS.with(|c| c.set(c.get() + 1));
+				TrustBackedConvert::matches_fungibles(a)
+			}
+		}
+
+		frame_support::parameter_types! {
+			pub NativeLocation: Location = Location::parent();
+			pub const NativeId: u32 = 0;
+		}
+		type Convert = NativeFirstConvertedConcreteId<NativeLocation, NativeId, CountingConvert>;
+
+		// native is matched without consulting `Inner`
+		assert_eq!(Convert::matches_fungibles(&(Location::parent(), 1000).into()), Ok((0, 1000)));
+		assert_eq!(INNER_CALLS.with(|c| c.get()), 0);
+
+		// other assets are delegated
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50), GeneralIndex(7)]), 1000).into()
+			),
+			Ok((7, 1000))
+		);
+		assert_eq!(
+			Convert::matches_fungibles(&(Location::new(1, [Parachain(1000)]), 1000).into()),
+			Err(MatchError::AssetNotHandled)
+		);
+		assert_eq!(INNER_CALLS.with(|c| c.get()), 2);
+	}
+
+	#[test]
+	fn composite_id_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub CollectionsPalletLocation: Location = Location::new(0, [PalletInstance(50)]);
+		}
+		type Convert =
+			CompositeIdConvertedConcreteId<CollectionsPalletLocation, sp_runtime::traits::Identity>;
+		let key = |item: &[u8]| {
+			let mut data = [0u8; 32];
+			data[..item.len()].copy_from_slice(item);
+			GeneralKey { length: item.len() as u8, data }
+		};
+		let item_1: Location =
+			Location::new(0, [PalletInstance(50), GeneralIndex(7), key(b"item-1")]);
+		let item_2: Location =
+			Location::new(0, [PalletInstance(50), GeneralIndex(7), key(b"item-2")]);
+
+		// index based converters ignore the key, so both items match the collection's asset
+		assert_eq!(
+			TrustBackedConvert::matches_fungibles(&(item_1.clone(), 1000).into()),
+			Ok((7, 1000))
+		);
+		assert_eq!(
+			TrustBackedConvert::matches_fungibles(&(item_2.clone(), 1000).into()),
+			Ok((7, 1000))
+		);
+
+		// items of the same collection are distinguished
+		assert_eq!(
+			<Convert as MatchesFungibles<(u32, Vec<u8>), u128>>::matches_fungibles(
+				&(item_1, 1000).into()
+			),
+			Ok(((7, b"item-1".to_vec()), 1000))
+		);
+		assert_eq!(
+			<Convert as MatchesFungibles<(u32, Vec<u8>), u128>>::matches_fungibles(
+				&(item_2, 1000).into()
+			),
+			Ok(((7, b"item-2".to_vec()), 1000))
+		);
+
+		// other pallet, missing item and trailing junctions are not handled
+		for location in [
+			Location::new(0, [PalletInstance(51), GeneralIndex(7), key(b"item-1")]),
+			Location::new(0, [PalletInstance(50), GeneralIndex(7)]),
+			Location::new(
+				0,
+				[PalletInstance(50), GeneralIndex(7), key(b"item-1"), GeneralIndex(1)],
+			),
+		] {
+			assert_eq!(
+				<Convert as MatchesFungibles<(u32, Vec<u8>), u128>>::matches_fungibles(
+					&(location, 1000).into()
+				),
+				Err(MatchError::AssetNotHandled)
+			);
+		}
+
+		// collection out of range
+		assert_eq!(
+			<Convert as MatchesFungibles<(u32, Vec<u8>), u128>>::matches_fungibles(
+				&(
+					Location::new(
+						0,
+						[PalletInstance(50), GeneralIndex(u64::MAX.into()), key(b"a")]
+					),
+					1000
+				)
+					.into()
+			),
+			Err(MatchError::AssetIdConversionFailed)
+		);
+	}
+
+	#[test]
+	fn matches_fungibles_batch_works() {
+		let assets: Assets = vec![
+			(Location::new(0, [PalletInstance(50), GeneralIndex(1)]), 100).into(),
+			(Location::new(0, [PalletInstance(50), GeneralIndex(2)]), 200).into(),
+			// other pallet
+			(Location::new(0, [PalletInstance(51), GeneralIndex(1)]), 300).into(),
+			// missing `GeneralIndex`
+			(Location::new(0, [PalletInstance(50)]), 400).into(),
+			// non-fungible
+			(Location::new(0, [PalletInstance(50), GeneralIndex(3)]), Index(1)).into(),
+		]
+		.into();
+
+		let batch = TrustBackedConvert::matches_fungibles_batch(&assets);
+
+		// the results line up with the input and equal the per-asset results
+		let expected: Vec<_> =
+			assets.inner().iter().map(TrustBackedConvert::matches_fungibles).collect();
+		assert_eq!(batch, expected);
+		assert_eq!(batch.iter().filter(|result| result.is_ok()).count(), 2);
+		assert!(batch.contains(&Ok((1, 100))));
+		assert!(batch.contains(&Ok((2, 200))));
+		assert!(batch.contains(&Err(MatchError::AssetIdConversionFailed)));
+	}
+
+	#[test]
+	fn wrapped_relay_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub const WrappedRelayId: u32 = 42;
+		}
+		let matches = |a: &Asset| {
+			<WrappedRelayConvertedConcreteId<WrappedRelayId, u64> as MatchesFungibles<u32, u64>>::matches_fungibles(a)
+		};
+
+		// the relay native asset is matched to the wrapped id
+		assert_eq!(matches(&(Location::parent(), 1000).into()), Ok((42, 1000)));
+		assert_eq!(
+			matches(&(Location::parent(), u128::MAX).into()),
+			Err(MatchError::AmountToBalanceConversionFailed)
+		);
+
+		// anything else is not handled
+		for location in [
+			Location::here(),
+			Location::new(1, [Parachain(1000)]),
+			Location::new(2, [GlobalConsensus(Westend)]),
+			Location::new(0, [PalletInstance(50), GeneralIndex(42)]),
+		] {
+			assert_eq!(matches(&(location, 1000).into()), Err(MatchError::AssetNotHandled));
+		}
+		assert_eq!(
+			matches(&Asset { id: AssetId(Location::parent()), fun: NonFungible(Index(1)) }),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn scaled_balance_converted_concrete_id_works() {
+		/// Asset 6 has 6 decimals and asset 18 has 18, both are normalized to 18 decimals.
+		struct Decimals;
+		impl BalanceScale<u32, u128> for Decimals {
+			fn scale_of(asset_id: &u32) -> u128 {
+				10u128.pow(18 - asset_id)
+			}
+		}
+		type Convert = ScaledBalanceConvertedConcreteId<TrustBackedConvert, Decimals>;
+		let asset = |id: u128, amount: u128| -> Asset {
+			(Location::new(0, [PalletInstance(50), GeneralIndex(id)]), amount).into()
+		};
+
+		// one unit of each asset is matched to the same normalized balance
+		assert_eq!(Convert::matches_fungibles(&asset(6, 1_000_000)), Ok((6, 10u128.pow(18))));
+		assert_eq!(
+			Convert::matches_fungibles(&asset(18, 10u128.pow(18))),
+			Ok((18, 10u128.pow(18)))
+		);
+
+		// overflow when scaling is rejected
+		assert_eq!(
+			Convert::matches_fungibles(&asset(6, u128::MAX / 10u128.pow(11))),
+			Err(MatchError::AmountToBalanceConversionFailed)
+		);
+		assert_eq!(Convert::matches_fungibles(&asset(18, u128::MAX)), Ok((18, u128::MAX)));
+
+		// errors of `Inner` are propagated
+		assert_eq!(
+			Convert::matches_fungibles(&(Location::new(1, [Parachain(1000)]), 1000).into()),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+
+	#[test]
+	fn only_child_stripping_converted_concrete_id_works() {
+		type Convert = OnlyChildStrippingConvertedConcreteId<TrustBackedConvert>;
+
+		// a trailing `OnlyChild` resolves to the parent asset
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50), GeneralIndex(1), OnlyChild]), 1000).into()
+			),
+			Ok((1, 1000))
+		);
+		// locations without it are delegated unchanged
+		assert_eq!(
+			Convert::matches_fungibles(
+				&(Location::new(0, [PalletInstance(50), GeneralIndex(1)]), 1000).into()
+			),
+			Ok((1, 1000))
+		);
+		// only a single trailing `OnlyChild` is stripped
+		for location in [
+			Location::new(0, [PalletInstance(50), GeneralIndex(1), OnlyChild, OnlyChild]),
+			Location::new(0, [PalletInstance(50), OnlyChild, GeneralIndex(1)]),
+			Location::new(1, [Parachain(1000), OnlyChild]),
+		] {
+			assert!(Convert::matches_fungibles(&(location, 1000).into()).is_err());
+		}
+	}
+
+	#[test]
+	fn pallet_instance_allow_list_converted_concrete_id_works() {
+		frame_support::parameter_types! {
+			pub const AllowedInstance: u8 = 50;
+			pub const OtherInstance: u8 = 51;
+		}
+		let asset = |location: Location| -> Asset { (location, 1000).into() };
+		let trust_backed = Location::new(0, [PalletInstance(50), GeneralIndex(1)]);
+
+		// allowed pallet instance
+		type Allowed =
+			PalletInstanceAllowListConvertedConcreteId<Equals<AllowedInstance>, TrustBackedConvert>;
+		assert_eq!(Allowed::matches_fungibles(&asset(trust_backed.clone())), Ok((1, 1000)));
+		assert_eq!(
+			Allowed::matches_fungibles(&asset(Location::new(1, [Parachain(1000)]))),
+			Err(MatchError::AssetNotHandled)
+		);
+
+		// disallowed pallet instance
+		type Disallowed =
+			PalletInstanceAllowListConvertedConcreteId<Equals<OtherInstance>, TrustBackedConvert>;
+		assert_eq!(
+			Disallowed::matches_fungibles(&asset(trust_backed)),
+			Err(MatchError::AssetNotHandled)
+		);
+	}
+}
@@ -13,18 +13,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use codec::{Decode, Encode};
 use frame_support::traits::{
 	ContainsPair, EnsureOrigin, EnsureOriginWithArg, Everything, OriginTrait,
 };
 use pallet_xcm::{EnsureXcm, Origin as XcmOrigin};
+use sp_runtime::RuntimeDebug;
 use xcm::latest::Location;
 use xcm_executor::traits::ConvertLocation;
 
+/// Reasons why a location cannot be resolved to a foreign asset creator.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug, scale_info::TypeInfo)]
+pub enum ForeignCreatorError {
+	/// The origin location is not allowed to create the asset at the given location.
+	NotAllowed,
+	/// The origin location could not be converted into an account.
+	NotConvertible,
+}
+
 /// `EnsureOriginWithArg` impl for `CreateOrigin` that allows only XCM origins that are locations
 /// containing the class location.
 pub struct ForeignCreators<IsForeign, AccountOf, AccountId, L = Location>(
 	core::marker::PhantomData<(IsForeign, AccountOf, AccountId, L)>,
 );
+impl<
+		IsForeign: ContainsPair<L, L>,
+		AccountOf: ConvertLocation<AccountId>,
+		AccountId,
+		L: TryInto<Location> + Clone,
+	> ForeignCreators<IsForeign, AccountOf, AccountId, L>
+{
+	/// Resolve the account of the creator at `origin_location` for the asset at
+	/// `asset_location`.
+	pub fn resolve_creator(
+		origin_location: &L,
+		asset_location: &L,
+	) -> Result<AccountId, ForeignCreatorError> {
+		if !IsForeign::contains(asset_location, origin_location) {
+			return Err(ForeignCreatorError::NotAllowed)
+		}
+		let latest_location: Location = origin_location
+			.clone()
+			.try_into()
+			.map_err(|_| ForeignCreatorError::NotConvertible)?;
+		AccountOf::convert_location(&latest_location).ok_or(ForeignCreatorError::NotConvertible)
+	}
+}
+
 impl<
 		IsForeign: ContainsPair<L, L>,
 		AccountOf: ConvertLocation<AccountId>,
@@ -43,12 +78,7 @@ where
 		asset_location: &L,
 	) -> core::result::Result<Self::Success, RuntimeOrigin> {
 		let origin_location = EnsureXcm::<Everything, L>::try_origin(origin.clone())?;
-		if !IsForeign::contains(asset_location, &origin_location) {
-			return Err(origin)
-		}
-		let latest_location: Location =
-			origin_location.clone().try_into().map_err(|_| origin.clone())?;
-		AccountOf::convert_location(&latest_location).ok_or(origin)
+		Self::resolve_creator(&origin_location, asset_location).map_err(|_| origin)
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -57,3 +87,66 @@ where
 		Ok(pallet_xcm::Origin::Xcm(latest_location).into())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use xcm::latest::prelude::*;
+
+	/// Allows only the sibling parachain `1000` to create assets under its own location.
+	pub struct IsSibling1000;
+	impl ContainsPair<Location, Location> for IsSibling1000 {
+		fn contains(asset_location: &Location, origin_location: &Location) -> bool {
+			origin_location == &Location::new(1, [Parachain(1000)]) &&
+				asset_location.starts_with(origin_location)
+		}
+	}
+
+	/// Converts only parachain locations into accounts.
+	pub struct ParachainAccountOf;
+	impl ConvertLocation<u64> for ParachainAccountOf {
+		fn convert_location(location: &Location) -> Option<u64> {
+			match location.unpack() {
+				(1, [Parachain(id)]) => Some(*id as u64),
+				_ => None,
+			}
+		}
+	}
+
+	type Creators = ForeignCreators<IsSibling1000, ParachainAccountOf, u64>;
+
+	#[test]
+	fn resolve_creator_works() {
+		let sibling = Location::new(1, [Parachain(1000)]);
+		let asset = Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(1)]);
+		assert_eq!(Creators::resolve_creator(&sibling, &asset), Ok(1000));
+	}
+
+	#[test]
+	fn resolve_creator_not_allowed() {
+		let other_sibling = Location::new(1, [Parachain(2000)]);
+		let asset = Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(1)]);
+		assert_eq!(
+			Creators::resolve_creator(&other_sibling, &asset),
+			Err(ForeignCreatorError::NotAllowed)
+		);
+	}
+
+	#[test]
+	fn resolve_creator_not_convertible() {
+		pub struct AllowAll;
+		impl ContainsPair<Location, Location> for AllowAll {
+			fn contains(_: &Location, _: &Location) -> bool {
+				true
+			}
+		}
+		type Creators = ForeignCreators<AllowAll, ParachainAccountOf, u64>;
+
+		let account = Location::new(1, [AccountId32 { network: None, id: [1; 32] }]);
+		let asset = Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(1)]);
+		assert_eq!(
+			Creators::resolve_creator(&account, &asset),
+			Err(ForeignCreatorError::NotConvertible)
+		);
+	}
+}
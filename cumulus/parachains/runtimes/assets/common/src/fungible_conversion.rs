@@ -15,12 +15,15 @@
 
 //! Runtime API definition for assets.
 
-use crate::runtime_api::FungiblesAccessError;
+use crate::runtime_api::{AssetIdKind, FungiblesAccessError};
 use alloc::vec::Vec;
 use core::borrow::Borrow;
 use frame_support::traits::Contains;
 use sp_runtime::traits::MaybeEquivalence;
-use xcm::latest::{Asset, Location};
+use xcm::{
+	latest::{Asset, Location},
+	VersionedAssetId, VersionedLocation,
+};
 use xcm_builder::{ConvertedConcreteId, MatchedConvertedConcreteId};
 use xcm_executor::traits::MatchesFungibles;
 
@@ -146,6 +149,69 @@ pub fn convert_balance<T: frame_support::pallet_prelude::Get<Location>, Balance:
 	}
 }
 
+/// Helper function to resolve `location` to the local asset it is stored as.
+///
+/// The converters are tried in the order `TrustBacked`, `Foreign`, `Pool`. Only the location is
+/// matched, the amount of the probed asset is irrelevant.
+pub fn location_to_asset_id<Balance, TrustBacked, Foreign, Pool>(
+	location: VersionedLocation,
+) -> Option<AssetIdKind>
+where
+	TrustBacked: MatchesFungibles<u32, Balance>,
+	Foreign: MatchesFungibles<Location, Balance>,
+	Pool: MatchesFungibles<u32, Balance>,
+{
+	let location: Location = location.try_into().ok()?;
+	let asset: Asset = (location, 1u128).into();
+	if let Ok((id, _)) = TrustBacked::matches_fungibles(&asset) {
+		return Some(AssetIdKind::TrustBacked(id))
+	}
+	if let Ok((id, _)) = Foreign::matches_fungibles(&asset) {
+		return Some(AssetIdKind::Foreign(id.into()))
+	}
+	Pool::matches_fungibles(&asset).ok().map(|(id, _)| AssetIdKind::Pool(id))
+}
+
+/// Helper function to collect the ids of all sufficient assets of the `pallet_assets` instance
+/// `I`.
+///
+/// Should only be used in runtime APIs since it iterates over the whole `pallet_assets::Asset`
+/// map. Assets whose id can not be converted back to a `Location` are skipped.
+pub fn sufficient_asset_ids<T, I, ConvertAssetId>() -> Vec<xcm::latest::AssetId>
+where
+	T: pallet_assets::Config<I>,
+	I: 'static,
+	ConvertAssetId: MaybeEquivalence<Location, T::AssetId>,
+{
+	pallet_assets::Asset::<T, I>::iter()
+		.filter(|(_, details)| details.is_sufficient)
+		.filter_map(|(id, _)| ConvertAssetId::convert_back(&id))
+		.map(xcm::latest::AssetId)
+		.collect()
+}
+
+/// Helper function to build the response of `FeePaymentAssetsApi::query_acceptable_payment_assets`
+/// from the `asset_ids` accepted for fee payment.
+///
+/// Duplicates are dropped, keeping the first occurrence. As in
+/// `pallet_xcm::Pallet::query_acceptable_payment_assets`, assets which can not be expressed in
+/// `xcm_version` are left out.
+pub fn acceptable_payment_assets(
+	xcm_version: xcm::Version,
+	asset_ids: impl IntoIterator<Item = xcm::latest::AssetId>,
+) -> Result<Vec<VersionedAssetId>, FungiblesAccessError> {
+	let mut unique = Vec::new();
+	for asset_id in asset_ids {
+		if !unique.contains(&asset_id) {
+			unique.push(asset_id);
+		}
+	}
+	Ok(unique
+		.into_iter()
+		.filter_map(|asset_id| VersionedAssetId::from(asset_id).into_version(xcm_version).ok())
+		.collect())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -193,4 +259,22 @@ mod tests {
 
 		assert_eq!(convert::<_, _, _, _, Converter>(data.iter()), Ok(expected_data));
 	}
+
+	#[test]
+	fn acceptable_payment_assets_deduplicates_and_converts() {
+		let first = AssetId(Location::new(1, [Parachain(1000)]));
+		let second = AssetId(Location::new(0, [PalletInstance(50), GeneralIndex(1)]));
+
+		assert_eq!(
+			acceptable_payment_assets(
+				4,
+				[first.clone(), second.clone(), first.clone(), second.clone()]
+			),
+			Ok(vec![
+				VersionedAssetId::from(first).into_version(4).unwrap(),
+				VersionedAssetId::from(second).into_version(4).unwrap(),
+			])
+		);
+		assert_eq!(acceptable_payment_assets(4, []), Ok(vec![]));
+	}
 }
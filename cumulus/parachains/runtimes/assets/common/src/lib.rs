@@ -17,6 +17,7 @@
 
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarks;
+pub mod converters;
 pub mod foreign_creators;
 pub mod fungible_conversion;
 pub mod local_and_foreign_assets;
@@ -29,9 +30,9 @@ use crate::matching::{LocalLocationPattern, ParentLocation};
 use alloc::vec::Vec;
 use codec::{Decode, EncodeLike};
 use core::{cmp::PartialEq, marker::PhantomData};
-use frame_support::traits::{Equals, EverythingBut};
+use frame_support::traits::{Contains, Equals, EverythingBut};
 use parachains_common::{AssetIdForTrustBackedAssets, CollectionId, ItemId};
-use sp_runtime::traits::TryConvertInto;
+use sp_runtime::traits::{MaybeEquivalence, TryConvertInto};
 use xcm::prelude::*;
 use xcm_builder::{
 	AsPrefixedGeneralIndex, MatchedConvertedConcreteId, StartsWith, WithLatestLocationConverter,
@@ -123,6 +124,51 @@ pub type ForeignAssetsConvertedConcreteId<
 	BalanceConverter,
 >;
 
+/// Reason why [`ForeignAssetsConvertedConcreteId`] does or does not match a `Location`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchExplanation {
+	/// The location is the parent (relay chain) or a local location.
+	ExcludedAsLocal,
+	/// The location is excluded by `AdditionalLocationExclusionFilter`.
+	WrongPrefix,
+	/// The location passed the filters, but could not be converted to the `AssetId`.
+	FailedIdConversion,
+	/// The location is matched.
+	Ok,
+}
+
+/// Debugging helper which explains the matching of [`ForeignAssetsConvertedConcreteId`]
+/// configured with the same `AdditionalLocationExclusionFilter`, `AssetId` and
+/// `LocationToAssetIdConverter`.
+pub struct ForeignAssetsMatchExplainer<
+	AdditionalLocationExclusionFilter,
+	AssetId,
+	LocationToAssetIdConverter = WithLatestLocationConverter<AssetId>,
+>(PhantomData<(AdditionalLocationExclusionFilter, AssetId, LocationToAssetIdConverter)>);
+impl<
+		AdditionalLocationExclusionFilter: Contains<Location>,
+		AssetId,
+		LocationToAssetIdConverter: MaybeEquivalence<Location, AssetId>,
+	>
+	ForeignAssetsMatchExplainer<AdditionalLocationExclusionFilter, AssetId, LocationToAssetIdConverter>
+{
+	/// Returns the first check (in the order used by [`ForeignAssetsConvertedConcreteId`]) which
+	/// rejects `location`, or [`MatchExplanation::Ok`] if there is none.
+	pub fn explain_match(location: &Location) -> MatchExplanation {
+		if Equals::<ParentLocation>::contains(location) ||
+			StartsWith::<LocalLocationPattern>::contains(location)
+		{
+			MatchExplanation::ExcludedAsLocal
+		} else if AdditionalLocationExclusionFilter::contains(location) {
+			MatchExplanation::WrongPrefix
+		} else if LocationToAssetIdConverter::convert(location).is_none() {
+			MatchExplanation::FailedIdConversion
+		} else {
+			MatchExplanation::Ok
+		}
+	}
+}
+
 pub type AssetIdForPoolAssets = u32;
 
 /// `Location` vs `AssetIdForPoolAssets` converter for `PoolAssets`.
@@ -138,6 +184,22 @@ pub type PoolAssetsConvertedConcreteId<PoolAssetsPalletLocation, Balance> =
 		TryConvertInto,
 	>;
 
+/// [`MatchedConvertedConcreteId`] converter dedicated for `PoolAssets`,
+/// it is a similar implementation to `PoolAssetsConvertedConcreteId`,
+/// but it converts `AssetId` to `xcm::v*::Location` type instead of `AssetIdForPoolAssets = u32`
+pub type PoolAssetsAsLocation<
+	PoolAssetsPalletLocation,
+	Balance,
+	L,
+	LocationConverter = WithLatestLocationConverter<L>,
+> = MatchedConvertedConcreteId<
+	L,
+	Balance,
+	StartsWith<PoolAssetsPalletLocation>,
+	LocationConverter,
+	TryConvertInto,
+>;
+
 /// Adapter implementation for accessing pools (`pallet_asset_conversion`) that uses `AssetKind` as
 /// a `xcm::v*` which could be different from the `xcm::latest`.
 pub struct PoolAdapter<Runtime>(PhantomData<Runtime>);
@@ -208,7 +270,6 @@ impl<
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use sp_runtime::traits::MaybeEquivalence;
 	use xcm_builder::{StartsWithExplicitGlobalConsensus, WithLatestLocationConverter};
 	use xcm_executor::traits::{Error as MatchError, MatchesFungibles};
 
@@ -324,6 +385,52 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn pool_assets_as_location_works() {
+		frame_support::parameter_types! {
+			pub PoolAssetsPalletLocation: Location = Location::new(0, [PalletInstance(55)]);
+		}
+		// set up a converter which uses `xcm::v4::Location` under the hood
+		type Convert = PoolAssetsAsLocation<PoolAssetsPalletLocation, u128, xcm::v4::Location>;
+
+		let test_data = vec![
+			// other pallet
+			(
+				ma_1000(0, [PalletInstance(50), GeneralIndex(1)].into()),
+				Err(MatchError::AssetNotHandled),
+			),
+			(
+				ma_1000(1, [PalletInstance(55), GeneralIndex(1)].into()),
+				Err(MatchError::AssetNotHandled),
+			),
+			// ok
+			(
+				ma_1000(0, [PalletInstance(55), GeneralIndex(1)].into()),
+				Ok((
+					xcm::v4::Location::new(
+						0,
+						[xcm::v4::Junction::PalletInstance(55), xcm::v4::Junction::GeneralIndex(1)],
+					),
+					1000,
+				)),
+			),
+		];
+
+		for (asset, expected_result) in test_data {
+			let matched =
+				<Convert as MatchesFungibles<xcm::v4::Location, u128>>::matches_fungibles(&asset);
+			assert_eq!(matched, expected_result, "asset: {:?}", asset);
+
+			// the matched location converts back to the original one
+			if let Ok((location, _)) = matched {
+				assert_eq!(
+					WithLatestLocationConverter::<xcm::v4::Location>::convert_back(&location),
+					Some(asset.id.0)
+				);
+			}
+		}
+	}
+
 	#[test]
 	fn foreign_assets_converted_concrete_id_converter_works() {
 		frame_support::parameter_types! {
@@ -462,6 +569,76 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn foreign_assets_match_explainer_works() {
+		frame_support::parameter_types! {
+			pub Parachain100Pattern: Location = Location::new(1, [Parachain(100)]);
+			pub Parachain200Pattern: Location = Location::new(1, [Parachain(200)]);
+			pub UniversalLocationNetworkId: NetworkId = NetworkId::ByGenesis([9; 32]);
+		}
+
+		type Filter = (
+			StartsWith<Parachain100Pattern>,
+			StartsWithExplicitGlobalConsensus<UniversalLocationNetworkId>,
+		);
+		// same setup as `foreign_assets_converted_concrete_id_converter_works`
+		type Explainer = ForeignAssetsMatchExplainer<
+			Filter,
+			xcm::v4::Location,
+			WithLatestLocationConverter<xcm::v4::Location>,
+		>;
+		// converter which only accepts `GeneralIndex` assets of `Parachain(200)`
+		type IndexExplainer = ForeignAssetsMatchExplainer<
+			Filter,
+			u32,
+			AsPrefixedGeneralIndex<Parachain200Pattern, u32, TryConvertInto>,
+		>;
+
+		let test_data = vec![
+			// excluded as local
+			(Location::new(0, Here), MatchExplanation::ExcludedAsLocal),
+			(Location::new(0, [Parachain(100)]), MatchExplanation::ExcludedAsLocal),
+			// excluded as parent
+			(Location::new(1, Here), MatchExplanation::ExcludedAsLocal),
+			// excluded as additional filter
+			(Location::new(1, [Parachain(100)]), MatchExplanation::WrongPrefix),
+			(Location::new(1, [Parachain(100), GeneralIndex(1234)]), MatchExplanation::WrongPrefix),
+			(
+				Location::new(2, [GlobalConsensus(NetworkId::ByGenesis([9; 32]))]),
+				MatchExplanation::WrongPrefix,
+			),
+			// ok
+			(Location::new(1, [Parachain(200)]), MatchExplanation::Ok),
+			(
+				Location::new(2, [GlobalConsensus(NetworkId::ByGenesis([7; 32]))]),
+				MatchExplanation::Ok,
+			),
+		];
+		for (location, expected) in test_data {
+			assert_eq!(Explainer::explain_match(&location), expected, "location: {:?}", location);
+		}
+
+		assert_eq!(
+			IndexExplainer::explain_match(&Location::new(1, [Parachain(200)])),
+			MatchExplanation::FailedIdConversion
+		);
+		assert_eq!(
+			IndexExplainer::explain_match(&Location::new(
+				2,
+				[GlobalConsensus(NetworkId::ByGenesis([7; 32]))]
+			)),
+			MatchExplanation::FailedIdConversion
+		);
+		assert_eq!(
+			IndexExplainer::explain_match(&Location::new(1, [Parachain(100), GeneralIndex(1)])),
+			MatchExplanation::WrongPrefix
+		);
+		assert_eq!(
+			IndexExplainer::explain_match(&Location::new(1, [Parachain(200), GeneralIndex(1)])),
+			MatchExplanation::Ok
+		);
+	}
+
 	// Create Asset
 	fn ma_1000(parents: u8, interior: Junctions) -> Asset {
 		(Location::new(parents, interior), 1000).into()
@@ -14,13 +14,13 @@
 // limitations under the License.
 
 use core::marker::PhantomData;
-use frame_support::traits::Get;
+use frame_support::traits::{tokens::fungibles, Get};
 use sp_runtime::{
 	traits::{Convert, MaybeEquivalence},
 	Either,
 	Either::{Left, Right},
 };
-use xcm::latest::Location;
+use xcm::latest::{AssetId, Location};
 
 /// Converts a given [`Location`] to [`Either::Left`] when equal to `Target`, or
 /// [`Either::Right`] otherwise.
@@ -52,3 +52,136 @@ where
 		}
 	}
 }
+
+/// Resolves the decimals of an asset, e.g. for wallets to display its amounts.
+///
+/// The asset is classified with `Criterion`, the same as for
+/// [`frame_support::traits::tokens::fungibles::UnionOf`], and the metadata is read from
+/// `LocalAssets` for [`Either::Left`] and from `ForeignAssets` for [`Either::Right`].
+pub struct AssetDecimals<Criterion, LocalAssets, ForeignAssets, AccountId>(
+	PhantomData<(Criterion, LocalAssets, ForeignAssets, AccountId)>,
+);
+impl<Criterion, LocalAssets, ForeignAssets, AccountId>
+	AssetDecimals<Criterion, LocalAssets, ForeignAssets, AccountId>
+where
+	Criterion: Convert<Location, Either<LocalAssets::AssetId, Location>>,
+	LocalAssets: fungibles::metadata::Inspect<AccountId>,
+	ForeignAssets: fungibles::metadata::Inspect<AccountId, AssetId = Location>,
+{
+	/// Returns the decimals of the asset `id`, or `None` if the asset doesn't exist.
+	pub fn decimals_for(id: &AssetId) -> Option<u8> {
+		match Criterion::convert(id.0.clone()) {
+			Left(local) =>
+				LocalAssets::asset_exists(local.clone()).then(|| LocalAssets::decimals(local)),
+			Right(foreign) => ForeignAssets::asset_exists(foreign.clone())
+				.then(|| ForeignAssets::decimals(foreign)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::AssetIdForTrustBackedAssetsConvert;
+	use frame_support::traits::tokens::{
+		AssetId as AssetIdT, DepositConsequence, Fortitude, Preservation, Provenance,
+		WithdrawConsequence,
+	};
+	use xcm::latest::prelude::*;
+
+	/// Mock assets with metadata, where `Known` lists the existing assets and their decimals.
+	struct MockMetadata<Id, Known>(PhantomData<(Id, Known)>);
+	impl<Id: AssetIdT, Known: Get<Vec<(Id, u8)>>> fungibles::Inspect<u64> for MockMetadata<Id, Known> {
+		type AssetId = Id;
+		type Balance = u128;
+
+		fn total_issuance(_: Id) -> u128 {
+			unimplemented!()
+		}
+
+		fn minimum_balance(_: Id) -> u128 {
+			unimplemented!()
+		}
+
+		fn total_balance(_: Id, _: &u64) -> u128 {
+			unimplemented!()
+		}
+
+		fn balance(_: Id, _: &u64) -> u128 {
+			unimplemented!()
+		}
+
+		fn reducible_balance(_: Id, _: &u64, _: Preservation, _: Fortitude) -> u128 {
+			unimplemented!()
+		}
+
+		fn can_deposit(_: Id, _: &u64, _: u128, _: Provenance) -> DepositConsequence {
+			unimplemented!()
+		}
+
+		fn can_withdraw(_: Id, _: &u64, _: u128) -> WithdrawConsequence<u128> {
+			unimplemented!()
+		}
+
+		fn asset_exists(asset: Id) -> bool {
+			Known::get().iter().any(|(id, _)| *id == asset)
+		}
+	}
+	impl<Id: AssetIdT, Known: Get<Vec<(Id, u8)>>> fungibles::metadata::Inspect<u64>
+		for MockMetadata<Id, Known>
+	{
+		fn name(_: Id) -> Vec<u8> {
+			unimplemented!()
+		}
+
+		fn symbol(_: Id) -> Vec<u8> {
+			unimplemented!()
+		}
+
+		fn decimals(asset: Id) -> u8 {
+			Known::get()
+				.into_iter()
+				.find_map(|(id, decimals)| (id == asset).then_some(decimals))
+				.unwrap_or(0)
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub TrustBackedAssetsPalletLocation: Location = Location::new(0, [PalletInstance(50)]);
+		pub KnownLocalAssets: Vec<(u32, u8)> = vec![(1, 6)];
+		pub KnownForeignAssets: Vec<(Location, u8)> =
+			vec![(Location::new(1, [Parachain(2000)]), 18)];
+	}
+
+	#[test]
+	fn asset_decimals_works() {
+		type Decimals = AssetDecimals<
+			LocalFromLeft<AssetIdForTrustBackedAssetsConvert<TrustBackedAssetsPalletLocation>, u32>,
+			MockMetadata<u32, KnownLocalAssets>,
+			MockMetadata<Location, KnownForeignAssets>,
+			u64,
+		>;
+
+		// local asset
+		assert_eq!(
+			Decimals::decimals_for(&AssetId(Location::new(
+				0,
+				[PalletInstance(50), GeneralIndex(1)]
+			))),
+			Some(6)
+		);
+
+		// foreign asset
+		assert_eq!(Decimals::decimals_for(&AssetId(Location::new(1, [Parachain(2000)]))), Some(18));
+
+		// unknown local and foreign assets
+		assert_eq!(
+			Decimals::decimals_for(&AssetId(Location::new(
+				0,
+				[PalletInstance(50), GeneralIndex(2)]
+			))),
+			None
+		);
+		assert_eq!(Decimals::decimals_for(&AssetId(Location::new(1, [Parachain(2001)]))), None);
+	}
+}
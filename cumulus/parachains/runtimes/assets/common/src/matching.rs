@@ -13,11 +13,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use cumulus_primitives_core::ParaId;
+use cumulus_primitives_core::{IsSystem, ParaId};
 use frame_support::{
 	pallet_prelude::Get,
 	traits::{Contains, ContainsPair},
 };
+use pallet_asset_conversion::PoolLocator;
+use sp_runtime::traits::MaybeEquivalence;
 use xcm::prelude::*;
 
 use xcm_builder::ensure_is_remote;
@@ -142,6 +144,140 @@ impl<AssetsAllowedNetworks: Contains<Location>, OriginLocation: Get<Location>>
 	}
 }
 
+/// Accepts a `Location` if it resolves to an `is_sufficient` asset of the `pallet_assets`
+/// instance `AssetsInstance`.
+///
+/// `AssetIdConverter` resolves the local asset id from the `Location`.
+pub struct IsSufficientAsset<Runtime, AssetsInstance, AssetIdConverter>(
+	core::marker::PhantomData<(Runtime, AssetsInstance, AssetIdConverter)>,
+);
+impl<
+		Runtime: pallet_assets::Config<AssetsInstance>,
+		AssetsInstance: 'static,
+		AssetIdConverter: MaybeEquivalence<Location, Runtime::AssetId>,
+	> Contains<Location> for IsSufficientAsset<Runtime, AssetsInstance, AssetIdConverter>
+{
+	fn contains(location: &Location) -> bool {
+		AssetIdConverter::convert(location)
+			.and_then(pallet_assets::Asset::<Runtime, AssetsInstance>::get)
+			.map_or(false, |details| details.is_sufficient)
+	}
+}
+
+/// Accepts a `Location` if there is a `pallet_asset_conversion` pool pairing it with `Target`.
+pub struct IsPooledWith<Runtime, Target, L = Location>(
+	core::marker::PhantomData<(Runtime, Target, L)>,
+);
+impl<
+		Runtime: pallet_asset_conversion::Config<AssetKind = L>,
+		Target: Get<Location>,
+		L: TryFrom<Location>,
+	> Contains<Location> for IsPooledWith<Runtime, Target, L>
+{
+	fn contains(location: &Location) -> bool {
+		let (Ok(target), Ok(asset)) = (L::try_from(Target::get()), L::try_from(location.clone()))
+		else {
+			return false;
+		};
+		Runtime::PoolLocator::pool_id(&target, &asset)
+			.map_or(false, pallet_asset_conversion::Pools::<Runtime>::contains_key)
+	}
+}
+
+/// Checks whether an [`AssetId`] can be used to pay for XCM fees.
+///
+/// Accepts the `NativeLocation` asset, any asset matched by `SufficientAssets` and any asset
+/// matched by `PooledAssets`. Runtimes should configure it with the same assets they report from
+/// `XcmPaymentApi::query_acceptable_payment_assets`, so that a barrier can cheaply reject
+/// messages offering unacceptable fee assets.
+pub struct AcceptableFeeAssets<NativeLocation, SufficientAssets, PooledAssets = ()>(
+	core::marker::PhantomData<(NativeLocation, SufficientAssets, PooledAssets)>,
+);
+impl<
+		NativeLocation: Get<Location>,
+		SufficientAssets: Contains<Location>,
+		PooledAssets: Contains<Location>,
+	> AcceptableFeeAssets<NativeLocation, SufficientAssets, PooledAssets>
+{
+	/// Returns `true` if `id` can be used to pay for XCM fees.
+	pub fn is_acceptable_fee_asset(id: &AssetId) -> bool {
+		let location = &id.0;
+		let acceptable = NativeLocation::get().eq(location) ||
+			SufficientAssets::contains(location) ||
+			PooledAssets::contains(location);
+		log::trace!(
+			target: "xcm::contains",
+			"AcceptableFeeAssets id: {id:?}, acceptable: {acceptable}",
+		);
+		acceptable
+	}
+}
+impl<
+		NativeLocation: Get<Location>,
+		SufficientAssets: Contains<Location>,
+		PooledAssets: Contains<Location>,
+	> Contains<AssetId> for AcceptableFeeAssets<NativeLocation, SufficientAssets, PooledAssets>
+{
+	fn contains(id: &AssetId) -> bool {
+		Self::is_acceptable_fee_asset(id)
+	}
+}
+
+/// Checks whether an [`AssetId`] can be teleported.
+///
+/// Accepts any asset whose location is contained in the runtime-configured `AllowList`, e.g.
+/// `Equals<ParentLocation>` for the relay chain token. Runtimes should configure it with the same
+/// assets they accept in their `IsTeleporter` configuration, so that barriers and other code can
+/// consult a single source. Trust-backed and foreign assets are transferred using reserves and
+/// are usually not part of the allow-list.
+pub struct TeleportableAssets<AllowList>(core::marker::PhantomData<AllowList>);
+impl<AllowList: Contains<Location>> TeleportableAssets<AllowList> {
+	/// Returns `true` if `id` can be teleported.
+	pub fn is_teleportable(id: &AssetId) -> bool {
+		let teleportable = AllowList::contains(&id.0);
+		log::trace!(
+			target: "xcm::contains",
+			"TeleportableAssets id: {id:?}, teleportable: {teleportable}",
+		);
+		teleportable
+	}
+}
+impl<AllowList: Contains<Location>> Contains<AssetId> for TeleportableAssets<AllowList> {
+	fn contains(id: &AssetId) -> bool {
+		Self::is_teleportable(id)
+	}
+}
+
+/// Matches the location of a sibling system parachain, i.e. `Location::new(1, [Parachain(id)])`
+/// with `id` below `2000`.
+///
+/// Meant to be slotted into the `AdditionalLocationExclusionFilter` of
+/// [`crate::ForeignAssetsConvertedConcreteId`] on system parachains, so that the native tokens of
+/// sibling system parachains are never treated as foreign assets. Assets of sibling system
+/// parachains, e.g. `Location::new(1, [Parachain(1000), PalletInstance(50), GeneralIndex(1)])`,
+/// are not matched.
+pub struct IsSiblingSystemParachain;
+impl Contains<Location> for IsSiblingSystemParachain {
+	fn contains(location: &Location) -> bool {
+		matches!(location.unpack(), (1, [Parachain(id)]) if ParaId::from(*id).is_system())
+	}
+}
+
+/// Matches any location anchored at `GlobalConsensus(NetworkGet::get())`, i.e. whose interior
+/// starts with that junction, regardless of the number of parents and of the depth below it.
+///
+/// Meant to be slotted into the filters of [`crate::ForeignAssetsConvertedConcreteId`] to include
+/// or exclude all assets of a remote consensus system.
+pub struct StartsWithGlobalConsensus<NetworkGet>(core::marker::PhantomData<NetworkGet>);
+impl<NetworkGet: Get<NetworkId>> Contains<Location> for StartsWithGlobalConsensus<NetworkGet> {
+	fn contains(location: &Location) -> bool {
+		matches!(
+			location.unpack(),
+			(_, [GlobalConsensus(network), ..]) if *network == NetworkGet::get()
+		)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -231,4 +367,92 @@ mod tests {
 		let origin: Location = (Parent, Parent, GlobalConsensus(Polkadot), Parachain(1000)).into();
 		assert!(!FromNetwork::<UniversalLocation, ExpectedNetworkId>::contains(&asset, &origin));
 	}
+
+	#[test]
+	fn acceptable_fee_assets_works() {
+		parameter_types! {
+			pub NativeLocation: Location = Location::parent();
+			pub SufficientLocation: Location = Location::new(0, [PalletInstance(50), GeneralIndex(1)]);
+		}
+		type SufficientAssets = frame_support::traits::Equals<SufficientLocation>;
+		type Acceptable = AcceptableFeeAssets<NativeLocation, SufficientAssets>;
+
+		// sufficient asset is accepted
+		assert!(Acceptable::is_acceptable_fee_asset(&AssetId(SufficientLocation::get())));
+
+		// native asset is accepted
+		assert!(Acceptable::is_acceptable_fee_asset(&AssetId(Location::parent())));
+
+		// non-sufficient asset is rejected
+		assert!(!Acceptable::is_acceptable_fee_asset(&AssetId(Location::new(
+			0,
+			[PalletInstance(50), GeneralIndex(2)]
+		))));
+	}
+
+	#[test]
+	fn teleportable_assets_works() {
+		type Teleportable = TeleportableAssets<frame_support::traits::Equals<ParentLocation>>;
+
+		// relay chain token is teleportable
+		assert!(Teleportable::is_teleportable(&AssetId(Location::parent())));
+
+		// trust-backed asset is not teleportable
+		assert!(!Teleportable::is_teleportable(&AssetId(Location::new(
+			0,
+			[PalletInstance(50), GeneralIndex(1)]
+		))));
+	}
+
+	#[test]
+	fn is_sibling_system_parachain_works() {
+		// system parachains
+		assert!(IsSiblingSystemParachain::contains(&Location::new(1, [Parachain(1000)])));
+		assert!(IsSiblingSystemParachain::contains(&Location::new(1, [Parachain(1999)])));
+
+		// first non-system parachain
+		assert!(!IsSiblingSystemParachain::contains(&Location::new(1, [Parachain(2000)])));
+
+		// assets of system parachains, local parachains and the relay chain are not matched
+		assert!(!IsSiblingSystemParachain::contains(&Location::new(
+			1,
+			[Parachain(1000), PalletInstance(50), GeneralIndex(1)]
+		)));
+		assert!(!IsSiblingSystemParachain::contains(&Location::new(0, [Parachain(1000)])));
+		assert!(!IsSiblingSystemParachain::contains(&Location::parent()));
+	}
+
+	#[test]
+	fn starts_with_global_consensus_works() {
+		parameter_types! {
+			pub RemoteNetwork: NetworkId = ByGenesis(WESTEND_GENESIS_HASH);
+		}
+		type IsRemote = StartsWithGlobalConsensus<RemoteNetwork>;
+
+		// any location under the network matches
+		for location in [
+			Location::new(2, [GlobalConsensus(ByGenesis(WESTEND_GENESIS_HASH))]),
+			Location::new(2, [GlobalConsensus(ByGenesis(WESTEND_GENESIS_HASH)), Parachain(1000)]),
+			Location::new(
+				2,
+				[
+					GlobalConsensus(ByGenesis(WESTEND_GENESIS_HASH)),
+					Parachain(1000),
+					PalletInstance(50),
+					GeneralIndex(1),
+				],
+			),
+		] {
+			assert!(IsRemote::contains(&location), "location: {location:?}");
+		}
+
+		// other networks and local locations don't match
+		for location in [
+			Location::new(2, [GlobalConsensus(ByGenesis(ROCOCO_GENESIS_HASH)), Parachain(1000)]),
+			Location::new(1, [Parachain(1000)]),
+			Location::new(0, [PalletInstance(50), GeneralIndex(1)]),
+		] {
+			assert!(!IsRemote::contains(&location), "location: {location:?}");
+		}
+	}
 }
@@ -27,6 +27,19 @@ pub enum FungiblesAccessError {
 	AssetIdConversionFailed,
 	/// `u128` amount to currency `Balance` conversion failed.
 	AmountToBalanceConversionFailed,
+	/// Reading the assets in a pool with the native token failed.
+	PoolAssetsReadFailed,
+}
+
+/// The local asset an XCM `Location` is stored as.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug, scale_info::TypeInfo)]
+pub enum AssetIdKind {
+	/// Asset of the trust-backed assets pallet.
+	TrustBacked(u32),
+	/// Asset of the foreign assets pallet.
+	Foreign(xcm::VersionedLocation),
+	/// Liquidity pool token of the pool assets pallet.
+	Pool(u32),
 }
 
 sp_api::decl_runtime_apis! {
@@ -43,4 +56,23 @@ sp_api::decl_runtime_apis! {
 		/// Returns the list of all [`Asset`] that an `AccountId` has.
 		fn query_account_balances(account: AccountId) -> Result<xcm::VersionedAssets, FungiblesAccessError>;
 	}
+
+	/// The API for resolving XCM locations to local assets.
+	pub trait AssetLocationApi {
+		/// Returns the local asset `location` is stored as, using the same converters as the
+		/// runtime's asset transactors, or `None` if no local asset corresponds to it.
+		fn location_to_asset_id(location: xcm::VersionedLocation) -> Option<AssetIdKind>;
+	}
+
+	/// The API for querying which assets can pay for XCM execution.
+	///
+	/// Unlike `XcmPaymentApi::query_acceptable_payment_assets`, which only lists the assets whose
+	/// fees can be quoted through a pool with the native token, this also lists the sufficient
+	/// assets, which are accepted by the traders of the asset hubs without a pool.
+	pub trait FeePaymentAssetsApi {
+		/// Returns the deduplicated ids of all assets accepted for paying XCM execution fees, that
+		/// is the sufficient assets and the assets in a pool with the native token, converted to
+		/// `xcm_version`.
+		fn query_acceptable_payment_assets(xcm_version: xcm::Version) -> Result<Vec<xcm::VersionedAssetId>, FungiblesAccessError>;
+	}
 }
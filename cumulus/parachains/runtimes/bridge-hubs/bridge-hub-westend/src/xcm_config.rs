@@ -289,7 +289,7 @@ pub struct XcmFeeManagerFromComponentsBridgeHub<WaivedLocations, HandleFee>(
 impl<WaivedLocations: Contains<Location>, FeeHandler: HandleFee> FeeManager
 	for XcmFeeManagerFromComponentsBridgeHub<WaivedLocations, FeeHandler>
 {
-	fn is_waived(origin: Option<&Location>, fee_reason: FeeReason) -> bool {
+	fn is_waived(origin: Option<&Location>, _: &Assets, fee_reason: FeeReason) -> bool {
 		let Some(loc) = origin else { return false };
 		if let Export { network, destination: Here } = fee_reason {
 			if network == EthereumNetwork::get().into() {
@@ -299,7 +299,7 @@ impl<WaivedLocations: Contains<Location>, FeeHandler: HandleFee> FeeManager
 		WaivedLocations::contains(loc)
 	}
 
-	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) {
-		FeeHandler::handle_fee(fee, context, reason);
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		FeeHandler::handle_fee(fee, context, reason)
 	}
 }
@@ -853,8 +853,17 @@ impl<
 			return (None, None);
 		}
 
+		// overestimate delivery fee
+		let mut max_assets: Vec<Asset> = Vec::new();
+		for i in 0..MAX_ITEMS_IN_ASSETS {
+			max_assets.push((GeneralIndex(i as u128), 100u128).into());
+		}
+		let overestimated_xcm =
+			vec![WithdrawAsset(max_assets.into()); MAX_INSTRUCTIONS_TO_DECODE as usize].into();
+		let overestimated_fees = PriceForDelivery::price_for_delivery((), &overestimated_xcm);
+
 		let mut fees_mode = None;
-		if !XcmConfig::FeeManager::is_waived(Some(origin_ref), fee_reason) {
+		if !XcmConfig::FeeManager::is_waived(Some(origin_ref), &overestimated_fees, fee_reason) {
 			// if not waived, we need to set up accounts for paying and receiving fees
 
 			// mint ED to origin if needed
@@ -862,15 +871,6 @@ impl<
 				XcmConfig::AssetTransactor::deposit_asset(&ed, &origin_ref, None).unwrap();
 			}
 
-			// overestimate delivery fee
-			let mut max_assets: Vec<Asset> = Vec::new();
-			for i in 0..MAX_ITEMS_IN_ASSETS {
-				max_assets.push((GeneralIndex(i as u128), 100u128).into());
-			}
-			let overestimated_xcm =
-				vec![WithdrawAsset(max_assets.into()); MAX_INSTRUCTIONS_TO_DECODE as usize].into();
-			let overestimated_fees = PriceForDelivery::price_for_delivery((), &overestimated_xcm);
-
 			// mint overestimated fee to origin
 			for fee in overestimated_fees.inner() {
 				XcmConfig::AssetTransactor::deposit_asset(&fee, &origin_ref, None).unwrap();
@@ -229,8 +229,13 @@ impl<
 		// allow more initialization for target parachain
 		ToParachainHelper::ensure(Parachain::get());
 
+		// overestimate delivery fee
+		let overestimated_xcm = alloc::vec![ClearOrigin; 128].into();
+		let overestimated_fees =
+			PriceForDelivery::price_for_delivery(Parachain::get(), &overestimated_xcm);
+
 		let mut fees_mode = None;
-		if !XcmConfig::FeeManager::is_waived(Some(origin_ref), fee_reason) {
+		if !XcmConfig::FeeManager::is_waived(Some(origin_ref), &overestimated_fees, fee_reason) {
 			// if not waived, we need to set up accounts for paying and receiving fees
 
 			// mint ED to origin if needed
@@ -238,11 +243,6 @@ impl<
 				XcmConfig::AssetTransactor::deposit_asset(&ed, &origin_ref, None).unwrap();
 			}
 
-			// overestimate delivery fee
-			let overestimated_xcm = alloc::vec![ClearOrigin; 128].into();
-			let overestimated_fees =
-				PriceForDelivery::price_for_delivery(Parachain::get(), &overestimated_xcm);
-
 			// mint overestimated fee to origin
 			for fee in overestimated_fees.inner() {
 				XcmConfig::AssetTransactor::deposit_asset(&fee, &origin_ref, None).unwrap();
@@ -235,7 +235,7 @@ benchmarks_instance_pallet! {
 		let (_, _) = T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&dest_location,
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		let mut executor = new_executor::<T>(Default::default());
@@ -268,7 +268,7 @@ benchmarks_instance_pallet! {
 		let (_, _) = T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&dest_location,
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		let mut executor = new_executor::<T>(Default::default());
@@ -301,7 +301,7 @@ benchmarks_instance_pallet! {
 		let (_, _) = T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&dest_location,
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		let mut executor = new_executor::<T>(Default::default());
@@ -331,7 +331,7 @@ benchmarks_instance_pallet! {
 		let (_, _) = T::DeliveryHelper::ensure_successful_delivery(
 			&sender_location,
 			&dest_location,
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		let sender_account_balance_before = T::TransactAsset::balance(&sender_account);
@@ -105,7 +105,7 @@ mod benchmarks {
 		T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&versioned_dest.clone().try_into().unwrap(),
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		#[extrinsic_call]
@@ -135,7 +135,7 @@ mod benchmarks {
 		let (_, _) = T::DeliveryHelper::ensure_successful_delivery(
 			&origin_location,
 			&destination,
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		match &asset.fun {
@@ -205,7 +205,7 @@ mod benchmarks {
 		let (_, _) = T::DeliveryHelper::ensure_successful_delivery(
 			&origin_location,
 			&destination,
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		match &asset.fun {
@@ -286,7 +286,7 @@ mod benchmarks {
 		T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&versioned_dest.clone().try_into().unwrap(),
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		#[extrinsic_call]
@@ -351,7 +351,7 @@ mod benchmarks {
 		T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&versioned_loc.clone().try_into().unwrap(),
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		#[extrinsic_call]
@@ -371,7 +371,7 @@ mod benchmarks {
 		T::DeliveryHelper::ensure_successful_delivery(
 			&Default::default(),
 			&versioned_loc.clone().try_into().unwrap(),
-			FeeReason::ChargeFees,
+			FeeReason::ChargeFees { caller: None },
 		);
 
 		let _ = crate::Pallet::<T>::request_version_notify(loc);
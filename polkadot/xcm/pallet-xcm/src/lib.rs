@@ -1445,6 +1445,12 @@ pub mod pallet {
 /// The maximum number of distinct assets allowed to be transferred in a single helper extrinsic.
 const MAX_ASSETS_FOR_TRANSFER: usize = 2;
 
+/// Caller reported in [`FeeReason::ChargeFees`] for delivery fees of asset transfers.
+const TRANSFER_CALLER: &str = "pallet_xcm::transfer";
+
+/// Caller reported in [`FeeReason::ChargeFees`] for delivery fees of sent messages.
+const SEND_XCM_CALLER: &str = "pallet_xcm::send_xcm";
+
 /// Specify how assets used for fees are handled during asset transfers.
 #[derive(Clone, PartialEq)]
 enum FeesHandling<T: Config> {
@@ -1818,13 +1824,15 @@ impl<T: Config> Pallet<T> {
 					Error::<T>::from(error)
 				})?;
 			if origin != Here.into_location() {
-				Self::charge_fees(origin.clone(), price.clone()).map_err(|error| {
-					tracing::error!(
-						target: "xcm::pallet_xcm::execute_xcm_transfer",
-						?error, ?price, ?origin, "Unable to charge fee",
-					);
-					Error::<T>::FeesNotMet
-				})?;
+				Self::charge_fees(TRANSFER_CALLER, origin.clone(), price.clone()).map_err(
+					|error| {
+						tracing::error!(
+							target: "xcm::pallet_xcm::execute_xcm_transfer",
+							?error, ?price, ?origin, "Unable to charge fee",
+						);
+						Error::<T>::FeesNotMet
+					},
+				)?;
 			}
 			let message_id = T::XcmRouter::deliver(ticket)
 				.map_err(|error| {
@@ -2491,15 +2499,18 @@ impl<T: Config> Pallet<T> {
 		let interior = interior.into();
 		let local_origin = interior.clone().into();
 		let dest = dest.into();
-		let is_waived =
-			<T::XcmExecutor as FeeManager>::is_waived(Some(&local_origin), FeeReason::ChargeFees);
 		if interior != Junctions::Here {
 			message.0.insert(0, DescendOrigin(interior.clone()));
 		}
 		tracing::debug!(target: "xcm::send_xcm", "{:?}, {:?}", dest.clone(), message.clone());
 		let (ticket, price) = validate_send::<T::XcmRouter>(dest, message)?;
+		let is_waived = <T::XcmExecutor as FeeManager>::is_waived(
+			Some(&local_origin),
+			&price,
+			FeeReason::ChargeFees { caller: Some(SEND_XCM_CALLER) },
+		);
 		if !is_waived {
-			Self::charge_fees(local_origin, price).map_err(|e| {
+			Self::charge_fees(SEND_XCM_CALLER, local_origin, price).map_err(|e| {
 				tracing::error!(
 					target: "xcm::pallet_xcm::send_xcm",
 					?e,
@@ -2805,13 +2816,14 @@ impl<T: Config> Pallet<T> {
 		});
 	}
 
-	/// Withdraw given `assets` from the given `location` and pay as XCM fees.
+	/// Withdraw given `assets` from the given `location` and pay as XCM fees on behalf of
+	/// `caller`.
 	///
 	/// Fails if:
 	/// - the `assets` are not known on this chain;
 	/// - the `assets` cannot be withdrawn with that location as the Origin.
-	fn charge_fees(location: Location, assets: Assets) -> DispatchResult {
-		T::XcmExecutor::charge_fees(location.clone(), assets.clone())
+	fn charge_fees(caller: &'static str, location: Location, assets: Assets) -> DispatchResult {
+		T::XcmExecutor::charge_fees_for(caller, location.clone(), assets.clone())
 			.map_err(|_| Error::<T>::FeesNotMet)?;
 		Self::deposit_event(Event::FeesPaid { paying: location, fees: assets });
 		Ok(())
@@ -317,6 +317,19 @@ pub trait ExecuteXcm<Call> {
 	/// Deduct some `fees` to the sovereign account of the given `location` and place them as per
 	/// the convention for fees.
 	fn charge_fees(location: impl Into<Location>, fees: Assets) -> Result;
+
+	/// Same as [`ExecuteXcm::charge_fees`], but attributes the fees to `caller`, the subsystem
+	/// charging them.
+	///
+	/// By default the `caller` is ignored.
+	fn charge_fees_for(
+		caller: &'static str,
+		location: impl Into<Location>,
+		fees: Assets,
+	) -> Result {
+		let _ = caller;
+		Self::charge_fees(location, fees)
+	}
 }
 
 pub enum Weightless {}
@@ -14,10 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+use alloc::vec::Vec;
 use core::marker::PhantomData;
-use frame_support::traits::{Contains, Get};
+use frame_support::{
+	storage::{storage_prefix, unhashed},
+	traits::{Contains, ContainsPair, Get, OnFinalize},
+};
+use sp_runtime::{
+	traits::{BlockNumberProvider, Convert, Saturating},
+	FixedPointNumber, FixedU128, PerThing, Perbill, SaturatedConversion,
+};
 use xcm::prelude::*;
-use xcm_executor::traits::{FeeManager, FeeReason, TransactAsset};
+use xcm_executor::{
+	traits::{ConvertLocation, FeeManager, FeeReason, TransactAsset},
+	AssetsInHolding,
+};
 
 /// Handles the fees that are taken by certain XCM instructions.
 pub trait HandleFee {
@@ -58,13 +69,816 @@ pub struct XcmFeeManagerFromComponents<WaivedLocations, HandleFee>(
 impl<WaivedLocations: Contains<Location>, FeeHandler: HandleFee> FeeManager
 	for XcmFeeManagerFromComponents<WaivedLocations, FeeHandler>
 {
-	fn is_waived(origin: Option<&Location>, _: FeeReason) -> bool {
+	fn is_waived(origin: Option<&Location>, _: &Assets, _: FeeReason) -> bool {
 		let Some(loc) = origin else { return false };
 		WaivedLocations::contains(loc)
 	}
 
-	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) {
-		FeeHandler::handle_fee(fee, context, reason);
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		FeeHandler::handle_fee(fee, context, reason)
+	}
+}
+
+/// Bounded classification of the origin of an XCM fee, relative to the local chain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum FeeOriginKind {
+	/// No origin was provided.
+	Unknown,
+	/// An origin within the local consensus system, e.g. a local account or pallet.
+	Local,
+	/// The relay chain or an origin on the relay chain.
+	Relay,
+	/// A sibling parachain or an origin on a sibling parachain.
+	Sibling,
+	/// An origin in a different global consensus system.
+	RemoteConsensus,
+}
+
+impl FeeOriginKind {
+	/// Classify the given `origin`.
+	pub fn classify(origin: Option<&Location>) -> Self {
+		let Some(origin) = origin else { return Self::Unknown };
+		match origin.unpack() {
+			(0, _) => Self::Local,
+			(1, [Parachain(_), ..]) => Self::Sibling,
+			(1, _) => Self::Relay,
+			_ => Self::RemoteConsensus,
+		}
+	}
+}
+
+/// Records fee metrics produced by [`ClassifiedMeteredFeeManager`].
+pub trait RecordFeeMetrics {
+	/// Record that a fee was evaluated for an origin of the given `kind`.
+	fn record(kind: FeeOriginKind, waived: bool);
+}
+
+impl RecordFeeMetrics for () {
+	fn record(_: FeeOriginKind, _: bool) {}
+}
+
+/// A `FeeManager` implementation that wraps `Inner` and records a fee count for every evaluated
+/// fee, broken down by the [`FeeOriginKind`] of the origin and whether the fee was waived.
+///
+/// Origins are classified into a bounded set of kinds so that the cardinality of the recorded
+/// metrics stays bounded.
+pub struct ClassifiedMeteredFeeManager<Inner, Metrics>(PhantomData<(Inner, Metrics)>);
+impl<Inner: FeeManager, Metrics: RecordFeeMetrics> FeeManager
+	for ClassifiedMeteredFeeManager<Inner, Metrics>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		let waived = Inner::is_waived(origin, assets, reason);
+		Metrics::record(FeeOriginKind::classify(origin), waived);
+		waived
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		Inner::handle_fee(fee, context, reason)
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// Emits an event for every fee handled by [`EventEmittingFeeManager`].
+///
+/// Implemented by the runtime to deposit its own event type, e.g. a `FeeHandled { reason, assets }`
+/// event of one of its pallets.
+pub trait EmitFeeEvent {
+	/// Emit the event for the `assets` handled as a fee for `reason`.
+	fn emit(reason: FeeReason, assets: Assets);
+}
+
+impl EmitFeeEvent for () {
+	fn emit(_: FeeReason, _: Assets) {}
+}
+
+/// A `FeeManager` implementation that wraps `Inner` and emits an event with `EventEmitter` for
+/// every fee after it was handled by `Inner`, so that indexers can track the fees.
+///
+/// Only the part of the fee which `Inner` handled is emitted, the part it returned unhandled is
+/// not. No event is emitted if `Inner` handled nothing.
+pub struct EventEmittingFeeManager<Inner, EventEmitter>(PhantomData<(Inner, EventEmitter)>);
+impl<Inner: FeeManager, EventEmitter: EmitFeeEvent> FeeManager
+	for EventEmittingFeeManager<Inner, EventEmitter>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let unhandled = Inner::handle_fee(fee.clone(), context, reason.clone());
+		let mut handled: AssetsInHolding = fee.into();
+		handled.saturating_take(unhandled.clone().into());
+		if !handled.is_empty() {
+			EventEmitter::emit(reason, handled.into());
+		}
+		unhandled
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` implementation that caps the total amount of fees handled by `Inner` within a
+/// single block to `MaxPerBlock`.
+///
+/// The amount of a fee is the sum of the amounts of its fungible assets. Fees which would push
+/// the total of the current block, as reported by `BlockNumber`, over `MaxPerBlock` are not
+/// handed to `Inner` and are returned unhandled, so the executor traps them for the local chain.
+/// This acts as a circuit breaker which limits the damage of a misconfigured fee handling to a
+/// single block, while governance can recover the fees. Only the part of a fee which `Inner`
+/// handles counts towards the total.
+///
+/// The total is kept in a storage value under `Prefix`, which must be unique for every instance
+/// of this type. The total only counts for the block it was recorded in. The [`OnFinalize`] hook
+/// of this type removes it, so it doesn't outlive the block when called from the `on_finalize`
+/// of a pallet of the runtime.
+pub struct BlockCappedFeeManager<MaxPerBlock, Inner, BlockNumber, Prefix>(
+	PhantomData<(MaxPerBlock, Inner, BlockNumber, Prefix)>,
+);
+impl<MaxPerBlock, Inner, BlockNumber: BlockNumberProvider, Prefix: Get<&'static [u8]>>
+	BlockCappedFeeManager<MaxPerBlock, Inner, BlockNumber, Prefix>
+{
+	/// Storage key of the total amount of fees handled in a block.
+	fn storage_key() -> [u8; 32] {
+		storage_prefix(Prefix::get(), b"HandledInBlock")
+	}
+
+	/// Get the total amount of fees handled in the current block.
+	pub fn handled_in_block() -> u128 {
+		let now = BlockNumber::current_block_number();
+		match unhashed::get::<(BlockNumber::BlockNumber, u128)>(&Self::storage_key()) {
+			Some((block, total)) if block == now => total,
+			_ => 0,
+		}
+	}
+}
+impl<MaxPerBlock, Inner, BlockNumber: BlockNumberProvider, Prefix: Get<&'static [u8]>>
+	OnFinalize<BlockNumber::BlockNumber>
+	for BlockCappedFeeManager<MaxPerBlock, Inner, BlockNumber, Prefix>
+{
+	fn on_finalize(_: BlockNumber::BlockNumber) {
+		unhashed::kill(&Self::storage_key());
+	}
+}
+impl<
+		MaxPerBlock: Get<u128>,
+		Inner: FeeManager,
+		BlockNumber: BlockNumberProvider,
+		Prefix: Get<&'static [u8]>,
+	> FeeManager for BlockCappedFeeManager<MaxPerBlock, Inner, BlockNumber, Prefix>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let handled_in_block = Self::handled_in_block();
+		let amount = fungible_amount(&fee);
+		if handled_in_block.saturating_add(amount) > MaxPerBlock::get() {
+			log::warn!(
+				target: "xcm::fees",
+				"Fees handled in block would exceed the cap of {:?}. Not handling fee: {fee:?}",
+				MaxPerBlock::get(),
+			);
+			return fee
+		}
+
+		let unhandled = Inner::handle_fee(fee, context, reason);
+		let handled = amount.saturating_sub(fungible_amount(&unhandled));
+		if handled > 0 {
+			unhashed::put(
+				&Self::storage_key(),
+				&(BlockNumber::current_block_number(), handled_in_block.saturating_add(handled)),
+			);
+		}
+		unhandled
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` wrapper which traces a sample of one in `SampleRate` handled fees, for chains
+/// with too many fee events to log each of them. A `SampleRate` of zero disables tracing.
+///
+/// Every fee is handed to `Inner`, whether it is sampled or not. The selection is derived from
+/// the message id of the [`XcmContext`], so it doesn't touch storage. Fees handled without a
+/// context are never sampled.
+pub struct SampledTracingFeeManager<Inner, SampleRate>(PhantomData<(Inner, SampleRate)>);
+impl<Inner, SampleRate: Get<u32>> SampledTracingFeeManager<Inner, SampleRate> {
+	/// Whether the fee handled in `context` should be traced.
+	pub fn should_sample(context: Option<&XcmContext>) -> bool {
+		match (SampleRate::get(), context) {
+			(0, _) | (_, None) => false,
+			(rate, Some(context)) => {
+				let [a, b, c, d, ..] = context.message_id;
+				u32::from_le_bytes([a, b, c, d]) % rate == 0
+			},
+		}
+	}
+}
+impl<Inner: FeeManager, SampleRate: Get<u32>> FeeManager
+	for SampledTracingFeeManager<Inner, SampleRate>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		if Self::should_sample(context) {
+			log::trace!(
+				target: "xcm::fees",
+				"Sampled fee: {fee:?}, reason: {reason:?}, context: {context:?}",
+			);
+		}
+		Inner::handle_fee(fee, context, reason)
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// Limits the rate of some operation.
+pub trait RateLimiter {
+	/// Takes a permit for one operation. Returns `false` if the rate limit has been reached.
+	fn try_acquire() -> bool;
+}
+
+impl RateLimiter for () {
+	fn try_acquire() -> bool {
+		true
+	}
+}
+
+/// A [`RateLimiter`] token bucket holding at most `Capacity` permits, which is refilled by
+/// `RefillPerBlock` permits in every block, as reported by `BlockNumber`.
+///
+/// The bucket is kept in a storage value under `Prefix`, which must be unique for every instance
+/// of this type, and starts full.
+pub struct TokenBucketLimiter<Capacity, RefillPerBlock, BlockNumber, Prefix>(
+	PhantomData<(Capacity, RefillPerBlock, BlockNumber, Prefix)>,
+);
+impl<
+		Capacity: Get<u32>,
+		RefillPerBlock: Get<u32>,
+		BlockNumber: BlockNumberProvider,
+		Prefix: Get<&'static [u8]>,
+	> TokenBucketLimiter<Capacity, RefillPerBlock, BlockNumber, Prefix>
+{
+	/// Storage key of the block of the last update and the permits left in the bucket.
+	fn storage_key() -> [u8; 32] {
+		storage_prefix(Prefix::get(), b"TokenBucket")
+	}
+
+	/// Get the permits available in the current block.
+	pub fn available() -> u32 {
+		let now = BlockNumber::current_block_number();
+		match unhashed::get::<(BlockNumber::BlockNumber, u32)>(&Self::storage_key()) {
+			Some((block, permits)) => {
+				let elapsed: u32 = now.saturating_sub(block).saturated_into();
+				permits
+					.saturating_add(elapsed.saturating_mul(RefillPerBlock::get()))
+					.min(Capacity::get())
+			},
+			None => Capacity::get(),
+		}
+	}
+}
+impl<
+		Capacity: Get<u32>,
+		RefillPerBlock: Get<u32>,
+		BlockNumber: BlockNumberProvider,
+		Prefix: Get<&'static [u8]>,
+	> RateLimiter for TokenBucketLimiter<Capacity, RefillPerBlock, BlockNumber, Prefix>
+{
+	fn try_acquire() -> bool {
+		let Some(permits) = Self::available().checked_sub(1) else { return false };
+		unhashed::put(&Self::storage_key(), &(BlockNumber::current_block_number(), permits));
+		true
+	}
+}
+
+/// A `FeeManager` wrapper which throttles operations paying fees for which `Reason` holds, e.g.
+/// `ExportMessage`, independently of the amount of their fees.
+///
+/// A permit is taken from `Limiter` for every such fee which isn't waived, looking through the way
+/// it is paid (see [`FeeReason::charged_for`]). When no permit is left, [`FeeManager::check_fee`]
+/// fails with [`XcmError::NoPermission`], so that the operation fails without its fee being
+/// charged. Fees are always handled by `Inner`.
+pub struct ThrottleByReasonFeeManager<Reason, Limiter, Inner>(
+	PhantomData<(Reason, Limiter, Inner)>,
+);
+impl<Reason: Contains<FeeReason>, Limiter: RateLimiter, Inner: FeeManager> FeeManager
+	for ThrottleByReasonFeeManager<Reason, Limiter, Inner>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		if Reason::contains(reason.charged_for()) && !Limiter::try_acquire() {
+			log::warn!(
+				target: "xcm::fees",
+				"Rate limit reached for fees of reason {reason:?}. Rejecting fee: {assets:?}",
+			);
+			return Err(XcmError::NoPermission)
+		}
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		Inner::handle_fee(fee, context, reason)
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` wrapper which waives the fees of all origins while the current block, as
+/// reported by `Now`, is in the window from `Start` (inclusive) to `End` (exclusive).
+///
+/// Outside of the window, waivers are decided by `Inner`. Fees are always handled by `Inner`.
+/// `Start` and `End` can be backed by storage, so that governance can schedule a free-fee window
+/// without a runtime upgrade.
+pub struct TimeWindowWaiver<Start, End, Now, Inner>(PhantomData<(Start, End, Now, Inner)>);
+impl<Start, End, Now: BlockNumberProvider, Inner> TimeWindowWaiver<Start, End, Now, Inner>
+where
+	Start: Get<Now::BlockNumber>,
+	End: Get<Now::BlockNumber>,
+{
+	/// Returns `true` if the current block is in the window.
+	pub fn is_active() -> bool {
+		let now = Now::current_block_number();
+		Start::get() <= now && now < End::get()
+	}
+}
+impl<Start, End, Now: BlockNumberProvider, Inner: FeeManager> FeeManager
+	for TimeWindowWaiver<Start, End, Now, Inner>
+where
+	Start: Get<Now::BlockNumber>,
+	End: Get<Now::BlockNumber>,
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Self::is_active() || Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		Inner::handle_fee(fee, context, reason)
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// Baseline of the fees handled by [`AnomalyDetectingFeeManager`].
+pub trait FeeBaseline {
+	/// Get the current baseline, if any fee has been noted yet.
+	fn baseline() -> Option<u128>;
+
+	/// Note the amount of a handled fee.
+	fn note(amount: u128);
+}
+
+/// A [`FeeBaseline`] which is the exponential moving average of the noted fees, where each new
+/// fee has a weight of `1 / Weight`.
+///
+/// The average is kept in a storage value under `Prefix`, which must be unique for every instance
+/// of this type. It is part of the state transition, so it is computed with integer arithmetic
+/// only.
+pub struct MovingAverageFeeBaseline<Weight, Prefix>(PhantomData<(Weight, Prefix)>);
+impl<Weight, Prefix: Get<&'static [u8]>> MovingAverageFeeBaseline<Weight, Prefix> {
+	/// Storage key of the moving average.
+	fn storage_key() -> [u8; 32] {
+		storage_prefix(Prefix::get(), b"MovingAverage")
+	}
+}
+impl<Weight: Get<u32>, Prefix: Get<&'static [u8]>> FeeBaseline
+	for MovingAverageFeeBaseline<Weight, Prefix>
+{
+	fn baseline() -> Option<u128> {
+		unhashed::get(&Self::storage_key())
+	}
+
+	fn note(amount: u128) {
+		let weight = u128::from(Weight::get().max(1));
+		let average = match Self::baseline() {
+			Some(average) => average - average / weight + amount / weight,
+			None => amount,
+		};
+		unhashed::put(&Self::storage_key(), &average);
+	}
+}
+
+/// Records the anomalies detected by [`AnomalyDetectingFeeManager`].
+pub trait RecordFeeAnomaly {
+	/// Record that a fee of `amount` exceeded the `baseline` by more than the configured factor.
+	fn record(reason: &FeeReason, amount: u128, baseline: u128);
+}
+
+impl RecordFeeAnomaly for () {
+	fn record(_: &FeeReason, _: u128, _: u128) {}
+}
+
+/// A `FeeManager` wrapper which warns when a handled fee exceeds the `Baseline` of the previous
+/// fees by more than `Factor` times, to surface bugs in fee specifications or attacks.
+///
+/// The amount of a fee is the sum of the amounts of its fungible assets. Anomalies are logged
+/// and recorded with `Metrics` before the fee is handed to `Inner`, every fee is noted in the
+/// `Baseline` afterwards. Fees are never rejected.
+pub struct AnomalyDetectingFeeManager<Inner, Baseline, Factor, Metrics = ()>(
+	PhantomData<(Inner, Baseline, Factor, Metrics)>,
+);
+impl<Inner: FeeManager, Baseline: FeeBaseline, Factor: Get<u32>, Metrics: RecordFeeAnomaly>
+	FeeManager for AnomalyDetectingFeeManager<Inner, Baseline, Factor, Metrics>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let amount = fungible_amount(&fee);
+		if let Some(baseline) = Baseline::baseline() {
+			if amount > baseline.saturating_mul(Factor::get().into()) {
+				log::warn!(
+					target: "xcm::fees",
+					"Fee {fee:?} with reason {reason:?} exceeds the baseline of {baseline} more \
+					than {} times",
+					Factor::get(),
+				);
+				Metrics::record(&reason, amount, baseline);
+			}
+		}
+		Baseline::note(amount);
+		Inner::handle_fee(fee, context, reason)
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` which returns fees to the sovereign account of the message origin, e.g. for
+/// fee-sharing arrangements with the sender chain.
+///
+/// The origin of the [`XcmContext`] is converted to its sovereign account with
+/// `SovereignConverter` and the fee is deposited there using the `Fungibles` asset transactor.
+/// Fees whose origin is unknown or can't be converted are handed to `Inner`, as are the errors
+/// of failed deposits. Fees which couldn't be deposited are returned unhandled.
+pub struct RefundToOriginSovereign<SovereignConverter, Fungibles, Inner, AccountId>(
+	PhantomData<(SovereignConverter, Fungibles, Inner, AccountId)>,
+);
+impl<
+		SovereignConverter: ConvertLocation<AccountId>,
+		Fungibles: TransactAsset,
+		Inner: FeeManager,
+		AccountId: Into<[u8; 32]>,
+	> FeeManager for RefundToOriginSovereign<SovereignConverter, Fungibles, Inner, AccountId>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let sovereign = context
+			.and_then(|context| context.origin.as_ref())
+			.and_then(SovereignConverter::convert_location);
+		let Some(sovereign) = sovereign else {
+			log::trace!(
+				target: "xcm::fees",
+				"Sovereign account of origin not resolved, handing fee to inner fee manager: {fee:?}",
+			);
+			return Inner::handle_fee(fee, context, reason)
+		};
+
+		let dest = AccountId32 { network: None, id: sovereign.into() }.into();
+		deposit_fee_or_else::<Fungibles>(fee, context, dest, |asset, e| {
+			Inner::on_handle_fee_error(&asset.into(), reason.clone(), e)
+		})
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` which rewards the author of the current block with the fees, tying XCM fee
+/// revenue to block production.
+///
+/// The fee is deposited to the author provided by `AuthorProvider` using the `Fungibles` asset
+/// transactor. Fees handled while no author is available are handed to `Inner`, as are the
+/// errors of failed deposits. Fees which couldn't be deposited are returned unhandled.
+pub struct CollatorRewardFeeManager<Inner, AuthorProvider, Fungibles, AccountId>(
+	PhantomData<(Inner, AuthorProvider, Fungibles, AccountId)>,
+);
+impl<
+		Inner: FeeManager,
+		AuthorProvider: Get<Option<AccountId>>,
+		Fungibles: TransactAsset,
+		AccountId: Into<[u8; 32]>,
+	> FeeManager for CollatorRewardFeeManager<Inner, AuthorProvider, Fungibles, AccountId>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let Some(author) = AuthorProvider::get() else {
+			log::trace!(
+				target: "xcm::fees",
+				"Block author not available, handing fee to inner fee manager: {fee:?}",
+			);
+			return Inner::handle_fee(fee, context, reason)
+		};
+
+		let dest = AccountId32 { network: None, id: author.into() }.into();
+		deposit_fee_or_else::<Fungibles>(fee, context, dest, |asset, e| {
+			Inner::on_handle_fee_error(&asset.into(), reason.clone(), e)
+		})
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` which scales the fees by a multiplier depending on the message origin before
+/// handing them to `Inner`, e.g. to grant discounts to some sender chains.
+///
+/// `Multipliers` maps the origin of the [`XcmContext`] to its multiplier. Fees of origins
+/// without a multiplier, or handled without a known origin, are passed on unscaled. Only the
+/// amounts of fungible assets are scaled.
+///
+/// The fees reaching this manager have already been withdrawn from the payer, so scaling them
+/// can't change what was paid. Only multipliers of at most one are applied and the scaled-off
+/// part of the fee is returned unhandled, so it is trapped rather than burned. Multipliers above
+/// one would mint value and are ignored, charging more to some origins must be done where the
+/// fee is priced, e.g. in the weight trader.
+pub struct PerOriginMultiplierFeeManager<Multipliers, Inner>(PhantomData<(Multipliers, Inner)>);
+impl<Multipliers: Convert<Location, Option<FixedU128>>, Inner: FeeManager> FeeManager
+	for PerOriginMultiplierFeeManager<Multipliers, Inner>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let multiplier = context
+			.and_then(|context| context.origin.clone())
+			.and_then(Multipliers::convert)
+			.unwrap_or_else(FixedU128::one);
+		if multiplier > FixedU128::one() {
+			log::warn!(
+				target: "xcm::fees",
+				"Ignoring fee multiplier {multiplier:?} above one, not scaling fee: {fee:?}",
+			);
+			return Inner::handle_fee(fee, context, reason)
+		}
+		if multiplier == FixedU128::one() {
+			return Inner::handle_fee(fee, context, reason)
+		}
+
+		let mut scaled = Vec::new();
+		let mut unhandled = Assets::new();
+		for asset in fee.into_inner() {
+			match asset.fun {
+				Fungible(amount) => {
+					let scaled_amount = multiplier.saturating_mul_int(amount);
+					if scaled_amount > 0 {
+						scaled.push(Asset { id: asset.id.clone(), fun: Fungible(scaled_amount) });
+					}
+					if amount > scaled_amount {
+						unhandled
+							.push(Asset { id: asset.id, fun: Fungible(amount - scaled_amount) });
+					}
+				},
+				NonFungible(_) => scaled.push(asset),
+			}
+		}
+		if !scaled.is_empty() {
+			for asset in Inner::handle_fee(scaled.into(), context, reason).into_inner() {
+				unhandled.push(asset);
+			}
+		}
+		unhandled
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` which splits the handling of fees between two managers by the XCM version of
+/// the message origin, e.g. to transition to new fee logic during a runtime upgrade.
+///
+/// The version of the origin is looked up with `Versions`. Fees of origins on a version below
+/// `Threshold` are handled by `OldManager`, all other fees, including those of origins whose
+/// version is unknown, by `NewManager`. Using `()` for a manager leaves the fees it handles
+/// unhandled.
+pub struct VersionSplitFeeManager<OldManager, NewManager, Threshold, Versions>(
+	PhantomData<(OldManager, NewManager, Threshold, Versions)>,
+);
+impl<OldManager, NewManager, Threshold: Get<XcmVersion>, Versions: GetVersion>
+	VersionSplitFeeManager<OldManager, NewManager, Threshold, Versions>
+{
+	/// Whether the fees of `origin` are handled by `OldManager`.
+	fn is_old(origin: Option<&Location>) -> bool {
+		origin
+			.and_then(Versions::get_version_for)
+			.map_or(false, |version| version < Threshold::get())
+	}
+}
+impl<
+		OldManager: FeeManager,
+		NewManager: FeeManager,
+		Threshold: Get<XcmVersion>,
+		Versions: GetVersion,
+	> FeeManager for VersionSplitFeeManager<OldManager, NewManager, Threshold, Versions>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		if Self::is_old(origin) {
+			OldManager::is_waived(origin, assets, reason)
+		} else {
+			NewManager::is_waived(origin, assets, reason)
+		}
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		if Self::is_old(origin) {
+			OldManager::check_fee(origin, assets, reason)
+		} else {
+			NewManager::check_fee(origin, assets, reason)
+		}
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		if Self::is_old(context.and_then(|context| context.origin.as_ref())) {
+			OldManager::handle_fee(fee, context, reason)
+		} else {
+			NewManager::handle_fee(fee, context, reason)
+		}
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		NewManager::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` which only accepts fees paid in assets the origin paying them is a reserve of,
+/// preventing fees from being paid in assets the origin has no legitimate claim to.
+///
+/// `ReserveChecker` decides whether the origin is a reserve of an asset, e.g. the `IsReserve`
+/// configured on the XCM executor. The check is done in [`FeeManager::check_fee`], before the fee
+/// is charged: if any fee asset fails it, the failing assets are reported to `Inner` with
+/// [`XcmError::UntrustedReserveLocation`] and the operation paying the fee fails with that error.
+/// Fees of local origins, which pay with assets held on this chain, and fees without a known
+/// origin are not checked. Fees are always handled by `Inner`.
+pub struct ReserveConsistentFeeManager<ReserveChecker, Inner>(PhantomData<(ReserveChecker, Inner)>);
+impl<ReserveChecker: ContainsPair<Asset, Location>, Inner: FeeManager> FeeManager
+	for ReserveConsistentFeeManager<ReserveChecker, Inner>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		if let Some(origin) = origin.filter(|origin| origin.parent_count() > 0) {
+			let inconsistent: Vec<Asset> = assets
+				.inner()
+				.iter()
+				.filter(|asset| !ReserveChecker::contains(asset, origin))
+				.cloned()
+				.collect();
+			if !inconsistent.is_empty() {
+				let inconsistent: Assets = inconsistent.into();
+				log::warn!(
+					target: "xcm::fees",
+					"Origin {origin:?} is not a reserve of fee assets, rejecting: {inconsistent:?}",
+				);
+				Inner::on_handle_fee_error(
+					&inconsistent,
+					reason,
+					XcmError::UntrustedReserveLocation,
+				);
+				return Err(XcmError::UntrustedReserveLocation)
+			}
+		}
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		Inner::handle_fee(fee, context, reason)
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
+	}
+}
+
+/// A `FeeManager` which diverts a share of the fees to a reserve account funding the delivery
+/// of the chain's own outbound XCM, and hands the rest to `Inner`.
+///
+/// `Share` of the amount of every fungible fee asset is deposited to `ReserveAccount` using the
+/// `Fungibles` asset transactor. Non-fungible fee assets are handed to `Inner` in full, as are
+/// the errors of failed deposits. Shares which couldn't be deposited are returned unhandled.
+pub struct DeliveryReserveFeeManager<ReserveAccount, Share, Fungibles, Inner>(
+	PhantomData<(ReserveAccount, Share, Fungibles, Inner)>,
+);
+impl<
+		ReserveAccount: Get<Location>,
+		Share: Get<Perbill>,
+		Fungibles: TransactAsset,
+		Inner: FeeManager,
+	> FeeManager for DeliveryReserveFeeManager<ReserveAccount, Share, Fungibles, Inner>
+{
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		Inner::is_waived(origin, assets, reason)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> XcmResult {
+		Inner::check_fee(origin, assets, reason)
+	}
+
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let share = Share::get();
+		let mut reserved = Vec::new();
+		let mut remaining = Vec::new();
+		for asset in fee.into_inner() {
+			match asset.fun {
+				Fungible(amount) => {
+					let reserved_amount = share.mul_floor(amount);
+					if reserved_amount > 0 {
+						reserved
+							.push(Asset { id: asset.id.clone(), fun: Fungible(reserved_amount) });
+					}
+					if amount > reserved_amount {
+						remaining
+							.push(Asset { id: asset.id, fun: Fungible(amount - reserved_amount) });
+					}
+				},
+				NonFungible(_) => remaining.push(asset),
+			}
+		}
+
+		let mut unhandled = deposit_fee_or_else::<Fungibles>(
+			reserved.into(),
+			context,
+			ReserveAccount::get(),
+			|asset, e| Inner::on_handle_fee_error(&asset.into(), reason.clone(), e),
+		);
+		if !remaining.is_empty() {
+			for asset in Inner::handle_fee(remaining.into(), context, reason).into_inner() {
+				unhandled.push(asset);
+			}
+		}
+		unhandled
+	}
+
+	fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+		Inner::on_handle_fee_error(assets, reason, error)
 	}
 }
 
@@ -101,20 +915,21 @@ impl<
 ///
 /// It reuses the `AssetTransactor` configured on the XCM executor to deposit fee assets. If
 /// the `AssetTransactor` returns an error while calling `deposit_asset`, then a warning will be
-/// logged and the fee burned.
+/// logged, [`FeeManager::on_handle_fee_error`] of `OnError` is called and the assets which
+/// couldn't be deposited are returned unhandled.
 ///
 /// `ReceiverAccount` should implement `Get<Location>`.
-pub struct SendXcmFeeToAccount<AssetTransactor, ReceiverAccount>(
-	PhantomData<(AssetTransactor, ReceiverAccount)>,
+pub struct SendXcmFeeToAccount<AssetTransactor, ReceiverAccount, OnError = ()>(
+	PhantomData<(AssetTransactor, ReceiverAccount, OnError)>,
 );
 
-impl<AssetTransactor: TransactAsset, ReceiverAccount: Get<Location>> HandleFee
-	for SendXcmFeeToAccount<AssetTransactor, ReceiverAccount>
+impl<AssetTransactor: TransactAsset, ReceiverAccount: Get<Location>, OnError: FeeManager> HandleFee
+	for SendXcmFeeToAccount<AssetTransactor, ReceiverAccount, OnError>
 {
-	fn handle_fee(fee: Assets, context: Option<&XcmContext>, _reason: FeeReason) -> Assets {
-		deposit_or_burn_fee::<AssetTransactor>(fee, context, ReceiverAccount::get());
-
-		Assets::new()
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		deposit_fee_or_else::<AssetTransactor>(fee, context, ReceiverAccount::get(), |asset, e| {
+			OnError::on_handle_fee_error(&asset.into(), reason.clone(), e)
+		})
 	}
 }
 
@@ -135,3 +950,918 @@ pub fn deposit_or_burn_fee<AssetTransactor: TransactAsset>(
 		}
 	}
 }
+
+/// Sum of the amounts of the fungible assets in `assets`.
+fn fungible_amount(assets: &Assets) -> u128 {
+	assets.inner().iter().fold(0u128, |total, asset| match asset.fun {
+		Fungible(amount) => total.saturating_add(amount),
+		NonFungible(_) => total,
+	})
+}
+
+/// Try to deposit the given fee in the specified account.
+/// Calls `on_error` with each asset which couldn't be deposited and returns them.
+fn deposit_fee_or_else<AssetTransactor: TransactAsset>(
+	fee: Assets,
+	context: Option<&XcmContext>,
+	dest: Location,
+	mut on_error: impl FnMut(Asset, XcmError),
+) -> Assets {
+	let mut unhandled = Assets::new();
+	for asset in fee.into_inner() {
+		if let Err(e) = AssetTransactor::deposit_asset(&asset, &dest, context) {
+			log::trace!(
+				target: "xcm::fees",
+				"`AssetTransactor::deposit_asset` returned error: {e:?}. Returning fee unhandled: \
+				{asset:?}.",
+			);
+			on_error(asset.clone(), e);
+			unhandled.push(asset);
+		}
+	}
+	unhandled
+}
+
+/// Assemble a stack of [`FeeManager`] wrappers into a single type.
+///
+/// Layers are separated by `;`, outermost first, and each layer marks the position of the layer
+/// below it with `_`. The last layer is the innermost manager. The macro expands to the
+/// equivalent nested generics, e.g.
+///
+/// ```ignore
+/// type Fees = fee_manager!(
+/// 	ClassifiedMeteredFeeManager<_, Metrics>;
+/// 	TimeWindowWaiver<Start, End, Now, _>;
+/// 	XcmFeeManagerFromComponents<Waived, Handler>
+/// );
+/// ```
+///
+/// is `ClassifiedMeteredFeeManager<TimeWindowWaiver<Start, End, Now,
+/// XcmFeeManagerFromComponents<Waived, Handler>>, Metrics>`. The `_` must be a generic argument
+/// of the layer, placeholders nested in tuples are not replaced.
+#[macro_export]
+macro_rules! fee_manager {
+	// split off the outermost layer
+	(@split [$($layer:tt)*] ;) => { $($layer)* };
+	(@split [$($layer:tt)*] ; $($inner:tt)+) => {
+		$crate::fee_manager!(@nest [] [$($layer)*] [$($inner)+])
+	};
+	(@split [$($layer:tt)*] $next:tt $($rest:tt)*) => {
+		$crate::fee_manager!(@split [$($layer)* $next] $($rest)*)
+	};
+	(@split [$($layer:tt)*]) => { $($layer)* };
+	// replace the placeholder of the layer with the layers below it
+	(@nest [$($done:tt)*] [] [$($inner:tt)+]) => { $($done)* };
+	(@nest [$($done:tt)*] [_ $($todo:tt)*] [$($inner:tt)+]) => {
+		$crate::fee_manager!(
+			@nest [$($done)* $crate::fee_manager!($($inner)+)] [$($todo)*] [$($inner)+]
+		)
+	};
+	(@nest [$($done:tt)*] [$next:tt $($todo:tt)*] [$($inner:tt)+]) => {
+		$crate::fee_manager!(@nest [$($done)* $next] [$($todo)*] [$($inner)+])
+	};
+	($($stack:tt)+) => { $crate::fee_manager!(@split [] $($stack)+) };
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::cell::{Cell, RefCell};
+	use std::collections::BTreeMap;
+
+	thread_local! {
+		static FEE_METRICS: RefCell<BTreeMap<(FeeOriginKind, bool), u32>> =
+			RefCell::new(BTreeMap::new());
+	}
+
+	struct TestMetrics;
+	impl RecordFeeMetrics for TestMetrics {
+		fn record(kind: FeeOriginKind, waived: bool) {
+			FEE_METRICS.with(|m| *m.borrow_mut().entry((kind, waived)).or_default() += 1);
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub WaivedRelay: Location = Location::parent();
+	}
+
+	type TestFeeManager = ClassifiedMeteredFeeManager<
+		XcmFeeManagerFromComponents<frame_support::traits::Equals<WaivedRelay>, ()>,
+		TestMetrics,
+	>;
+
+	#[test]
+	fn fee_origin_kind_classification_works() {
+		assert_eq!(FeeOriginKind::classify(None), FeeOriginKind::Unknown);
+		assert_eq!(FeeOriginKind::classify(Some(&Location::here())), FeeOriginKind::Local);
+		assert_eq!(FeeOriginKind::classify(Some(&Location::parent())), FeeOriginKind::Relay);
+		assert_eq!(
+			FeeOriginKind::classify(Some(&Location::new(1, [Parachain(1000)]))),
+			FeeOriginKind::Sibling
+		);
+		assert_eq!(
+			FeeOriginKind::classify(Some(&Location::new(2, [GlobalConsensus(Kusama)]))),
+			FeeOriginKind::RemoteConsensus
+		);
+	}
+
+	#[test]
+	fn classified_metered_fee_manager_records_sibling_fee() {
+		let sibling = Location::new(1, [Parachain(1000)]);
+		let fee: Assets = (Here, 10).into();
+		assert!(!TestFeeManager::is_waived(Some(&sibling), &fee, FeeReason::TransferReserveAsset));
+		assert!(TestFeeManager::is_waived(
+			Some(&Location::parent()),
+			&fee,
+			FeeReason::ChargeFees { caller: None }
+		));
+
+		FEE_METRICS.with(|m| {
+			let m = m.borrow();
+			assert_eq!(m.get(&(FeeOriginKind::Sibling, false)), Some(&1));
+			assert_eq!(m.get(&(FeeOriginKind::Sibling, true)), None);
+			assert_eq!(m.get(&(FeeOriginKind::Relay, true)), Some(&1));
+		});
+	}
+
+	thread_local! {
+		static BLOCK_NUMBER: Cell<u32> = Cell::new(1);
+		static HANDLED_FEES: RefCell<Vec<Assets>> = RefCell::new(Vec::new());
+	}
+
+	struct TestBlockNumber;
+	impl BlockNumberProvider for TestBlockNumber {
+		type BlockNumber = u32;
+
+		fn current_block_number() -> u32 {
+			BLOCK_NUMBER.with(|b| b.get())
+		}
+	}
+
+	struct RecordingFeeManager;
+	impl FeeManager for RecordingFeeManager {
+		fn is_waived(_: Option<&Location>, _: &Assets, _: FeeReason) -> bool {
+			false
+		}
+
+		fn handle_fee(fee: Assets, _: Option<&XcmContext>, _: FeeReason) -> Assets {
+			HANDLED_FEES.with(|f| f.borrow_mut().push(fee));
+			Assets::new()
+		}
+	}
+
+	/// Handles the native part of the fees with `RecordingFeeManager` and returns the rest.
+	struct NativeOnlyFeeManager;
+	impl FeeManager for NativeOnlyFeeManager {
+		fn is_waived(_: Option<&Location>, _: &Assets, _: FeeReason) -> bool {
+			false
+		}
+
+		fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+			let (native, other): (Vec<Asset>, Vec<Asset>) =
+				fee.into_inner().into_iter().partition(|asset| asset.id == AssetId(Here.into()));
+			RecordingFeeManager::handle_fee(native.into(), context, reason);
+			other.into()
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub const MaxFeesPerBlock: u128 = 100;
+		pub const CappedPrefix: &'static [u8] = b"CappedFeeManager";
+		pub const OtherCappedPrefix: &'static [u8] = b"OtherCappedFeeManager";
+	}
+
+	type CappedFeeManager =
+		BlockCappedFeeManager<MaxFeesPerBlock, RecordingFeeManager, TestBlockNumber, CappedPrefix>;
+
+	#[test]
+	fn block_capped_fee_manager_works() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let fee = |amount: u128| -> Assets { (Here, amount).into() };
+			let handled = || HANDLED_FEES.with(|f| f.borrow().len());
+
+			CappedFeeManager::handle_fee(fee(60), None, FeeReason::ChargeFees { caller: None });
+			CappedFeeManager::handle_fee(fee(40), None, FeeReason::ChargeFees { caller: None });
+			assert_eq!(CappedFeeManager::handled_in_block(), 100);
+			assert_eq!(handled(), 2);
+
+			// crossing the cap within the block is rejected
+			CappedFeeManager::handle_fee(fee(1), None, FeeReason::ChargeFees { caller: None });
+			assert_eq!(CappedFeeManager::handled_in_block(), 100);
+			assert_eq!(handled(), 2);
+
+			// the counter is reset in the next block
+			BLOCK_NUMBER.with(|b| b.set(2));
+			assert_eq!(CappedFeeManager::handled_in_block(), 0);
+			CappedFeeManager::handle_fee(fee(1), None, FeeReason::ChargeFees { caller: None });
+			assert_eq!(CappedFeeManager::handled_in_block(), 1);
+			assert_eq!(handled(), 3);
+
+			// the total is removed from the state at the end of the block
+			CappedFeeManager::on_finalize(2);
+			assert_eq!(CappedFeeManager::handled_in_block(), 0);
+			assert!(sp_io::storage::get(&CappedFeeManager::storage_key()).is_none());
+		});
+	}
+
+	#[test]
+	fn block_capped_fee_manager_only_counts_handled_fees() {
+		type NativeCappedFeeManager = BlockCappedFeeManager<
+			MaxFeesPerBlock,
+			NativeOnlyFeeManager,
+			TestBlockNumber,
+			CappedPrefix,
+		>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			let fee: Assets = vec![(Here, 30u128).into(), (Parent, 50u128).into()].into();
+			assert_eq!(
+				NativeCappedFeeManager::handle_fee(fee, None, FeeReason::Report),
+				(Parent, 50u128).into()
+			);
+			assert_eq!(NativeCappedFeeManager::handled_in_block(), 30);
+
+			// a fee which isn't handled at all isn't counted
+			assert_eq!(
+				NativeCappedFeeManager::handle_fee(
+					(Parent, 60u128).into(),
+					None,
+					FeeReason::Report
+				),
+				(Parent, 60u128).into()
+			);
+			assert_eq!(NativeCappedFeeManager::handled_in_block(), 30);
+		});
+	}
+
+	#[test]
+	fn block_capped_fee_managers_with_different_prefixes_dont_collide() {
+		type OtherCappedFeeManager = BlockCappedFeeManager<
+			MaxFeesPerBlock,
+			RecordingFeeManager,
+			TestBlockNumber,
+			OtherCappedPrefix,
+		>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			CappedFeeManager::handle_fee((Here, 100).into(), None, FeeReason::Report);
+			assert_eq!(CappedFeeManager::handled_in_block(), 100);
+			assert_eq!(OtherCappedFeeManager::handled_in_block(), 0);
+
+			// the other cap is not exhausted by the first one
+			assert!(OtherCappedFeeManager::handle_fee((Here, 50).into(), None, FeeReason::Report)
+				.is_empty());
+			assert_eq!(OtherCappedFeeManager::handled_in_block(), 50);
+			assert_eq!(CappedFeeManager::handled_in_block(), 100);
+		});
+	}
+
+	thread_local! {
+		static FEE_ERRORS: RefCell<Vec<(Assets, FeeReason, XcmError)>> = RefCell::new(Vec::new());
+	}
+
+	struct RejectingTransactor;
+	impl TransactAsset for RejectingTransactor {
+		fn deposit_asset(_: &Asset, _: &Location, _: Option<&XcmContext>) -> XcmResult {
+			Err(XcmError::FailedToTransactAsset("frozen"))
+		}
+	}
+
+	struct RecordingErrors;
+	impl FeeManager for RecordingErrors {
+		fn is_waived(_: Option<&Location>, _: &Assets, _: FeeReason) -> bool {
+			false
+		}
+
+		fn handle_fee(_: Assets, _: Option<&XcmContext>, _: FeeReason) -> Assets {
+			Assets::new()
+		}
+
+		fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+			FEE_ERRORS.with(|e| e.borrow_mut().push((assets.clone(), reason, error)));
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub FeeReceiver: Location = Location::new(0, [AccountId32 { network: None, id: [1; 32] }]);
+	}
+
+	#[test]
+	fn fee_error_hook_is_called_when_deposit_fails() {
+		type FeeHandler = SendXcmFeeToAccount<RejectingTransactor, FeeReceiver, RecordingErrors>;
+
+		let fee: Assets = vec![(Here, 10).into(), (Parent, 20).into()].into();
+		// the fee couldn't be deposited, so it's returned unhandled instead of being burned
+		assert_eq!(FeeHandler::handle_fee(fee.clone(), None, FeeReason::Report), fee);
+
+		FEE_ERRORS.with(|e| {
+			assert_eq!(
+				*e.borrow(),
+				vec![
+					(
+						(Here, 10).into(),
+						FeeReason::Report,
+						XcmError::FailedToTransactAsset("frozen")
+					),
+					(
+						(Parent, 20).into(),
+						FeeReason::Report,
+						XcmError::FailedToTransactAsset("frozen")
+					),
+				]
+			)
+		});
+	}
+
+	thread_local! {
+		static DEPOSITS: RefCell<Vec<(Asset, Location)>> = RefCell::new(Vec::new());
+	}
+
+	struct RecordingTransactor;
+	impl TransactAsset for RecordingTransactor {
+		fn deposit_asset(what: &Asset, who: &Location, _: Option<&XcmContext>) -> XcmResult {
+			DEPOSITS.with(|d| d.borrow_mut().push((what.clone(), who.clone())));
+			Ok(())
+		}
+	}
+
+	/// Converts sibling parachain locations into accounts.
+	struct SiblingAccountOf;
+	impl ConvertLocation<[u8; 32]> for SiblingAccountOf {
+		fn convert_location(location: &Location) -> Option<[u8; 32]> {
+			match location.unpack() {
+				(1, [Parachain(id)]) => Some([*id as u8; 32]),
+				_ => None,
+			}
+		}
+	}
+
+	type RefundFeeManager = RefundToOriginSovereign<
+		SiblingAccountOf,
+		RecordingTransactor,
+		RecordingFeeManager,
+		[u8; 32],
+	>;
+
+	#[test]
+	fn refund_to_origin_sovereign_deposits_to_resolved_origin() {
+		let context = XcmContext {
+			origin: Some(Location::new(1, [Parachain(7)])),
+			message_id: [0; 32],
+			topic: None,
+		};
+		RefundFeeManager::handle_fee((Here, 10).into(), Some(&context), FeeReason::Report);
+
+		DEPOSITS.with(|d| {
+			assert_eq!(
+				*d.borrow(),
+				vec![(
+					(Here, 10).into(),
+					Location::new(0, [AccountId32 { network: None, id: [7; 32] }])
+				)]
+			)
+		});
+		assert!(HANDLED_FEES.with(|f| f.borrow().is_empty()));
+	}
+
+	#[test]
+	fn refund_to_origin_sovereign_falls_back_to_inner() {
+		let context = XcmContext {
+			origin: Some(Location::new(0, [AccountId32 { network: None, id: [1; 32] }])),
+			message_id: [0; 32],
+			topic: None,
+		};
+		RefundFeeManager::handle_fee((Here, 10).into(), Some(&context), FeeReason::Report);
+		RefundFeeManager::handle_fee((Here, 20).into(), None, FeeReason::Report);
+
+		assert!(DEPOSITS.with(|d| d.borrow().is_empty()));
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![(Here, 10).into(), (Here, 20).into()]));
+	}
+
+	thread_local! {
+		static BLOCK_AUTHOR: Cell<Option<[u8; 32]>> = Cell::new(None);
+	}
+
+	struct TestAuthor;
+	impl Get<Option<[u8; 32]>> for TestAuthor {
+		fn get() -> Option<[u8; 32]> {
+			BLOCK_AUTHOR.with(|a| a.get())
+		}
+	}
+
+	type RewardFeeManager =
+		CollatorRewardFeeManager<RecordingFeeManager, TestAuthor, RecordingTransactor, [u8; 32]>;
+
+	#[test]
+	fn collator_reward_fee_manager_deposits_to_author() {
+		BLOCK_AUTHOR.with(|a| a.set(Some([3; 32])));
+		RewardFeeManager::handle_fee((Here, 10).into(), None, FeeReason::Report);
+
+		DEPOSITS.with(|d| {
+			assert_eq!(
+				*d.borrow(),
+				vec![(
+					(Here, 10).into(),
+					Location::new(0, [AccountId32 { network: None, id: [3; 32] }])
+				)]
+			)
+		});
+		assert!(HANDLED_FEES.with(|f| f.borrow().is_empty()));
+	}
+
+	#[test]
+	fn collator_reward_fee_manager_falls_back_to_inner() {
+		RewardFeeManager::handle_fee((Here, 10).into(), None, FeeReason::Report);
+
+		assert!(DEPOSITS.with(|d| d.borrow().is_empty()));
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![(Here, 10).into()]));
+	}
+
+	thread_local! {
+		static FEE_EVENTS: RefCell<Vec<(FeeReason, Assets)>> = RefCell::new(Vec::new());
+	}
+
+	struct TestEventEmitter;
+	impl EmitFeeEvent for TestEventEmitter {
+		fn emit(reason: FeeReason, assets: Assets) {
+			FEE_EVENTS.with(|e| e.borrow_mut().push((reason, assets)));
+		}
+	}
+
+	#[test]
+	fn event_emitting_fee_manager_emits_event() {
+		type EmittingFeeManager = EventEmittingFeeManager<RecordingFeeManager, TestEventEmitter>;
+
+		let fee: Assets = vec![(Here, 10).into(), (Parent, 20).into()].into();
+		EmittingFeeManager::handle_fee(fee.clone(), None, FeeReason::TransferReserveAsset);
+
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![fee.clone()]));
+		FEE_EVENTS.with(|e| assert_eq!(*e.borrow(), vec![(FeeReason::TransferReserveAsset, fee)]));
+	}
+
+	#[test]
+	fn event_emitting_fee_manager_only_emits_handled_fees() {
+		type EmittingFeeManager = EventEmittingFeeManager<NativeOnlyFeeManager, TestEventEmitter>;
+
+		let fee: Assets = vec![(Here, 10).into(), (Parent, 20).into()].into();
+		assert_eq!(
+			EmittingFeeManager::handle_fee(fee, None, FeeReason::Report),
+			(Parent, 20).into()
+		);
+		FEE_EVENTS.with(|e| assert_eq!(*e.borrow(), vec![(FeeReason::Report, (Here, 10).into())]));
+
+		// a fee which isn't handled at all isn't emitted
+		EmittingFeeManager::handle_fee((Parent, 30).into(), None, FeeReason::Report);
+		assert_eq!(FEE_EVENTS.with(|e| e.borrow().len()), 1);
+	}
+
+	#[test]
+	fn per_origin_multiplier_fee_manager_scales_fees() {
+		struct TestMultipliers;
+		impl Convert<Location, Option<FixedU128>> for TestMultipliers {
+			fn convert(origin: Location) -> Option<FixedU128> {
+				match origin.unpack() {
+					(1, [Parachain(1000)]) => Some(FixedU128::from_rational(1, 2)),
+					(1, [Parachain(2000)]) => Some(FixedU128::from_u32(3)),
+					_ => None,
+				}
+			}
+		}
+		type MultiplierFeeManager =
+			PerOriginMultiplierFeeManager<TestMultipliers, RecordingFeeManager>;
+
+		let handle_from = |origin: Location| {
+			let context = XcmContext { origin: Some(origin), message_id: [0; 32], topic: None };
+			MultiplierFeeManager::handle_fee((Here, 10).into(), Some(&context), FeeReason::Report)
+		};
+		// the scaled-off part of the fee is returned unhandled
+		assert_eq!(handle_from(Location::new(1, [Parachain(1000)])), (Here, 5).into());
+		// multipliers above one are ignored, they would mint value
+		assert!(handle_from(Location::new(1, [Parachain(2000)])).is_none());
+		// origin without a multiplier is charged the unscaled fee
+		assert!(handle_from(Location::new(1, [Parachain(3000)])).is_none());
+
+		HANDLED_FEES.with(|f| {
+			assert_eq!(*f.borrow(), vec![(Here, 5).into(), (Here, 10).into(), (Here, 10).into()])
+		});
+	}
+
+	#[test]
+	fn version_split_fee_manager_routes_by_origin_version() {
+		struct TestVersions;
+		impl GetVersion for TestVersions {
+			fn get_version_for(dest: &Location) -> Option<XcmVersion> {
+				match dest.unpack() {
+					(1, [Parachain(1000)]) => Some(4),
+					(1, [Parachain(2000)]) => Some(5),
+					_ => None,
+				}
+			}
+		}
+		frame_support::parameter_types! {
+			pub const Threshold: XcmVersion = 5;
+		}
+		type SplitFeeManager = VersionSplitFeeManager<
+			RecordingFeeManager,
+			EventEmittingFeeManager<RecordingErrors, TestEventEmitter>,
+			Threshold,
+			TestVersions,
+		>;
+
+		let handle_from = |origin: Location, amount: u128| {
+			let context = XcmContext { origin: Some(origin), message_id: [0; 32], topic: None };
+			SplitFeeManager::handle_fee((Here, amount).into(), Some(&context), FeeReason::Report);
+		};
+		handle_from(Location::new(1, [Parachain(1000)]), 10);
+		handle_from(Location::new(1, [Parachain(2000)]), 20);
+		// origin with unknown version is handled by the new manager
+		handle_from(Location::new(1, [Parachain(3000)]), 30);
+
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![(Here, 10).into()]));
+		FEE_EVENTS.with(|e| {
+			assert_eq!(
+				*e.borrow(),
+				vec![
+					(FeeReason::Report, (Here, 20).into()),
+					(FeeReason::Report, (Here, 30).into())
+				]
+			)
+		});
+	}
+
+	#[test]
+	fn reserve_consistent_fee_manager_rejects_inconsistent_assets_before_charging() {
+		struct RecordingFeesAndErrors;
+		impl FeeManager for RecordingFeesAndErrors {
+			fn is_waived(_: Option<&Location>, _: &Assets, _: FeeReason) -> bool {
+				false
+			}
+
+			fn handle_fee(fee: Assets, context: Option<&XcmContext>, reason: FeeReason) -> Assets {
+				RecordingFeeManager::handle_fee(fee, context, reason)
+			}
+
+			fn on_handle_fee_error(assets: &Assets, reason: FeeReason, error: XcmError) {
+				RecordingErrors::on_handle_fee_error(assets, reason, error)
+			}
+		}
+		type ReserveFeeManager =
+			ReserveConsistentFeeManager<crate::NativeAsset, RecordingFeesAndErrors>;
+
+		let sibling = Location::new(1, [Parachain(1000)]);
+
+		// the sibling is the reserve of its native asset
+		let fee: Assets = (sibling.clone(), 10).into();
+		assert_eq!(ReserveFeeManager::check_fee(Some(&sibling), &fee, FeeReason::Report), Ok(()));
+		assert!(FEE_ERRORS.with(|e| e.borrow().is_empty()));
+
+		// but not of the relay chain asset, so such fees are rejected before being charged
+		let fee: Assets = vec![(sibling.clone(), 10).into(), (Parent, 20).into()].into();
+		assert_eq!(
+			ReserveFeeManager::check_fee(Some(&sibling), &fee, FeeReason::Report),
+			Err(XcmError::UntrustedReserveLocation)
+		);
+		FEE_ERRORS.with(|e| {
+			assert_eq!(
+				*e.borrow(),
+				vec![((Parent, 20).into(), FeeReason::Report, XcmError::UntrustedReserveLocation)]
+			)
+		});
+
+		// local origins pay the native asset without being its reserve
+		let local = Location::new(0, [AccountId32 { network: None, id: [1; 32] }]);
+		let fee: Assets = (Here, 30).into();
+		assert_eq!(ReserveFeeManager::check_fee(Some(&local), &fee, FeeReason::Report), Ok(()));
+		assert_eq!(FEE_ERRORS.with(|e| e.borrow().len()), 1);
+
+		// accepted fees are handed to `Inner` unchecked
+		let context = XcmContext { origin: Some(sibling), message_id: [0; 32], topic: None };
+		assert!(ReserveFeeManager::handle_fee(fee.clone(), Some(&context), FeeReason::Report)
+			.is_empty());
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![fee]));
+	}
+
+	frame_support::parameter_types! {
+		pub DeliveryReserve: Location =
+			Location::new(0, [AccountId32 { network: None, id: [2; 32] }]);
+		pub const DeliveryShare: Perbill = Perbill::from_percent(20);
+	}
+
+	#[test]
+	fn delivery_reserve_fee_manager_splits_fees() {
+		type ReserveFeeManager = DeliveryReserveFeeManager<
+			DeliveryReserve,
+			DeliveryShare,
+			RecordingTransactor,
+			RecordingFeeManager,
+		>;
+		let reserve_balance = || {
+			DEPOSITS.with(|d| {
+				d.borrow()
+					.iter()
+					.filter(|(_, who)| *who == DeliveryReserve::get())
+					.map(|(what, _)| match what.fun {
+						Fungible(amount) => amount,
+						NonFungible(_) => 0,
+					})
+					.sum::<u128>()
+			})
+		};
+
+		ReserveFeeManager::handle_fee((Here, 100).into(), None, FeeReason::Report);
+		assert_eq!(reserve_balance(), 20);
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![(Here, 80).into()]));
+
+		// the reserve grows with every handled fee
+		ReserveFeeManager::handle_fee((Here, 50).into(), None, FeeReason::Report);
+		assert_eq!(reserve_balance(), 30);
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![(Here, 80).into(), (Here, 40).into()]));
+	}
+
+	#[test]
+	fn failed_deposits_are_returned_unhandled() {
+		type RejectingRefund = RefundToOriginSovereign<
+			SiblingAccountOf,
+			RejectingTransactor,
+			RecordingErrors,
+			[u8; 32],
+		>;
+		type RejectingReward =
+			CollatorRewardFeeManager<RecordingErrors, TestAuthor, RejectingTransactor, [u8; 32]>;
+		type RejectingReserve = DeliveryReserveFeeManager<
+			DeliveryReserve,
+			DeliveryShare,
+			RejectingTransactor,
+			RecordingFeeManager,
+		>;
+
+		let context = XcmContext {
+			origin: Some(Location::new(1, [Parachain(7)])),
+			message_id: [0; 32],
+			topic: None,
+		};
+		assert_eq!(
+			RejectingRefund::handle_fee((Here, 10).into(), Some(&context), FeeReason::Report),
+			(Here, 10).into()
+		);
+
+		BLOCK_AUTHOR.with(|a| a.set(Some([3; 32])));
+		assert_eq!(
+			RejectingReward::handle_fee((Here, 10).into(), None, FeeReason::Report),
+			(Here, 10).into()
+		);
+		assert_eq!(FEE_ERRORS.with(|e| e.borrow().len()), 2);
+
+		// only the share which couldn't be deposited to the reserve is returned
+		assert_eq!(
+			RejectingReserve::handle_fee((Here, 100).into(), None, FeeReason::Report),
+			(Here, 20).into()
+		);
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![(Here, 80).into()]));
+	}
+
+	frame_support::parameter_types! {
+		pub const SampleRate: u32 = 10;
+	}
+
+	#[test]
+	fn sampled_tracing_fee_manager_samples_fraction_of_fees() {
+		type SampledFeeManager = SampledTracingFeeManager<RecordingFeeManager, SampleRate>;
+		let context = |i: u32| XcmContext {
+			origin: None,
+			message_id: sp_io::hashing::blake2_256(&i.to_le_bytes()),
+			topic: None,
+		};
+
+		sp_io::TestExternalities::default().execute_with(|| {
+			let sampled = (0..10_000u32)
+				.filter(|i| SampledFeeManager::should_sample(Some(&context(*i))))
+				.count();
+			assert!((800..=1200).contains(&sampled), "sampled {sampled} of 10000 fees");
+
+			// the selection is stable for a message
+			assert_eq!(
+				SampledFeeManager::should_sample(Some(&context(7))),
+				SampledFeeManager::should_sample(Some(&context(7))),
+			);
+			// fees without a context are never sampled
+			assert!(!SampledFeeManager::should_sample(None));
+
+			// every fee is delegated, sampled or not
+			for i in 0..20 {
+				SampledFeeManager::handle_fee(
+					(Here, 1).into(),
+					Some(&context(i)),
+					FeeReason::Report,
+				);
+			}
+			HANDLED_FEES.with(|f| assert_eq!(f.borrow().len(), 20));
+
+			// sampling doesn't write to storage
+			assert_eq!(sp_io::storage::next_key(&[]), None);
+		});
+	}
+
+	struct IsExport;
+	impl Contains<FeeReason> for IsExport {
+		fn contains(reason: &FeeReason) -> bool {
+			matches!(reason, FeeReason::Export { .. })
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub const ExportCapacity: u32 = 2;
+		pub const ExportRefillPerBlock: u32 = 1;
+		pub const ExportLimiterPrefix: &'static [u8] = b"ExportLimiter";
+		pub const OtherLimiterPrefix: &'static [u8] = b"OtherLimiter";
+	}
+
+	type ExportLimiter = TokenBucketLimiter<
+		ExportCapacity,
+		ExportRefillPerBlock,
+		TestBlockNumber,
+		ExportLimiterPrefix,
+	>;
+
+	#[test]
+	fn throttle_by_reason_fee_manager_exhausts_bucket() {
+		type Limiter = ExportLimiter;
+		type ThrottledFeeManager =
+			ThrottleByReasonFeeManager<IsExport, Limiter, RecordingFeeManager>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			let export = || FeeReason::Export { network: Polkadot, destination: Here };
+			let fee: Assets = (Here, 10).into();
+			let check = |reason| ThrottledFeeManager::check_fee(None, &fee, reason);
+
+			// the bucket starts full
+			assert_eq!(Limiter::available(), 2);
+			assert_eq!(check(export()), Ok(()));
+			assert_eq!(check(export()), Ok(()));
+
+			// the empty bucket rejects further exports
+			assert_eq!(Limiter::available(), 0);
+			assert_eq!(check(export()), Err(XcmError::NoPermission));
+
+			// however they are paid
+			let paid_export = FeeReason::PayFees { charged_for: Box::new(export()) };
+			assert_eq!(check(paid_export), Err(XcmError::NoPermission));
+
+			// other reasons are not throttled
+			assert_eq!(check(FeeReason::Report), Ok(()));
+
+			// the bucket is refilled in later blocks, up to its capacity
+			BLOCK_NUMBER.with(|b| b.set(2));
+			assert_eq!(Limiter::available(), 1);
+			assert_eq!(check(export()), Ok(()));
+			assert_eq!(check(export()), Err(XcmError::NoPermission));
+			BLOCK_NUMBER.with(|b| b.set(10));
+			assert_eq!(Limiter::available(), 2);
+
+			// fees are always handled by the inner fee manager
+			assert!(ThrottledFeeManager::handle_fee(fee.clone(), None, export()).is_empty());
+			assert_eq!(HANDLED_FEES.with(|f| f.borrow().len()), 1);
+			assert_eq!(Limiter::available(), 2);
+		});
+	}
+
+	#[test]
+	fn token_bucket_limiters_with_different_prefixes_dont_collide() {
+		type OtherLimiter = TokenBucketLimiter<
+			ExportCapacity,
+			ExportRefillPerBlock,
+			TestBlockNumber,
+			OtherLimiterPrefix,
+		>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert!(ExportLimiter::try_acquire());
+			assert!(ExportLimiter::try_acquire());
+			assert!(!ExportLimiter::try_acquire());
+
+			// the other bucket is not drained by the first one
+			assert_eq!(OtherLimiter::available(), 2);
+			assert!(OtherLimiter::try_acquire());
+			assert_eq!(OtherLimiter::available(), 1);
+			assert_eq!(ExportLimiter::available(), 0);
+		});
+	}
+
+	frame_support::parameter_types! {
+		pub const WindowStart: u32 = 10;
+		pub const WindowEnd: u32 = 20;
+	}
+
+	#[test]
+	fn time_window_waiver_works() {
+		type Waiver =
+			TimeWindowWaiver<WindowStart, WindowEnd, TestBlockNumber, RecordingFeeManager>;
+		let fee: Assets = (Here, 10).into();
+		let waived = || {
+			Waiver::is_waived(Some(&Location::parent()), &fee, FeeReason::Report) &&
+				Waiver::is_waived(None, &fee, FeeReason::ChargeFees { caller: None })
+		};
+
+		// before the window
+		BLOCK_NUMBER.with(|b| b.set(9));
+		assert!(!Waiver::is_waived(Some(&Location::parent()), &fee, FeeReason::Report));
+
+		// during the window, any origin is waived
+		for block in [10, 15, 19] {
+			BLOCK_NUMBER.with(|b| b.set(block));
+			assert!(waived(), "block {block}");
+		}
+
+		// after the window
+		BLOCK_NUMBER.with(|b| b.set(20));
+		assert!(!Waiver::is_waived(Some(&Location::parent()), &fee, FeeReason::Report));
+
+		// fees are handled by `Inner` regardless of the window
+		assert!(Waiver::handle_fee(fee.clone(), None, FeeReason::Report).is_empty());
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![fee.clone()]));
+	}
+
+	thread_local! {
+		static FEE_ANOMALIES: RefCell<Vec<(u128, u128)>> = RefCell::new(Vec::new());
+	}
+
+	struct TestAnomalies;
+	impl RecordFeeAnomaly for TestAnomalies {
+		fn record(_: &FeeReason, amount: u128, baseline: u128) {
+			FEE_ANOMALIES.with(|a| a.borrow_mut().push((amount, baseline)));
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub const BaselineWeight: u32 = 2;
+		pub const AnomalyFactor: u32 = 5;
+		pub const BaselinePrefix: &'static [u8] = b"FeeBaseline";
+		pub const OtherBaselinePrefix: &'static [u8] = b"OtherFeeBaseline";
+	}
+
+	#[test]
+	fn anomaly_detecting_fee_manager_works() {
+		type Baseline = MovingAverageFeeBaseline<BaselineWeight, BaselinePrefix>;
+		type DetectingFeeManager =
+			AnomalyDetectingFeeManager<RecordingFeeManager, Baseline, AnomalyFactor, TestAnomalies>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			let handle = |amount: u128| {
+				DetectingFeeManager::handle_fee((Here, amount).into(), None, FeeReason::Report)
+			};
+			let anomalies = || FEE_ANOMALIES.with(|a| a.borrow().clone());
+
+			for _ in 0..3 {
+				handle(10);
+			}
+			assert_eq!(Baseline::baseline(), Some(10));
+			assert!(anomalies().is_empty());
+
+			// a spike is detected, but still handled
+			assert!(handle(100).is_empty());
+			assert_eq!(anomalies(), vec![(100, 10)]);
+			assert_eq!(HANDLED_FEES.with(|f| f.borrow().len()), 4);
+
+			// the baseline moves towards the spike
+			assert_eq!(Baseline::baseline(), Some(55));
+			handle(100);
+			assert_eq!(anomalies().len(), 1);
+		});
+	}
+
+	#[test]
+	fn moving_average_fee_baselines_with_different_prefixes_dont_collide() {
+		type Baseline = MovingAverageFeeBaseline<BaselineWeight, BaselinePrefix>;
+		type OtherBaseline = MovingAverageFeeBaseline<BaselineWeight, OtherBaselinePrefix>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			Baseline::note(10);
+			assert_eq!(Baseline::baseline(), Some(10));
+			assert_eq!(OtherBaseline::baseline(), None);
+
+			OtherBaseline::note(100);
+			assert_eq!(OtherBaseline::baseline(), Some(100));
+			assert_eq!(Baseline::baseline(), Some(10));
+		});
+	}
+
+	#[test]
+	fn fee_manager_macro_works() {
+		type Stacked = crate::fee_manager!(
+			ClassifiedMeteredFeeManager<_, TestMetrics>;
+			TimeWindowWaiver<WindowStart, WindowEnd, TestBlockNumber, _>;
+			RecordingFeeManager
+		);
+		type HandWritten = ClassifiedMeteredFeeManager<
+			TimeWindowWaiver<WindowStart, WindowEnd, TestBlockNumber, RecordingFeeManager>,
+			TestMetrics,
+		>;
+		assert_eq!(core::any::TypeId::of::<Stacked>(), core::any::TypeId::of::<HandWritten>());
+
+		let fee: Assets = (Here, 10).into();
+		for block in [5, 15] {
+			BLOCK_NUMBER.with(|b| b.set(block));
+			assert_eq!(
+				Stacked::is_waived(Some(&Location::parent()), &fee, FeeReason::Report),
+				HandWritten::is_waived(Some(&Location::parent()), &fee, FeeReason::Report),
+			);
+		}
+		assert!(Stacked::handle_fee(fee.clone(), None, FeeReason::Report).is_empty());
+		assert!(HandWritten::handle_fee(fee.clone(), None, FeeReason::Report).is_empty());
+		HANDLED_FEES.with(|f| assert_eq!(*f.borrow(), vec![fee.clone(), fee]));
+	}
+}
@@ -59,7 +59,13 @@ pub use currency_adapter::CurrencyAdapter;
 
 mod fee_handling;
 pub use fee_handling::{
-	deposit_or_burn_fee, HandleFee, SendXcmFeeToAccount, XcmFeeManagerFromComponents,
+	deposit_or_burn_fee, AnomalyDetectingFeeManager, BlockCappedFeeManager,
+	ClassifiedMeteredFeeManager, CollatorRewardFeeManager, DeliveryReserveFeeManager, EmitFeeEvent,
+	EventEmittingFeeManager, FeeBaseline, FeeOriginKind, HandleFee, MovingAverageFeeBaseline,
+	PerOriginMultiplierFeeManager, RateLimiter, RecordFeeAnomaly, RecordFeeMetrics,
+	RefundToOriginSovereign, ReserveConsistentFeeManager, SampledTracingFeeManager,
+	SendXcmFeeToAccount, ThrottleByReasonFeeManager, TimeWindowWaiver, TokenBucketLimiter,
+	VersionSplitFeeManager, XcmFeeManagerFromComponents,
 };
 
 mod filter_asset_location;
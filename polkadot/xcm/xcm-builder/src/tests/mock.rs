@@ -19,7 +19,7 @@
 use crate::{
 	barriers::{AllowSubscriptionsFrom, RespectSuspension, TrailingSetTopicAsId},
 	test_utils::*,
-	EnsureDecodableXcm,
+	EnsureDecodableXcm, RateLimiter, ThrottleByReasonFeeManager,
 };
 pub use crate::{
 	AliasChildLocation, AliasForeignAccountId32, AllowExplicitUnpaidExecutionFrom,
@@ -523,11 +523,38 @@ pub fn set_fee_waiver(waived: Vec<FeeReason>) {
 
 pub struct TestFeeManager;
 impl FeeManager for TestFeeManager {
-	fn is_waived(_: Option<&Location>, r: FeeReason) -> bool {
+	fn is_waived(_: Option<&Location>, _: &Assets, r: FeeReason) -> bool {
 		IS_WAIVED.with(|l| l.borrow().contains(&r))
 	}
 
-	fn handle_fee(_: Assets, _: Option<&XcmContext>, _: FeeReason) {}
+	fn handle_fee(_: Assets, _: Option<&XcmContext>, _: FeeReason) -> Assets {
+		Assets::new()
+	}
+}
+
+thread_local! {
+	pub static EXPORT_PERMITS: Cell<Option<u32>> = Cell::new(None);
+}
+/// Limit the number of exports paying fees to `permits`, or don't limit them if `None`.
+pub fn set_export_permits(permits: Option<u32>) {
+	EXPORT_PERMITS.with(|p| p.set(permits));
+}
+
+pub struct TestExportLimiter;
+impl RateLimiter for TestExportLimiter {
+	fn try_acquire() -> bool {
+		EXPORT_PERMITS.with(|p| match p.get() {
+			Some(permits) => permits.checked_sub(1).map(|left| p.set(Some(left))).is_some(),
+			None => true,
+		})
+	}
+}
+
+pub struct IsExportFee;
+impl Contains<FeeReason> for IsExportFee {
+	fn contains(reason: &FeeReason) -> bool {
+		matches!(reason, FeeReason::Export { .. })
+	}
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -756,7 +783,7 @@ impl Config for TestConfig {
 	type SubscriptionService = TestSubscriptionService;
 	type PalletInstancesInfo = TestPalletsInfo;
 	type MaxAssetsIntoHolding = MaxAssetsIntoHolding;
-	type FeeManager = TestFeeManager;
+	type FeeManager = ThrottleByReasonFeeManager<IsExportFee, TestExportLimiter, TestFeeManager>;
 	type UniversalAliases = TestUniversalAliases;
 	type MessageExporter = TestMessageExporter;
 	type CallDispatcher = TestCall;
@@ -108,6 +108,58 @@ fn export_message_should_work() {
 	);
 }
 
+#[test]
+fn throttled_export_should_fail_without_charging_fee() {
+	AllowUnpaidFrom::set(vec![[Parachain(1)].into()]);
+	set_exporter_override(
+		|_, _, _, _, _| Ok((Here, 10u128).into()),
+		|network, channel, uni_src, dest, msg| {
+			let hash = fake_message_hash(&msg);
+			EXPORTED_XCM
+				.with(|q| q.borrow_mut().push((network, channel, uni_src, dest, msg, hash)));
+			Ok(hash)
+		},
+	);
+	// Only a single export may pay its fee.
+	set_export_permits(Some(1));
+	add_asset(Parachain(1), (Here, 200u128));
+	let message = Xcm(vec![
+		WithdrawAsset((Here, 100u128).into()),
+		ExportMessage { network: Polkadot, destination: Here, xcm: Xcm(vec![]) },
+		DepositAsset { assets: AllCounted(1).into(), beneficiary: Parachain(1).into() },
+	]);
+
+	let mut hash = fake_message_hash(&message);
+	let r = XcmExecutor::<TestConfig>::prepare_and_execute(
+		Parachain(1),
+		message.clone(),
+		&mut hash,
+		Weight::from_parts(50, 50),
+		Weight::zero(),
+	);
+	assert_eq!(r, Outcome::Complete { used: Weight::from_parts(30, 30) });
+	assert_eq!(exported_xcm().len(), 1);
+	assert_eq!(asset_list(Parachain(1)), vec![(Here, 190u128).into()]);
+
+	// The second export is throttled: it fails before its fee is charged, so the whole withdrawn
+	// amount is trapped for the sender and no fee is trapped for it to claim.
+	let mut hash = fake_message_hash(&message);
+	let r = XcmExecutor::<TestConfig>::prepare_and_execute(
+		Parachain(1),
+		message,
+		&mut hash,
+		Weight::from_parts(50, 50),
+		Weight::zero(),
+	);
+	assert_eq!(
+		r,
+		Outcome::Incomplete { used: Weight::from_parts(35, 35), error: XcmError::NoPermission }
+	);
+	assert_eq!(exported_xcm().len(), 1);
+	assert_eq!(asset_list(Parachain(1)), vec![(Here, 90u128).into()]);
+	assert_eq!(TrappedAssets::get(), vec![(Parachain(1).into(), (Here, 100u128).into())]);
+}
+
 #[test]
 fn unpaid_execution_should_work() {
 	// Bridge chain (assumed to be Relay) lets Parachain #1 have message execution for free.
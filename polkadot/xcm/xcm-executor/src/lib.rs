@@ -18,7 +18,7 @@
 
 extern crate alloc;
 
-use alloc::{vec, vec::Vec};
+use alloc::{boxed::Box, vec, vec::Vec};
 use codec::{Decode, Encode};
 use core::{fmt::Debug, marker::PhantomData};
 use frame_support::{
@@ -99,6 +99,8 @@ pub struct XcmExecutor<Config: config::Config> {
 	/// Stores the current message's weight.
 	message_weight: Weight,
 	asset_claimer: Option<Location>,
+	/// The weight of trapping the fees which weren't handled by the `FeeManager`.
+	trapped_fees_weight: Weight,
 	_config: PhantomData<Config>,
 }
 
@@ -293,15 +295,60 @@ impl<Config: config::Config> ExecuteXcm<Config::RuntimeCall> for XcmExecutor<Con
 	}
 
 	fn charge_fees(origin: impl Into<Location>, fees: Assets) -> XcmResult {
-		let origin = origin.into();
-		if !Config::FeeManager::is_waived(Some(&origin), FeeReason::ChargeFees) {
+		Self::charge_fees_with_caller(origin.into(), fees, None)
+	}
+
+	fn charge_fees_for(
+		caller: &'static str,
+		origin: impl Into<Location>,
+		fees: Assets,
+	) -> XcmResult {
+		Self::charge_fees_with_caller(origin.into(), fees, Some(caller))
+	}
+}
+
+impl<Config: config::Config> XcmExecutor<Config> {
+	fn charge_fees_with_caller(
+		origin: Location,
+		fees: Assets,
+		caller: Option<&'static str>,
+	) -> XcmResult {
+		let reason = FeeReason::ChargeFees { caller };
+		if !Config::FeeManager::is_waived(Some(&origin), &fees, reason.clone()) {
+			Config::FeeManager::check_fee(Some(&origin), &fees, reason.clone())?;
 			for asset in fees.inner() {
 				Config::AssetTransactor::withdraw_asset(&asset, &origin, None)?;
 			}
-			Config::FeeManager::handle_fee(fees.into(), None, FeeReason::ChargeFees);
+			let unhandled = Config::FeeManager::handle_fee(fees.into(), None, reason);
+			// Fees charged outside of a message have no weight to account for trapping them, so
+			// the part which wasn't handled is burned.
+			if !unhandled.is_none() {
+				tracing::warn!(
+					target: "xcm::fees",
+					?unhandled,
+					?origin,
+					"Burning charged fee not handled by the fee manager",
+				);
+			}
 		}
 		Ok(())
 	}
+
+	/// Trap the part of a fee which the fee manager didn't handle, so that it isn't burned.
+	///
+	/// The fee has been paid, so it is trapped for the local chain rather than for the payer and
+	/// only governance can claim it. Returns the weight of trapping it.
+	fn trap_unhandled_fee(unhandled: Assets, context: &XcmContext) -> Weight {
+		if unhandled.is_none() {
+			return Weight::zero()
+		}
+		tracing::trace!(
+			target: "xcm::fees",
+			?unhandled,
+			"Trapping fee not handled by the fee manager",
+		);
+		Config::AssetTrap::drop_assets(&Location::here(), unhandled.into(), context)
+	}
 }
 
 impl<Config: config::Config> XcmAssetTransfers for XcmExecutor<Config> {
@@ -311,13 +358,20 @@ impl<Config: config::Config> XcmAssetTransfers for XcmExecutor<Config> {
 }
 
 impl<Config: config::Config> FeeManager for XcmExecutor<Config> {
-	fn is_waived(origin: Option<&Location>, r: FeeReason) -> bool {
-		Config::FeeManager::is_waived(origin, r)
+	fn is_waived(origin: Option<&Location>, assets: &Assets, r: FeeReason) -> bool {
+		Config::FeeManager::is_waived(origin, assets, r)
+	}
+
+	fn check_fee(origin: Option<&Location>, assets: &Assets, r: FeeReason) -> XcmResult {
+		Config::FeeManager::check_fee(origin, assets, r)
 	}
 
-	fn handle_fee(fee: Assets, context: Option<&XcmContext>, r: FeeReason) {
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, r: FeeReason) -> Assets {
 		Config::FeeManager::handle_fee(fee, context, r)
 	}
+	fn on_handle_fee_error(assets: &Assets, r: FeeReason, error: XcmError) {
+		Config::FeeManager::on_handle_fee_error(assets, r, error)
+	}
 }
 
 #[derive(Debug, PartialEq)]
@@ -362,6 +416,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 			asset_used_in_buy_execution: None,
 			message_weight: Weight::zero(),
 			asset_claimer: None,
+			trapped_fees_weight: Weight::zero(),
 			_config: PhantomData,
 		}
 	}
@@ -393,6 +448,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 			let trap_weight = Config::AssetTrap::drop_assets(claimer, self.holding, &self.context);
 			weight_used.saturating_accrue(trap_weight);
 		};
+		weight_used.saturating_accrue(self.trapped_fees_weight);
 
 		match self.error {
 			None => Outcome::Complete { used: weight_used },
@@ -521,9 +577,36 @@ impl<Config: config::Config> XcmExecutor<Config> {
 	}
 
 	fn take_fee(&mut self, fees: Assets, reason: FeeReason) -> XcmResult {
-		if Config::FeeManager::is_waived(self.origin_ref(), reason.clone()) {
+		// We only ever use the first asset from `fees`.
+		let asset_needed_for_fees = match fees.get(0) {
+			Some(fee) => fee,
+			None => return Ok(()), // No delivery fees need to be paid.
+		};
+		// If `BuyExecution` or `PayFees` was called, we use that asset for delivery fees as well.
+		let asset_to_pay_for_fees =
+			self.calculate_asset_for_delivery_fees(asset_needed_for_fees.clone());
+		// The waiver is checked against the asset which is actually charged.
+		let candidate_fee: Assets = asset_to_pay_for_fees.clone().into();
+		// Fees paid from the `fees` register or withdrawn just-in-time are only waived if the
+		// way they are paid is waived as well, and are handled with the reason of that way.
+		let payment_reason = if self.fees_mode.jit_withdraw {
+			Some(FeeReason::JitWithdraw { charged_for: Box::new(reason.clone()) })
+		} else if !self.fees.is_empty() {
+			Some(FeeReason::PayFees { charged_for: Box::new(reason.clone()) })
+		} else {
+			None
+		};
+		if Config::FeeManager::is_waived(self.origin_ref(), &candidate_fee, reason.clone()) &&
+			payment_reason.clone().map_or(true, |payment| {
+				Config::FeeManager::is_waived(self.origin_ref(), &candidate_fee, payment)
+			}) {
 			return Ok(())
 		}
+		Config::FeeManager::check_fee(
+			self.origin_ref(),
+			&candidate_fee,
+			payment_reason.clone().unwrap_or_else(|| reason.clone()),
+		)?;
 		tracing::trace!(
 			target: "xcm::fees",
 			?fees,
@@ -532,14 +615,6 @@ impl<Config: config::Config> XcmExecutor<Config> {
 			?reason,
 			"Taking fees",
 		);
-		// We only ever use the first asset from `fees`.
-		let asset_needed_for_fees = match fees.get(0) {
-			Some(fee) => fee,
-			None => return Ok(()), // No delivery fees need to be paid.
-		};
-		// If `BuyExecution` or `PayFees` was called, we use that asset for delivery fees as well.
-		let asset_to_pay_for_fees =
-			self.calculate_asset_for_delivery_fees(asset_needed_for_fees.clone());
 		tracing::trace!(target: "xcm::fees", ?asset_to_pay_for_fees);
 		// We withdraw or take from holding the asset the user wants to use for fee payment.
 		let withdrawn_fee_asset: AssetsInHolding = if self.fees_mode.jit_withdraw {
@@ -603,7 +678,13 @@ impl<Config: config::Config> XcmExecutor<Config> {
 			// We just use the assets withdrawn or taken from holding.
 			withdrawn_fee_asset.into()
 		};
-		Config::FeeManager::handle_fee(paid, Some(&self.context), reason);
+		let unhandled = Config::FeeManager::handle_fee(
+			paid,
+			Some(&self.context),
+			payment_reason.unwrap_or(reason),
+		);
+		let trap_weight = Self::trap_unhandled_fee(unhandled, &self.context);
+		self.trapped_fees_weight.saturating_accrue(trap_weight);
 		Ok(())
 	}
 
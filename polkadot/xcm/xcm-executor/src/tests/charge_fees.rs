@@ -0,0 +1,91 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit tests related to the `charge_fees` API.
+
+use xcm::prelude::*;
+
+use super::mock::*;
+use crate::{FeeReason, XcmExecutor};
+
+#[test]
+fn charge_fees_passes_caller_to_fee_manager() {
+	let sender = Location::new(0, [AccountId32 { id: [0; 32], network: None }]);
+	add_asset(sender.clone(), (Here, 100u128));
+
+	assert_eq!(
+		XcmExecutor::<XcmConfig>::charge_fees_for("test", sender.clone(), (Here, 10u128).into()),
+		Ok(())
+	);
+	assert_eq!(
+		XcmExecutor::<XcmConfig>::charge_fees(sender.clone(), (Here, 20u128).into()),
+		Ok(())
+	);
+
+	assert_eq!(
+		handled_fees(),
+		vec![
+			((Here, 10u128).into(), FeeReason::ChargeFees { caller: Some("test") }),
+			((Here, 20u128).into(), FeeReason::ChargeFees { caller: None }),
+		]
+	);
+	assert_eq!(asset_list(sender), vec![(Here, 70u128).into()]);
+}
+
+#[test]
+fn unhandled_charged_fees_are_burned() {
+	let sender = Location::new(0, [AccountId32 { id: [0; 32], network: None }]);
+	add_asset(sender.clone(), (Here, 100u128));
+	add_asset(sender.clone(), (Parent, 100u128));
+
+	assert_eq!(
+		XcmExecutor::<XcmConfig>::charge_fees(
+			sender.clone(),
+			vec![(Here, 10u128).into(), (Parent, 20u128).into()].into()
+		),
+		Ok(())
+	);
+
+	// the native fee was handled, the other one is burned as there is no message to account for
+	// trapping it
+	assert_eq!(
+		handled_fees(),
+		vec![((Here, 10u128).into(), FeeReason::ChargeFees { caller: None })]
+	);
+	assert_eq!(asset_list(TRAPPED_ASSETS), vec![]);
+	assert_eq!(asset_list(sender), vec![(Here, 90u128).into(), (Parent, 80u128).into()]);
+}
+
+#[test]
+fn charged_fees_can_be_waived_by_amount() {
+	let sender = Location::new(0, [AccountId32 { id: [0; 32], network: None }]);
+	add_asset(sender.clone(), (Parent, 100u128));
+
+	assert_eq!(
+		XcmExecutor::<XcmConfig>::charge_fees(sender.clone(), (Parent, WAIVED_PARENT_FEE).into()),
+		Ok(())
+	);
+	assert_eq!(asset_list(sender.clone()), vec![(Parent, 100u128).into()]);
+
+	assert_eq!(
+		XcmExecutor::<XcmConfig>::charge_fees(
+			sender.clone(),
+			(Parent, WAIVED_PARENT_FEE + 1).into()
+		),
+		Ok(())
+	);
+	assert_eq!(asset_list(sender), vec![(Parent, 94u128).into()]);
+}
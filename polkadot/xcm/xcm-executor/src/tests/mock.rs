@@ -30,8 +30,8 @@ use xcm::prelude::*;
 
 use crate::{
 	traits::{
-		DropAssets, FeeManager, Properties, ShouldExecute, TransactAsset, WeightBounds,
-		WeightTrader,
+		AssetExchange, DropAssets, FeeManager, Properties, ShouldExecute, TransactAsset,
+		WeightBounds, WeightTrader,
 	},
 	AssetsInHolding, Config, FeeReason, XcmExecutor,
 };
@@ -97,6 +97,9 @@ impl<C> WeightBounds<C> for TestWeigher {
 thread_local! {
 	pub static ASSETS: RefCell<BTreeMap<Location, AssetsInHolding>> = RefCell::new(BTreeMap::new());
 	pub static SENT_XCM: RefCell<Vec<(Location, Xcm<()>)>> = RefCell::new(Vec::new());
+	pub static HANDLED_FEES: RefCell<Vec<(Assets, FeeReason)>> = RefCell::new(Vec::new());
+	pub static TRAP_CLAIMERS: RefCell<Vec<Location>> = RefCell::new(Vec::new());
+	pub static TRAP_WEIGHT: RefCell<Weight> = RefCell::new(Weight::zero());
 }
 
 pub fn add_asset(who: impl Into<Location>, what: impl Into<Asset>) {
@@ -206,17 +209,28 @@ impl WeightTrader for TestTrader {
 /// Account where all dropped assets are deposited.
 pub const TRAPPED_ASSETS: [u8; 32] = [255; 32];
 
+/// Gets the locations the dropped assets were trapped for, in order.
+pub fn trap_claimers() -> Vec<Location> {
+	TRAP_CLAIMERS.with(|c| c.borrow().clone())
+}
+
+/// Sets the weight of trapping assets.
+pub fn set_trap_weight(weight: Weight) {
+	TRAP_WEIGHT.with(|w| *w.borrow_mut() = weight);
+}
+
 /// Test asset trap that moves all dropped assets to the `TRAPPED_ASSETS` account.
 pub struct TestAssetTrap;
 impl DropAssets for TestAssetTrap {
-	fn drop_assets(_origin: &Location, assets: AssetsInHolding, _context: &XcmContext) -> Weight {
+	fn drop_assets(origin: &Location, assets: AssetsInHolding, _context: &XcmContext) -> Weight {
 		ASSETS.with(|a| {
 			a.borrow_mut()
 				.entry(TRAPPED_ASSETS.into())
 				.or_insert(AssetsInHolding::new())
 				.subsume_assets(assets)
 		});
-		Weight::zero()
+		TRAP_CLAIMERS.with(|c| c.borrow_mut().push(origin.clone()));
+		TRAP_WEIGHT.with(|w| *w.borrow())
 	}
 }
 
@@ -250,21 +264,82 @@ pub fn sent_xcm() -> Vec<(Location, Xcm<()>)> {
 /// A mock contract address that doesn't need to pay for fees.
 pub const WAIVED_CONTRACT_ADDRESS: [u8; 20] = [128; 20];
 
+/// A mock sender whose fees are waived for reports, unless paid with `PayFees` or
+/// `SetFeesMode`.
+pub const REPORT_WAIVED_SENDER: [u8; 32] = [3; 32];
+
+/// Fees of at most this amount of the relay chain asset are waived.
+pub const WAIVED_PARENT_FEE: u128 = 5;
+
+/// Gets the fees handled by the fee manager.
+pub fn handled_fees() -> Vec<(Assets, FeeReason)> {
+	HANDLED_FEES.with(|f| (*f.borrow()).clone())
+}
+
 /// Test fee manager that will waive the fee for some origins.
 ///
-/// Doesn't do anything with the fee, which effectively burns it.
+/// Only records the fee, which effectively burns it. Fees in assets other than the native one
+/// are returned unhandled.
 pub struct TestFeeManager;
 impl FeeManager for TestFeeManager {
-	fn is_waived(origin: Option<&Location>, _: FeeReason) -> bool {
+	fn is_waived(origin: Option<&Location>, assets: &Assets, reason: FeeReason) -> bool {
+		let is_dust = !assets.is_none() &&
+			assets.inner().iter().all(|asset| match asset {
+				Asset { id: AssetId(location), fun: Fungible(amount) } =>
+					*location == Location::parent() && *amount <= WAIVED_PARENT_FEE,
+				_ => false,
+			});
+		if is_dust {
+			return true
+		}
 		let Some(origin) = origin else { return false };
 		// Match the root origin and a particular smart contract account.
 		matches!(
 			origin.unpack(),
 			(0, []) | (0, [AccountKey20 { network: None, key: WAIVED_CONTRACT_ADDRESS }])
-		)
+		) || (reason == FeeReason::Report &&
+			matches!(
+				origin.unpack(),
+				(0, [AccountId32 { network: None, id: REPORT_WAIVED_SENDER }])
+			))
 	}
 
-	fn handle_fee(_: Assets, _: Option<&XcmContext>, _: FeeReason) {}
+	fn handle_fee(fee: Assets, _: Option<&XcmContext>, reason: FeeReason) -> Assets {
+		let (native, unhandled): (Vec<Asset>, Vec<Asset>) =
+			fee.into_inner().into_iter().partition(|asset| asset.id == AssetId(Here.into()));
+		if !native.is_empty() {
+			HANDLED_FEES.with(|f| f.borrow_mut().push((native.into(), reason)));
+		}
+		unhandled.into()
+	}
+}
+
+/// Test asset exchanger that swaps the relay chain asset for the native asset one to one.
+pub struct TestAssetExchanger;
+impl AssetExchange for TestAssetExchanger {
+	fn exchange_asset(
+		_origin: Option<&Location>,
+		give: AssetsInHolding,
+		want: &Assets,
+		_maximal: bool,
+	) -> Result<AssetsInHolding, AssetsInHolding> {
+		match (give.fungible.get(&AssetId(Location::parent())), want.inner().as_slice()) {
+			(Some(given), [Asset { id: AssetId(location), fun: Fungible(wanted) }])
+				if location.is_here() && given == wanted =>
+				Ok(want.clone().into()),
+			_ => Err(give),
+		}
+	}
+
+	fn quote_exchange_price(give: &Assets, want: &Assets, maximal: bool) -> Option<Assets> {
+		match (give.inner().as_slice(), want.inner().as_slice()) {
+			(
+				[Asset { id: AssetId(from), .. }],
+				[Asset { id: AssetId(to), fun: Fungible(amount) }],
+			) if *from == Location::parent() && to.is_here() && !maximal => Some((Parent, *amount).into()),
+			_ => None,
+		}
+	}
 }
 
 /// Test XcmConfig that uses all the test implementations in this file.
@@ -283,7 +358,7 @@ impl Config for XcmConfig {
 	type ResponseHandler = ();
 	type AssetTrap = TestAssetTrap;
 	type AssetLocker = ();
-	type AssetExchanger = ();
+	type AssetExchanger = TestAssetExchanger;
 	type AssetClaims = ();
 	type SubscriptionService = ();
 	type PalletInstancesInfo = ();
@@ -20,6 +20,7 @@
 //! `xcm-emulator` based tests in the cumulus folder.
 //! These tests deal with internal state changes of the XCVM.
 
+mod charge_fees;
 mod execute_with_origin;
 mod initiate_transfer;
 mod mock;
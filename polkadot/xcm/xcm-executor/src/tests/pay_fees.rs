@@ -22,6 +22,7 @@
 use xcm::prelude::*;
 
 use super::mock::*;
+use crate::{FeeReason, FeesMode};
 
 // The sender and recipient we use across these tests.
 const SENDER: [u8; 32] = [0; 32];
@@ -120,6 +121,84 @@ fn works_for_delivery_fees() {
 	);
 }
 
+// Fee managers can tell delivery fees paid from the `fees` register apart from the others.
+#[test]
+fn delivery_fees_paid_with_pay_fees_can_be_charged() {
+	add_asset(REPORT_WAIVED_SENDER, (Here, 200u128));
+	let query_response_info =
+		QueryResponseInfo { destination: Parent.into(), query_id: 0, max_weight: Weight::zero() };
+
+	// Without `PayFees`, the report fees of the sender are waived.
+	let xcm = Xcm::<TestCall>::builder()
+		.withdraw_asset((Here, 100u128))
+		.buy_execution((Here, 100u128), Limited(Weight::from_parts(3, 3)))
+		.report_error(query_response_info.clone())
+		.build();
+	let (mut vm, _) = instantiate_executor(REPORT_WAIVED_SENDER, xcm.clone());
+	assert!(vm.bench_process(xcm).is_ok());
+	assert_eq!(handled_fees(), vec![]);
+
+	// With `PayFees`, they are charged.
+	let xcm = Xcm::<TestCall>::builder()
+		.withdraw_asset((Here, 100u128))
+		.pay_fees((Here, 10u128))
+		.report_error(query_response_info)
+		.build();
+	let (mut vm, _) = instantiate_executor(REPORT_WAIVED_SENDER, xcm.clone());
+	assert!(vm.bench_process(xcm).is_ok());
+	let reason = FeeReason::PayFees { charged_for: Box::new(FeeReason::Report) };
+	assert_eq!(handled_fees(), vec![((Here, 1u128).into(), reason)]);
+}
+
+// Fees which aren't handled are trapped for the local chain, not for the payer, and the weight
+// of trapping them is accounted.
+#[test]
+fn unhandled_fees_are_trapped_for_local_chain() {
+	set_trap_weight(Weight::from_parts(5, 5));
+	let (mut vm, weight) = instantiate_executor(SENDER, Xcm::new());
+	vm.fees = Asset::from((Parent, 10u128)).into();
+
+	// The test fee manager only handles the native asset.
+	assert_eq!(vm.take_fee((Parent, 8u128).into(), FeeReason::Report), Ok(()));
+	assert_eq!(handled_fees(), vec![]);
+	assert_eq!(asset_list(TRAPPED_ASSETS), vec![(Parent, 8u128).into()]);
+	assert_eq!(trap_claimers(), vec![Location::here()]);
+
+	assert_eq!(vm.bench_post_process(weight), Outcome::Complete { used: Weight::from_parts(5, 5) });
+}
+
+// Delivery fees withdrawn just-in-time are handled with their own reason.
+#[test]
+fn delivery_fees_withdrawn_just_in_time_are_handled_as_such() {
+	add_asset(SENDER, (Here, 10u128));
+	let (mut vm, _) = instantiate_executor(SENDER, Xcm::new());
+	vm.set_fees_mode(FeesMode { jit_withdraw: true });
+
+	assert_eq!(vm.take_fee((Here, 1u128).into(), FeeReason::Report), Ok(()));
+	assert_eq!(asset_list(SENDER), vec![(Here, 9u128).into()]);
+	let reason = FeeReason::JitWithdraw { charged_for: Box::new(FeeReason::Report) };
+	assert_eq!(handled_fees(), vec![((Here, 1u128).into(), reason)]);
+}
+
+// The waiver of delivery fees paid in another asset is checked against the asset charged.
+#[test]
+fn waiver_of_swapped_delivery_fees_is_checked_against_charged_asset() {
+	let (mut vm, _) = instantiate_executor(SENDER, Xcm::new());
+	vm.fees = Asset::from((Parent, 10u128)).into();
+
+	// Charging `1` of the relay chain asset is waived.
+	assert_eq!(vm.take_fee((Here, 1u128).into(), FeeReason::Report), Ok(()));
+	assert_eq!(get_first_fungible(vm.fees()), Some((Parent, 10u128).into()));
+	assert_eq!(handled_fees(), vec![]);
+
+	// Charging more than `WAIVED_PARENT_FEE` of it is not.
+	let fee = WAIVED_PARENT_FEE + 1;
+	assert_eq!(vm.take_fee((Here, fee).into(), FeeReason::Report), Ok(()));
+	assert_eq!(get_first_fungible(vm.fees()), Some((Parent, 10 - fee).into()));
+	let reason = FeeReason::PayFees { charged_for: Box::new(FeeReason::Report) };
+	assert_eq!(handled_fees(), vec![((Here, fee).into(), reason)]);
+}
+
 // Tests the support for `BuyExecution` while the ecosystem transitions to `PayFees`.
 #[test]
 fn buy_execution_works_as_before() {
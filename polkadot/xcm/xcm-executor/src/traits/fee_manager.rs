@@ -14,16 +14,46 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+use alloc::boxed::Box;
 use xcm::prelude::*;
 
 /// Handle stuff to do with taking fees in certain XCM instructions.
 pub trait FeeManager {
-	/// Determine if a fee should be waived.
-	fn is_waived(origin: Option<&Location>, r: FeeReason) -> bool;
+	/// Determine if the fee `assets` about to be charged should be waived.
+	///
+	/// This allows waiving fees depending on the assets they are denominated in or on their
+	/// amount. Implementations migrating from the previous `is_waived(origin, reason)` form,
+	/// which only decide on the origin and reason, can simply ignore `assets`.
+	fn is_waived(origin: Option<&Location>, assets: &Assets, r: FeeReason) -> bool;
 
-	/// Do something with the fee which has been paid. Doing nothing here silently burns the
-	/// fees.
-	fn handle_fee(fee: Assets, context: Option<&XcmContext>, r: FeeReason);
+	/// Check that the fee `assets` may be charged, before they are withdrawn.
+	///
+	/// Returning an error fails the operation paying the fee without charging it, e.g. the
+	/// instruction being executed or the `charge_fees` call. Not called for waived fees. Fee
+	/// managers wrapping another one must forward this call to it. Passes by default.
+	fn check_fee(_origin: Option<&Location>, _assets: &Assets, _r: FeeReason) -> XcmResult {
+		Ok(())
+	}
+
+	/// Do something with the fee which has been paid.
+	///
+	/// Returns the part of the fee which wasn't handled, e.g. deposited somewhere. When the fee is
+	/// paid while executing a message, the executor traps the returned assets for the local
+	/// chain, so that only governance can claim them, and accounts for the weight of trapping
+	/// them. The part returned for fees charged through `charge_fees` is burned.
+	fn handle_fee(fee: Assets, context: Option<&XcmContext>, r: FeeReason) -> Assets;
+
+	/// Called when handling the fee `assets` failed with `error`, e.g. because they could not be
+	/// deposited into the fee receiver.
+	///
+	/// Fee handlers call this hook for the part of the fee they failed to handle, which they
+	/// return unhandled, so that the failure can be observed. Does nothing by default.
+	///
+	/// `error` is the [`XcmError`] of the failed operation, e.g. the one returned by
+	/// [`TransactAsset::deposit_asset`](crate::traits::TransactAsset::deposit_asset). Fee handlers
+	/// deposit through the asset transactor, which reports failures as `XcmError` rather than as
+	/// `DispatchError`.
+	fn on_handle_fee_error(_assets: &Assets, _reason: FeeReason, _error: XcmError) {}
 }
 
 /// Context under which a fee is paid.
@@ -45,18 +75,142 @@ pub enum FeeReason {
 	QueryPallet,
 	/// When the `ExportMessage` instruction is called (and includes the network ID).
 	Export { network: NetworkId, destination: InteriorLocation },
-	/// The `charge_fees` API.
-	ChargeFees,
+	/// The `charge_fees` API (and includes the subsystem which called it, if known).
+	ChargeFees { caller: Option<&'static str> },
 	/// When the `LockAsset` instruction is called.
 	LockAsset,
 	/// When the `RequestUnlock` instruction is called.
 	RequestUnlock,
+	/// When a fee is paid from the `fees` register filled by the `PayFees` instruction (and
+	/// includes the reason the fee is charged for).
+	///
+	/// The executor handles such fees with this reason. Their waiver is checked for both this
+	/// reason and the one they are charged for.
+	PayFees { charged_for: Box<FeeReason> },
+	/// When a fee is withdrawn directly from the origin because `SetFeesMode` enabled
+	/// `jit_withdraw` (and includes the reason the fee is charged for).
+	///
+	/// The executor handles such fees with this reason. Their waiver is checked for both this
+	/// reason and the one they are charged for.
+	JitWithdraw { charged_for: Box<FeeReason> },
+}
+
+impl FeeReason {
+	/// Returns the reason the fee is charged for, looking through the way it is paid, i.e.
+	/// [`FeeReason::PayFees`] and [`FeeReason::JitWithdraw`].
+	pub fn charged_for(&self) -> &FeeReason {
+		match self {
+			FeeReason::PayFees { charged_for } | FeeReason::JitWithdraw { charged_for } =>
+				charged_for.charged_for(),
+			reason => reason,
+		}
+	}
+
+	/// Returns `true` if the fee is paid for initiating an outbound transfer or message.
+	///
+	/// The following reasons are classified as outbound:
+	/// - [`FeeReason::DepositReserveAsset`]
+	/// - [`FeeReason::InitiateReserveWithdraw`]
+	/// - [`FeeReason::InitiateTeleport`]
+	/// - [`FeeReason::Export`]
+	///
+	/// Fees paid with [`FeeReason::PayFees`] or [`FeeReason::JitWithdraw`] are classified by the
+	/// reason they are charged for. All other reasons are considered to be incurred while
+	/// handling an inbound message.
+	pub fn is_outbound(&self) -> bool {
+		matches!(
+			self.charged_for(),
+			FeeReason::DepositReserveAsset |
+				FeeReason::InitiateReserveWithdraw |
+				FeeReason::InitiateTeleport |
+				FeeReason::Export { .. }
+		)
+	}
+
+	/// Returns the name of the instruction the fee is paid for and, for
+	/// [`FeeReason::Export`], the network the message is exported to.
+	///
+	/// Reasons which don't correspond to a single instruction, e.g. [`FeeReason::Report`] and
+	/// [`FeeReason::ChargeFees`], return the name of the reason instead.
+	///
+	/// Meant to be used as structured fields when tracing fee events.
+	pub fn as_trace_fields(&self) -> (&'static str, Option<NetworkId>) {
+		match self {
+			FeeReason::Report => ("Report", None),
+			FeeReason::TransferReserveAsset => ("TransferReserveAsset", None),
+			FeeReason::DepositReserveAsset => ("DepositReserveAsset", None),
+			FeeReason::InitiateReserveWithdraw => ("InitiateReserveWithdraw", None),
+			FeeReason::InitiateTeleport => ("InitiateTeleport", None),
+			FeeReason::InitiateTransfer => ("InitiateTransfer", None),
+			FeeReason::QueryPallet => ("QueryPallet", None),
+			FeeReason::Export { network, .. } => ("ExportMessage", Some(*network)),
+			FeeReason::ChargeFees { .. } => ("ChargeFees", None),
+			FeeReason::LockAsset => ("LockAsset", None),
+			FeeReason::RequestUnlock => ("RequestUnlock", None),
+			FeeReason::PayFees { .. } => ("PayFees", None),
+			FeeReason::JitWithdraw { .. } => ("JitWithdraw", None),
+		}
+	}
 }
 
 impl FeeManager for () {
-	fn is_waived(_: Option<&Location>, _: FeeReason) -> bool {
+	fn is_waived(_: Option<&Location>, _: &Assets, _: FeeReason) -> bool {
 		false
 	}
 
-	fn handle_fee(_: Assets, _: Option<&XcmContext>, _: FeeReason) {}
+	fn handle_fee(fee: Assets, _: Option<&XcmContext>, _: FeeReason) -> Assets {
+		fee
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fee_reason_is_outbound_works() {
+		let export = FeeReason::Export { network: Polkadot, destination: Here };
+		let outbound = [
+			FeeReason::DepositReserveAsset,
+			FeeReason::InitiateReserveWithdraw,
+			FeeReason::InitiateTeleport,
+			FeeReason::PayFees { charged_for: Box::new(export.clone()) },
+			export,
+		];
+		for reason in outbound {
+			assert!(reason.is_outbound(), "{reason:?} should be outbound");
+		}
+
+		let inbound = [
+			FeeReason::Report,
+			FeeReason::TransferReserveAsset,
+			FeeReason::InitiateTransfer,
+			FeeReason::QueryPallet,
+			FeeReason::ChargeFees { caller: None },
+			FeeReason::LockAsset,
+			FeeReason::RequestUnlock,
+			FeeReason::PayFees { charged_for: Box::new(FeeReason::Report) },
+			FeeReason::JitWithdraw { charged_for: Box::new(FeeReason::Report) },
+		];
+		for reason in inbound {
+			assert!(!reason.is_outbound(), "{reason:?} should not be outbound");
+		}
+	}
+
+	#[test]
+	fn fee_reason_as_trace_fields_works() {
+		let export = FeeReason::Export { network: Kusama, destination: Parachain(1000).into() };
+		assert_eq!(export.as_trace_fields(), ("ExportMessage", Some(Kusama)));
+		assert_eq!(FeeReason::InitiateTeleport.as_trace_fields(), ("InitiateTeleport", None));
+	}
+
+	#[test]
+	fn fee_reason_charged_for_works() {
+		let export = FeeReason::Export { network: Kusama, destination: Here };
+		assert_eq!(export.charged_for(), &export);
+		let paid = FeeReason::PayFees { charged_for: Box::new(export.clone()) };
+		assert_eq!(paid.charged_for(), &export);
+		let withdrawn = FeeReason::JitWithdraw { charged_for: Box::new(FeeReason::Report) };
+		assert_eq!(withdrawn.charged_for(), &FeeReason::Report);
+	}
 }
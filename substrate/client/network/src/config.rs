@@ -24,7 +24,10 @@
 pub use crate::{
 	discovery::DEFAULT_KADEMLIA_REPLICATION_FACTOR,
 	peer_store::PeerStoreProvider,
-	protocol::{notification_service, NotificationsSink, ProtocolHandlePair},
+	protocol::{
+		notification_service, notification_service_with_config, ChannelOverflow, InboundRateLimit,
+		NotificationChannelConfig, NotificationsSink, ProtocolHandlePair,
+	},
 	request_responses::{
 		IncomingRequest, OutgoingResponse, ProtocolConfig as RequestResponseConfig,
 	},
@@ -507,9 +510,31 @@ impl NonDefaultSetConfig {
 		max_notification_size: u64,
 		handshake: Option<NotificationHandshake>,
 		set_config: SetConfig,
+	) -> (Self, Box<dyn NotificationService>) {
+		Self::new_with_channel_config(
+			protocol_name,
+			fallback_names,
+			max_notification_size,
+			handshake,
+			set_config,
+			NotificationChannelConfig::default(),
+		)
+	}
+
+	/// Creates a new [`NonDefaultSetConfig`] whose subscribers receive their events through
+	/// channels using `channel_config`.
+	///
+	/// See [`NonDefaultSetConfig::new()`].
+	pub fn new_with_channel_config(
+		protocol_name: ProtocolName,
+		fallback_names: Vec<ProtocolName>,
+		max_notification_size: u64,
+		handshake: Option<NotificationHandshake>,
+		set_config: SetConfig,
+		channel_config: NotificationChannelConfig,
 	) -> (Self, Box<dyn NotificationService>) {
 		let (protocol_handle_pair, notification_service) =
-			notification_service(protocol_name.clone());
+			notification_service_with_config(protocol_name.clone(), channel_config);
 		(
 			Self {
 				protocol_name,
@@ -571,6 +596,13 @@ impl NonDefaultSetConfig {
 	pub fn add_fallback_names(&mut self, fallback_names: Vec<ProtocolName>) {
 		self.fallback_names.extend(fallback_names);
 	}
+
+	/// Limit the notifications received from each peer on this protocol.
+	///
+	/// See [`ProtocolHandlePair::set_inbound_rate_limit()`].
+	pub fn set_inbound_rate_limit(&mut self, limit: InboundRateLimit) {
+		self.protocol_handle_pair.set_inbound_rate_limit(limit);
+	}
 }
 
 impl NotificationConfig for NonDefaultSetConfig {
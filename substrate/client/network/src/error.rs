@@ -77,6 +77,12 @@ pub enum Error {
 	/// Connection closed.
 	#[error("Connection closed")]
 	ConnectionClosed,
+	/// Timed out waiting for a response.
+	#[error("Timed out")]
+	Timeout,
+	/// The message sink outlived its time-to-live.
+	#[error("Message sink expired")]
+	Expired,
 	/// Litep2p error.
 	#[error("Litep2p error: `{0}`")]
 	Litep2p(litep2p::Error),
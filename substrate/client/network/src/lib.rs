@@ -280,11 +280,13 @@ pub use service::{
 	metrics::NotificationMetrics,
 	signature::Signature,
 	traits::{
-		KademliaKey, MessageSink, NetworkBackend, NetworkBlock, NetworkDHTProvider,
-		NetworkEventStream, NetworkPeers, NetworkRequest, NetworkSigner, NetworkStateInfo,
-		NetworkStatus, NetworkStatusProvider, NetworkSyncForkRequest, NotificationConfig,
-		NotificationSender as NotificationSenderT, NotificationSenderError,
-		NotificationSenderReady, NotificationService,
+		AuthMode, CoalesceConfig, CompressionConfig, KademliaKey, MessageSink, NetworkBackend,
+		NetworkBlock, NetworkDHTProvider, NetworkEventStream, NetworkPeers, NetworkRequest,
+		NetworkSigner, NetworkStateInfo, NetworkStatus, NetworkStatusProvider,
+		NetworkSyncForkRequest, NotificationConfig, NotificationSender as NotificationSenderT,
+		NotificationSenderError, NotificationSenderReady, NotificationService,
+		NotificationServiceExt, PeerDiagnostic, PeerStats, ScopedMessageSink, SendStatus,
+		SinkState, TypedNotificationEvent, TypedNotificationService,
 	},
 	DecodingError, Keypair, NetworkService, NetworkWorker, NotificationSender, OutboundFailure,
 	PublicKey,
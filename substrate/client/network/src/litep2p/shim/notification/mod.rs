@@ -24,9 +24,10 @@ use crate::{
 	litep2p::shim::notification::peerset::{OpenResult, Peerset, PeersetNotificationCommand},
 	service::{
 		metrics::NotificationMetrics,
-		traits::{NotificationEvent as SubstrateNotificationEvent, ValidationResult},
+		traits::{Direction, NotificationEvent as SubstrateNotificationEvent, ValidationResult},
 	},
-	MessageSink, NotificationService, ProtocolName,
+	AuthMode, CoalesceConfig, CompressionConfig, MessageSink, NotificationService, PeerDiagnostic,
+	PeerStats, ProtocolName, SendStatus, SinkState,
 };
 
 use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
@@ -38,7 +39,11 @@ use tokio::sync::oneshot;
 
 use sc_network_types::PeerId;
 
-use std::{collections::HashSet, fmt};
+use std::{
+	collections::{HashMap, HashSet},
+	fmt,
+	sync::Arc,
+};
 
 pub mod config;
 pub mod peerset;
@@ -93,6 +98,29 @@ impl MessageSink for Litep2pMessageSink {
 		}
 	}
 
+	/// Send synchronous `notification` to the peer associated with this [`MessageSink`],
+	/// reporting whether it was queued or dropped.
+	fn try_send_sync_notification(&self, notification: Vec<u8>) -> SendStatus {
+		let size = notification.len();
+
+		match self.sink.send_sync_notification(notification) {
+			Ok(_) => {
+				self.metrics.register_notification_sent(&self.protocol, size);
+				SendStatus::Queued
+			},
+			Err(error) => {
+				log::trace!(
+					target: LOG_TARGET,
+					"{}: failed to send sync notification to {:?}: {error:?}",
+					self.protocol,
+					self.peer,
+				);
+				self.metrics.register_notification_dropped(&self.protocol);
+				SendStatus::Dropped
+			},
+		}
+	}
+
 	/// Send an asynchronous `notification` to to the peer associated with this [`MessageSink`],
 	/// allowing sender to exercise backpressure.
 	///
@@ -119,6 +147,22 @@ impl MessageSink for Litep2pMessageSink {
 	}
 }
 
+/// Type representing the receive interceptor applied to received notifications.
+type ReceiveInterceptor = Arc<dyn Fn(PeerId, Vec<u8>) -> Option<Vec<u8>> + Send + Sync>;
+
+/// Type representing the callback checking whether a peer supports compression from its
+/// handshake.
+type CompressionSupport = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Peer with an open substream.
+struct PeerContext {
+	/// Direction of the substream.
+	direction: Direction,
+
+	/// Whether the peer advertised support for compression in its handshake.
+	compression: bool,
+}
+
 /// Notification protocol implementation.
 pub struct NotificationProtocol {
 	/// Protocol name.
@@ -140,6 +184,15 @@ pub struct NotificationProtocol {
 	/// Pending cancels.
 	pending_cancels: HashSet<litep2p::PeerId>,
 
+	/// Peers with an open substream reported to the protocol.
+	peers: HashMap<PeerId, PeerContext>,
+
+	/// Interceptor applied to received notifications, if set.
+	receive_interceptor: Option<ReceiveInterceptor>,
+
+	/// Compression of notifications, if enabled.
+	compression: Option<(CompressionConfig, CompressionSupport)>,
+
 	/// Notification metrics.
 	metrics: NotificationMetrics,
 }
@@ -168,6 +221,48 @@ impl NotificationProtocol {
 			metrics,
 			pending_cancels: HashSet::new(),
 			pending_validations: FuturesUnordered::new(),
+			peers: HashMap::new(),
+			receive_interceptor: None,
+			compression: None,
+		}
+	}
+
+	/// Compress `notification` if `peer` supports compression and the notification is large
+	/// enough.
+	fn encode_for_peer(&self, peer: &PeerId, notification: Vec<u8>) -> Vec<u8> {
+		match (&self.compression, self.peers.get(peer)) {
+			(Some((config, _)), Some(context))
+				if context.compression && notification.len() >= config.threshold =>
+				sp_maybe_compressed_blob::compress(&notification, config.max_size)
+					.filter(|compressed| compressed.len() < notification.len())
+					.unwrap_or(notification),
+			_ => notification,
+		}
+	}
+
+	/// Decompress `notification` received from `peer` and apply the receive interceptor to it.
+	///
+	/// Returns `None` if the notification couldn't be decompressed or the interceptor dropped it.
+	fn on_notification_received(&self, peer: PeerId, notification: Vec<u8>) -> Option<Vec<u8>> {
+		let notification = match (&self.compression, self.peers.get(&peer)) {
+			(Some((config, _)), Some(context)) if context.compression =>
+				match sp_maybe_compressed_blob::decompress(&notification, config.max_size) {
+					Ok(decompressed) => decompressed.into_owned(),
+					Err(error) => {
+						log::debug!(
+							target: LOG_TARGET,
+							"{}: failed to decompress notification from {peer:?}: {error:?}",
+							self.protocol,
+						);
+						return None
+					},
+				},
+			_ => notification,
+		};
+
+		match &self.receive_interceptor {
+			Some(interceptor) => interceptor(peer, notification),
+			None => Some(notification),
 		}
 	}
 
@@ -199,6 +294,7 @@ impl NotificationService for NotificationProtocol {
 	}
 
 	fn send_sync_notification(&mut self, peer: &PeerId, notification: Vec<u8>) {
+		let notification = self.encode_for_peer(peer, notification);
 		let size = notification.len();
 
 		if let Ok(_) = self.handle.send_sync_notification(peer.into(), notification) {
@@ -211,6 +307,7 @@ impl NotificationService for NotificationProtocol {
 		peer: &PeerId,
 		notification: Vec<u8>,
 	) -> Result<(), Error> {
+		let notification = self.encode_for_peer(peer, notification);
 		let size = notification.len();
 
 		match self.handle.send_async_notification(peer.into(), notification).await {
@@ -306,6 +403,11 @@ impl NotificationService for NotificationProtocol {
 							OpenResult::Accept { direction } => {
 								log::trace!(target: LOG_TARGET, "{}: substream opened for {peer:?}", self.protocol);
 
+								let compression = self.compression.as_ref().map_or(false, |(_, supports_compression)| {
+									supports_compression(&handshake)
+								});
+								self.peers.insert(peer.into(), PeerContext { direction, compression });
+
 								return Some(SubstrateNotificationEvent::NotificationStreamOpened {
 									peer: peer.into(),
 									handshake,
@@ -322,6 +424,7 @@ impl NotificationService for NotificationProtocol {
 
 						self.metrics.register_substream_closed(&self.protocol);
 						self.peerset.report_substream_closed(peer.into());
+						self.peers.remove(&PeerId::from(peer));
 
 						if self.pending_cancels.remove(&peer) {
 							log::debug!(
@@ -347,10 +450,14 @@ impl NotificationService for NotificationProtocol {
 					} => {
 						self.metrics.register_notification_received(&self.protocol, notification.len());
 
-						if !self.pending_cancels.contains(&peer) {
+						if self.pending_cancels.contains(&peer) {
+							continue
+						}
+
+						if let Some(notification) = self.on_notification_received(peer.into(), notification.to_vec()) {
 							return Some(SubstrateNotificationEvent::NotificationReceived {
 								peer: peer.into(),
-								notification: notification.to_vec(),
+								notification,
 							});
 						}
 					}
@@ -371,4 +478,209 @@ impl NotificationService for NotificationProtocol {
 			}
 		}
 	}
+
+	/// Fallback encoding is not supported by `litep2p`, the encoder is ignored.
+	fn set_fallback_encoder(
+		&mut self,
+		_encoder: Arc<dyn Fn(&[u8], &ProtocolName) -> Vec<u8> + Send + Sync>,
+	) {
+		log::warn!(
+			target: LOG_TARGET,
+			"{}: fallback encoding not supported by `litep2p`, ignoring",
+			self.protocol,
+		);
+	}
+
+	/// Clonable `NotificationService` is not supported by `litep2p`.
+	fn clone_shared(&mut self) -> Result<Box<dyn NotificationService>, ()> {
+		Err(())
+	}
+
+	/// Negotiated protocols are not tracked by `litep2p`.
+	fn negotiated_protocol(&self, _peer: &PeerId) -> Option<ProtocolName> {
+		None
+	}
+
+	/// Coalescing is not supported by `litep2p`, notifications are sent individually.
+	fn set_coalescing(
+		&mut self,
+		_config: CoalesceConfig,
+		_framer: Arc<dyn Fn(Vec<Vec<u8>>) -> Vec<u8> + Send + Sync>,
+	) {
+		log::warn!(
+			target: LOG_TARGET,
+			"{}: coalescing not supported by `litep2p`, ignoring",
+			self.protocol,
+		);
+	}
+
+	/// Draining received notifications is not supported by `litep2p`.
+	fn drain_received(&mut self, _peer: &PeerId) -> Vec<Vec<u8>> {
+		Vec::new()
+	}
+
+	/// Check whether the substream to `peer` supports backpressure.
+	///
+	/// `litep2p` notification sinks always support sending notifications asynchronously.
+	fn supports_backpressure(&self, peer: &PeerId) -> Option<bool> {
+		self.handle.notification_sink(peer.into()).map(|_| true)
+	}
+
+	/// Peer statistics are not supported by `litep2p`.
+	fn peer_stats(&self, _peer: &PeerId) -> Option<PeerStats> {
+		None
+	}
+
+	/// Set the interceptor applied to received notifications.
+	fn set_receive_interceptor(&mut self, interceptor: ReceiveInterceptor) {
+		self.receive_interceptor = Some(interceptor);
+	}
+
+	/// Get the peers with an open substream in `direction`.
+	fn peers_by_direction(&self, direction: Direction) -> Vec<PeerId> {
+		self.peers
+			.iter()
+			.filter_map(|(peer, context)| (context.direction == direction).then_some(*peer))
+			.collect()
+	}
+
+	/// Queued bytes are not reported by `litep2p`.
+	fn total_queued_bytes(&self) -> Option<usize> {
+		None
+	}
+
+	/// Enable compression of notifications.
+	///
+	/// Notifications sent through a [`MessageSink`] are not compressed.
+	fn set_compression(
+		&mut self,
+		config: CompressionConfig,
+		supports_compression: CompressionSupport,
+	) {
+		self.compression = Some((config, supports_compression));
+	}
+
+	/// Validation outcomes are not tracked by `litep2p`.
+	fn handshake_accepted(&self, _peer: &PeerId) -> Option<bool> {
+		None
+	}
+
+	/// Idle peers are not tracked by `litep2p`, no peer is closed.
+	async fn close_idle(&mut self, _idle_for: std::time::Duration) -> usize {
+		0
+	}
+
+	/// Authentication tags are not supported by `litep2p`, the signer is refused.
+	fn set_auth_signer(
+		&mut self,
+		_signer: Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>,
+		_supports_auth: Arc<dyn Fn(&[u8]) -> bool + Send + Sync>,
+	) -> Result<(), ()> {
+		log::warn!(
+			target: LOG_TARGET,
+			"{}: authentication tags not supported by `litep2p`, refusing signer",
+			self.protocol,
+		);
+		Err(())
+	}
+
+	/// Authentication tags are not supported by `litep2p`, the verifier is refused.
+	fn set_auth_verifier(
+		&mut self,
+		_verifier: Arc<dyn Fn(&[u8], &[u8]) -> bool + Send + Sync>,
+		_supports_auth: Arc<dyn Fn(&[u8]) -> bool + Send + Sync>,
+		_mode: AuthMode,
+	) -> Result<(), ()> {
+		log::warn!(
+			target: LOG_TARGET,
+			"{}: authentication tags not supported by `litep2p`, refusing verifier",
+			self.protocol,
+		);
+		Err(())
+	}
+
+	/// Flow control is not supported by `litep2p`, notifications keep being delivered.
+	fn request_pause(&mut self) {
+		log::debug!(
+			target: LOG_TARGET,
+			"{}: flow control not supported by `litep2p`",
+			self.protocol,
+		);
+	}
+
+	/// Flow control is not supported by `litep2p`.
+	fn request_resume(&mut self) {
+		log::debug!(
+			target: LOG_TARGET,
+			"{}: flow control not supported by `litep2p`",
+			self.protocol,
+		);
+	}
+
+	/// Send synchronous `notification` to all peers with an open substream.
+	fn broadcast_sync_notification(&mut self, notification: Vec<u8>) {
+		let peers: Vec<PeerId> = self.peers.keys().copied().collect();
+
+		for peer in peers {
+			self.send_sync_notification(&peer, notification.clone());
+		}
+	}
+
+	/// Peer metadata is not supported by `litep2p`.
+	fn set_peer_metadata(
+		&mut self,
+		_peer: &PeerId,
+		_metadata: Arc<dyn std::any::Any + Send + Sync>,
+	) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Peer metadata is not supported by `litep2p`.
+	fn peer_metadata(&self, _peer: &PeerId) -> Option<Arc<dyn std::any::Any + Send + Sync>> {
+		None
+	}
+
+	/// Get the peers with an open substream.
+	fn connected_peers(&self) -> Vec<PeerId> {
+		self.peers.keys().copied().collect()
+	}
+
+	/// Check whether there is an open substream to `peer`.
+	fn is_connected(&self, peer: &PeerId) -> bool {
+		self.peers.contains_key(peer)
+	}
+
+	/// Sink backpressure is not reported by `litep2p`.
+	fn sink_backpressure(&self, _peer: &PeerId) -> Option<SinkState> {
+		None
+	}
+
+	/// Peer snapshots are not supported by `litep2p`.
+	fn peer_snapshot(&self) -> Vec<PeerDiagnostic> {
+		Vec::new()
+	}
+
+	/// Opening substreams is not supported by `litep2p`.
+	async fn open_substream_with_fallbacks(
+		&mut self,
+		_peer: PeerId,
+		_fallbacks: Vec<ProtocolName>,
+	) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Peer health is not tracked by `litep2p`.
+	fn peer_health(&self, _peer: &PeerId) -> Option<f32> {
+		None
+	}
+
+	/// Opening substreams is not supported by `litep2p`.
+	fn try_open_substream(&mut self, _peer: PeerId) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Closing substreams is not supported by `litep2p`.
+	fn try_close_substream(&mut self, _peer: PeerId) -> Result<(), ()> {
+		Err(())
+	}
 }
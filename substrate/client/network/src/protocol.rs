@@ -47,7 +47,10 @@ use notifications::{Notifications, NotificationsOut};
 
 pub(crate) use notifications::ProtocolHandle;
 
-pub use notifications::{notification_service, NotificationsSink, ProtocolHandlePair, Ready};
+pub use notifications::{
+	notification_service, notification_service_with_config, ChannelOverflow, InboundRateLimit,
+	NotificationChannelConfig, NotificationsSink, ProtocolHandlePair, Ready,
+};
 
 mod notifications;
 
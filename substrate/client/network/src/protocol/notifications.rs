@@ -22,7 +22,10 @@
 pub use self::{
 	behaviour::{Notifications, NotificationsOut, ProtocolConfig},
 	handler::{NotificationsSink, Ready},
-	service::{notification_service, ProtocolHandlePair},
+	service::{
+		notification_service, notification_service_with_config, ChannelOverflow, InboundRateLimit,
+		NotificationChannelConfig, ProtocolHandlePair,
+	},
 };
 
 pub(crate) use self::service::ProtocolHandle;
@@ -161,6 +161,12 @@ pub struct Notifications {
 	/// Events to produce from `poll()`.
 	events: VecDeque<ToSwarm<NotificationsOut, NotifsHandlerIn>>,
 
+	/// Fallback protocols requested through [`NotificationCommand::OpenSubstreamWithFallbacks`],
+	/// offered instead of the configured fallbacks when opening substreams to the peer.
+	///
+	/// Dropped when the last connection to the peer is closed.
+	fallback_preferences: FnvHashMap<(PeerId, SetId), Vec<ProtocolName>>,
+
 	/// Pending inbound substream validations.
 	//
 	// NOTE: it's possible to read a stale response from `pending_inbound_validations`
@@ -456,6 +462,7 @@ impl Notifications {
 			incoming: SmallVec::new(),
 			next_incoming_index: IncomingIndex(0),
 			events: VecDeque::new(),
+			fallback_preferences: FnvHashMap::default(),
 			pending_inbound_validations: FuturesUnordered::new(),
 			metrics,
 		}
@@ -475,6 +482,69 @@ impl Notifications {
 		}
 	}
 
+	/// Sets the fallback protocols offered when opening a substream to `peer` for `set_id`, or
+	/// restores the configured ones if `fallbacks` is `None`.
+	///
+	/// Only fallbacks configured for the protocol are kept, in the order given in `fallbacks`. The
+	/// handlers of the already established connections are updated, new connections pick up the
+	/// preference when their handler is created.
+	fn set_fallback_preference(
+		&mut self,
+		peer: PeerId,
+		set_id: SetId,
+		fallbacks: Option<Vec<ProtocolName>>,
+	) {
+		let configured = &self.notif_protocols[usize::from(set_id)].fallback_names;
+		let fallback_names = match fallbacks {
+			Some(fallbacks) => {
+				let fallbacks: Vec<_> =
+					fallbacks.into_iter().filter(|name| configured.contains(name)).collect();
+				self.fallback_preferences.insert((peer, set_id), fallbacks.clone());
+				fallbacks
+			},
+			None => {
+				if self.fallback_preferences.remove(&(peer, set_id)).is_none() {
+					return
+				}
+				configured.clone()
+			},
+		};
+
+		let connections = match self.peers.get(&(peer, set_id)) {
+			Some(PeerState::Disabled { connections, .. }) |
+			Some(PeerState::DisabledPendingEnable { connections, .. }) |
+			Some(PeerState::Enabled { connections, .. }) |
+			Some(PeerState::Incoming { connections, .. }) => connections,
+			_ => return,
+		};
+		for (connection_id, _) in connections.iter() {
+			trace!(
+				target: LOG_TARGET,
+				"Handler({:?}, {:?}) <= SetFallbacks({:?}, {:?})",
+				peer, connection_id, set_id, fallback_names,
+			);
+			self.events.push_back(ToSwarm::NotifyHandler {
+				peer_id: peer,
+				handler: NotifyHandler::One(*connection_id),
+				event: NotifsHandlerIn::SetFallbacks {
+					protocol_index: set_id.into(),
+					fallback_names: fallback_names.clone(),
+				},
+			});
+		}
+	}
+
+	/// Returns the protocols of the handler of a new connection to `peer`.
+	fn handler_protocols(&self, peer: &PeerId) -> Vec<handler::ProtocolConfig> {
+		let mut protocols = self.notif_protocols.clone();
+		for (set_id, protocol) in protocols.iter_mut().enumerate() {
+			if let Some(fallbacks) = self.fallback_preferences.get(&(*peer, SetId::from(set_id))) {
+				protocol.fallback_names = fallbacks.clone();
+			}
+		}
+		protocols
+	}
+
 	/// Returns the list of all the peers we have an open channel to.
 	pub fn open_peers(&self) -> impl Iterator<Item = &PeerId> {
 		self.peers.iter().filter(|(_, state)| state.is_open()).map(|((id, _), _)| id)
@@ -1225,7 +1295,7 @@ impl NetworkBehaviour for Notifications {
 		_local_addr: &Multiaddr,
 		_remote_addr: &Multiaddr,
 	) -> Result<THandler<Self>, ConnectionDenied> {
-		Ok(NotifsHandler::new(peer, self.notif_protocols.clone(), Some(self.metrics.clone())))
+		Ok(NotifsHandler::new(peer, self.handler_protocols(&peer), Some(self.metrics.clone())))
 	}
 
 	fn handle_established_outbound_connection(
@@ -1236,7 +1306,7 @@ impl NetworkBehaviour for Notifications {
 		_role_override: Endpoint,
 		_port_use: PortUse,
 	) -> Result<THandler<Self>, ConnectionDenied> {
-		Ok(NotifsHandler::new(peer, self.notif_protocols.clone(), Some(self.metrics.clone())))
+		Ok(NotifsHandler::new(peer, self.handler_protocols(&peer), Some(self.metrics.clone())))
 	}
 
 	fn on_swarm_event(&mut self, event: FromSwarm) {
@@ -1300,7 +1370,16 @@ impl NetworkBehaviour for Notifications {
 					}
 				}
 			},
-			FromSwarm::ConnectionClosed(ConnectionClosed { peer_id, connection_id, .. }) => {
+			FromSwarm::ConnectionClosed(ConnectionClosed {
+				peer_id,
+				connection_id,
+				remaining_established,
+				..
+			}) => {
+				if remaining_established == 0 {
+					self.fallback_preferences.retain(|(peer, _), _| peer != &peer_id);
+				}
+
 				for set_id in (0..self.notif_protocols.len()).map(SetId::from) {
 					let mut entry = if let Entry::Occupied(entry) =
 						self.peers.entry((peer_id, set_id))
@@ -2272,9 +2351,27 @@ impl NetworkBehaviour for Notifications {
 					NotificationCommand::SetHandshake(handshake) => {
 						self.set_notif_protocol_handshake(set_id.into(), handshake);
 					},
-					NotificationCommand::OpenSubstream(_peer) |
-					NotificationCommand::CloseSubstream(_peer) => {
-						todo!("substream control not implemented");
+					NotificationCommand::OpenSubstream(peer, result_tx) => {
+						self.protocol_controller_handles[set_id].connect_peer(peer, result_tx);
+					},
+					NotificationCommand::OpenSubstreamWithFallbacks {
+						peer,
+						fallbacks,
+						result_tx,
+					} => {
+						trace!(
+							target: LOG_TARGET,
+							"Protocol => Open({}, {:?}, {:?})",
+							peer, set_id, fallbacks,
+						);
+						self.set_fallback_preference(peer, set_id.into(), Some(fallbacks));
+						self.protocol_controller_handles[set_id].connect_peer(peer, result_tx);
+					},
+					NotificationCommand::CloseSubstream(peer) => {
+						let set_id = SetId::from(set_id);
+						trace!(target: LOG_TARGET, "Protocol => Close({}, {:?})", peer, set_id);
+						self.set_fallback_preference(peer, set_id, None);
+						self.disconnect_peer_inner(&peer, set_id);
 					},
 				},
 				Poll::Ready(None) => {
@@ -5041,6 +5138,49 @@ mod tests {
 		));
 	}
 
+	#[test]
+	fn fallback_preferences_dropped_when_last_connection_closes() {
+		let (mut notif, _controller, _notif_service) = development_notifs();
+		let set_id = SetId::from(0);
+		let peer = PeerId::random();
+		let connected = ConnectedPoint::Listener {
+			local_addr: Multiaddr::empty(),
+			send_back_addr: Multiaddr::empty(),
+		};
+
+		notif.set_fallback_preference(peer, set_id, Some(vec!["/foo/0".into()]));
+		assert!(notif.fallback_preferences.contains_key(&(peer, set_id)));
+
+		for conn in [ConnectionId::new_unchecked(0), ConnectionId::new_unchecked(1)] {
+			notif.on_swarm_event(FromSwarm::ConnectionEstablished(
+				libp2p::swarm::behaviour::ConnectionEstablished {
+					peer_id: peer,
+					connection_id: conn,
+					endpoint: &connected,
+					failed_addresses: &[],
+					other_established: 0usize,
+				},
+			));
+		}
+
+		// the preference is kept while a connection to the peer remains
+		for (conn, remaining_established) in
+			[(ConnectionId::new_unchecked(0), 1usize), (ConnectionId::new_unchecked(1), 0usize)]
+		{
+			assert!(notif.fallback_preferences.contains_key(&(peer, set_id)));
+			notif.on_swarm_event(FromSwarm::ConnectionClosed(
+				libp2p::swarm::behaviour::ConnectionClosed {
+					peer_id: peer,
+					connection_id: conn,
+					endpoint: &connected.clone(),
+					cause: None,
+					remaining_established,
+				},
+			));
+		}
+		assert!(!notif.fallback_preferences.contains_key(&(peer, set_id)));
+	}
+
 	#[test]
 	#[should_panic]
 	#[cfg(debug_assertions)]
@@ -62,7 +62,7 @@ use crate::{
 		NotificationsIn, NotificationsInSubstream, NotificationsOut, NotificationsOutSubstream,
 		UpgradeCollec,
 	},
-	service::metrics::NotificationMetrics,
+	service::{metrics::NotificationMetrics, traits::SinkState},
 	types::ProtocolName,
 };
 
@@ -85,7 +85,10 @@ use std::{
 	collections::VecDeque,
 	mem,
 	pin::Pin,
-	sync::Arc,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
 	task::{Context, Poll},
 	time::Duration,
 };
@@ -98,7 +101,7 @@ const LOG_TARGET: &str = "sub-libp2p::notification::handler";
 pub(crate) const ASYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 8;
 
 /// Number of pending notifications in synchronous contexts.
-const SYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 2048;
+pub(crate) const SYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 2048;
 
 /// Maximum duration to open a substream and receive the handshake message. After that, we
 /// consider that we failed to open the substream.
@@ -235,6 +238,14 @@ enum State {
 			>,
 		>,
 
+		/// Number of bytes of the notifications in `notifications_sink_rx`, shared with the
+		/// [`NotificationsSink`].
+		queued_bytes: Arc<AtomicUsize>,
+
+		/// Number of notifications in `notifications_sink_rx`, shared with the
+		/// [`NotificationsSink`].
+		queued_notifications: Arc<AtomicUsize>,
+
 		/// Outbound substream that has been accepted by the remote.
 		///
 		/// Always `Some` on transition to [`State::Open`]. Switched to `None` only if the remote
@@ -266,6 +277,16 @@ pub enum NotifsHandlerIn {
 		protocol_index: usize,
 	},
 
+	/// Replace the fallback protocols offered when the handler opens an outbound substream.
+	///
+	/// Doesn't affect a substream which is already being opened. Isn't answered.
+	SetFallbacks {
+		/// Index of the protocol in the list of protocols passed at initialization.
+		protocol_index: usize,
+		/// Fallback protocol names, in order of preference.
+		fallback_names: Vec<ProtocolName>,
+	},
+
 	/// Instruct the handler to close the notification substreams, or reject any pending incoming
 	/// substream request.
 	///
@@ -369,6 +390,8 @@ impl NotificationsSink {
 					peer_id,
 					async_channel: FuturesMutex::new(async_tx),
 					sync_channel: Mutex::new(Some(sync_tx)),
+					queued_bytes: Arc::new(AtomicUsize::new(0)),
+					queued_notifications: Arc::new(AtomicUsize::new(0)),
 				}),
 				metrics: None,
 			},
@@ -396,6 +419,11 @@ struct NotificationsSinkInner {
 	/// back-pressure cannot be properly exerted.
 	/// It will be removed in a future version.
 	sync_channel: Mutex<Option<mpsc::Sender<NotificationsSinkMessage>>>,
+	/// Number of bytes of the notifications queued in the channels and not yet written to the
+	/// substream.
+	queued_bytes: Arc<AtomicUsize>,
+	/// Number of notifications queued in the channels and not yet written to the substream.
+	queued_notifications: Arc<AtomicUsize>,
 }
 
 /// Message emitted through the [`NotificationsSink`] and processed by the background task
@@ -416,6 +444,31 @@ impl NotificationsSink {
 		&self.inner.peer_id
 	}
 
+	/// Returns the number of bytes of the notifications queued for the peer which haven't been
+	/// written to the substream yet.
+	pub fn queued_bytes(&self) -> usize {
+		self.inner.queued_bytes.load(Ordering::Relaxed)
+	}
+
+	/// Returns a snapshot of the backpressure state of the sink.
+	pub fn state(&self) -> SinkState {
+		let queued_notifications = self.inner.queued_notifications.load(Ordering::Relaxed);
+		let sync_overflowed = self.inner.sync_channel.lock().is_none();
+
+		SinkState {
+			queued_notifications,
+			queued_bytes: self.queued_bytes(),
+			// notifications queued through `reserve_notification()` are counted as well, which
+			// makes this a lower bound of the remaining capacity
+			sync_capacity: if sync_overflowed {
+				0
+			} else {
+				SYNC_NOTIFICATIONS_BUFFER_SIZE.saturating_sub(queued_notifications)
+			},
+			sync_overflowed,
+		}
+	}
+
 	/// Sends a notification to the peer.
 	///
 	/// If too many messages are already buffered, the notification is silently discarded and the
@@ -426,22 +479,41 @@ impl NotificationsSink {
 	///
 	/// This method will be removed in a future version.
 	pub fn send_sync_notification(&self, message: impl Into<Vec<u8>>) {
+		let _ = self.try_send_sync_notification(message);
+	}
+
+	/// Sends a notification to the peer, reporting whether it was queued.
+	///
+	/// Behaves like [`NotificationsSink::send_sync_notification`] but returns `Err(())` if the
+	/// notification was discarded because too many messages are buffered or were buffered
+	/// before.
+	pub fn try_send_sync_notification(&self, message: impl Into<Vec<u8>>) -> Result<(), ()> {
 		let mut lock = self.inner.sync_channel.lock();
 
-		if let Some(tx) = lock.as_mut() {
-			let message = message.into();
-			let result = tx.try_send(NotificationsSinkMessage::Notification { message });
+		let Some(tx) = lock.as_mut() else { return Err(()) };
+		let message = message.into();
+		let len = message.len();
 
-			if result.is_err() {
-				// Cloning the `mpsc::Sender` guarantees the allocation of an extra spot in the
-				// buffer, and therefore `try_send` will succeed.
-				let _result2 = tx.clone().try_send(NotificationsSinkMessage::ForceClose);
-				debug_assert!(_result2.map(|()| true).unwrap_or_else(|err| err.is_disconnected()));
+		// count the bytes before queueing so the handler never dequeues uncounted bytes
+		self.inner.queued_bytes.fetch_add(len, Ordering::Relaxed);
+		self.inner.queued_notifications.fetch_add(1, Ordering::Relaxed);
+		let result = tx.try_send(NotificationsSinkMessage::Notification { message });
 
-				// Destroy the sender in order to not send more `ForceClose` messages.
-				*lock = None;
-			}
+		if result.is_err() {
+			self.inner.queued_bytes.fetch_sub(len, Ordering::Relaxed);
+			self.inner.queued_notifications.fetch_sub(1, Ordering::Relaxed);
+
+			// Cloning the `mpsc::Sender` guarantees the allocation of an extra spot in the
+			// buffer, and therefore `try_send` will succeed.
+			let _result2 = tx.clone().try_send(NotificationsSinkMessage::ForceClose);
+			debug_assert!(_result2.map(|()| true).unwrap_or_else(|err| err.is_disconnected()));
+
+			// Destroy the sender in order to not send more `ForceClose` messages.
+			*lock = None;
+			return Err(())
 		}
+
+		Ok(())
 	}
 
 	/// Wait until the remote is ready to accept a notification.
@@ -455,7 +527,11 @@ impl NotificationsSink {
 
 		let poll_ready = future::poll_fn(|cx| lock.poll_ready(cx)).await;
 		if poll_ready.is_ok() {
-			Ok(Ready { lock })
+			Ok(Ready {
+				lock,
+				queued_bytes: &self.inner.queued_bytes,
+				queued_notifications: &self.inner.queued_notifications,
+			})
 		} else {
 			Err(())
 		}
@@ -468,6 +544,10 @@ impl NotificationsSink {
 pub struct Ready<'a> {
 	/// Guarded channel. The channel inside is guaranteed to not be full.
 	lock: FuturesMutexGuard<'a, mpsc::Sender<NotificationsSinkMessage>>,
+	/// Number of bytes queued in the channels of the sink.
+	queued_bytes: &'a AtomicUsize,
+	/// Number of notifications queued in the channels of the sink.
+	queued_notifications: &'a AtomicUsize,
 }
 
 impl<'a> Ready<'a> {
@@ -475,9 +555,17 @@ impl<'a> Ready<'a> {
 	///
 	/// Returns an error if the substream has been closed.
 	pub fn send(mut self, notification: impl Into<Vec<u8>>) -> Result<(), ()> {
+		let message = notification.into();
+		let len = message.len();
+		// count the bytes before queueing so the handler never dequeues uncounted bytes
+		self.queued_bytes.fetch_add(len, Ordering::Relaxed);
+		self.queued_notifications.fetch_add(1, Ordering::Relaxed);
 		self.lock
-			.start_send(NotificationsSinkMessage::Notification { message: notification.into() })
-			.map_err(|_| ())
+			.start_send(NotificationsSinkMessage::Notification { message })
+			.map_err(|_| {
+				self.queued_bytes.fetch_sub(len, Ordering::Relaxed);
+				self.queued_notifications.fetch_sub(1, Ordering::Relaxed);
+			})
 	}
 }
 
@@ -570,11 +658,15 @@ impl ConnectionHandler for NotifsHandler {
 					State::Opening { ref mut in_substream, inbound } => {
 						let (async_tx, async_rx) = mpsc::channel(ASYNC_NOTIFICATIONS_BUFFER_SIZE);
 						let (sync_tx, sync_rx) = mpsc::channel(SYNC_NOTIFICATIONS_BUFFER_SIZE);
+						let queued_bytes = Arc::new(AtomicUsize::new(0));
+						let queued_notifications = Arc::new(AtomicUsize::new(0));
 						let notifications_sink = NotificationsSink {
 							inner: Arc::new(NotificationsSinkInner {
 								peer_id: self.peer_id,
 								async_channel: FuturesMutex::new(async_tx),
 								sync_channel: Mutex::new(Some(sync_tx)),
+								queued_bytes: queued_bytes.clone(),
+								queued_notifications: queued_notifications.clone(),
 							}),
 							metrics: self.metrics.clone(),
 						};
@@ -582,6 +674,8 @@ impl ConnectionHandler for NotifsHandler {
 						self.protocols[protocol_index].state = State::Open {
 							notifications_sink_rx: stream::select(async_rx.fuse(), sync_rx.fuse())
 								.peekable(),
+							queued_bytes,
+							queued_notifications,
 							out_substream: Some(new_open.substream),
 							in_substream: in_substream.take(),
 						};
@@ -719,6 +813,10 @@ impl ConnectionHandler for NotifsHandler {
 					NotifsHandlerOut::CloseResult { protocol_index },
 				));
 			},
+
+			NotifsHandlerIn::SetFallbacks { protocol_index, fallback_names } => {
+				self.protocols[protocol_index].config.fallback_names = fallback_names;
+			},
 		}
 	}
 
@@ -755,7 +853,11 @@ impl ConnectionHandler for NotifsHandler {
 		// substream.
 		for protocol_index in 0..self.protocols.len() {
 			if let State::Open {
-				notifications_sink_rx, out_substream: Some(out_substream), ..
+				notifications_sink_rx,
+				queued_bytes,
+				queued_notifications,
+				out_substream: Some(out_substream),
+				..
 			} = &mut self.protocols[protocol_index].state
 			{
 				loop {
@@ -791,6 +893,8 @@ impl ConnectionHandler for NotifsHandler {
 						},
 					};
 
+					queued_bytes.fetch_sub(message.len(), Ordering::Relaxed);
+					queued_notifications.fetch_sub(1, Ordering::Relaxed);
 					let _ = out_substream.start_send_unpin(message);
 					// Note that flushing is performed later down this function.
 				}
@@ -937,6 +1041,8 @@ pub mod tests {
 					peer_id: peer,
 					async_channel: FuturesMutex::new(async_tx),
 					sync_channel: Mutex::new(Some(sync_tx)),
+					queued_bytes: Arc::new(AtomicUsize::new(0)),
+					queued_notifications: Arc::new(AtomicUsize::new(0)),
 				}),
 				metrics: None,
 			};
@@ -1597,17 +1703,23 @@ pub mod tests {
 
 		let (async_tx, async_rx) = futures::channel::mpsc::channel(ASYNC_NOTIFICATIONS_BUFFER_SIZE);
 		let (sync_tx, sync_rx) = futures::channel::mpsc::channel(1);
+		let queued_bytes = Arc::new(AtomicUsize::new(0));
+		let queued_notifications = Arc::new(AtomicUsize::new(0));
 		let notifications_sink = NotificationsSink {
 			inner: Arc::new(NotificationsSinkInner {
 				peer_id: PeerId::random(),
 				async_channel: FuturesMutex::new(async_tx),
 				sync_channel: Mutex::new(Some(sync_tx)),
+				queued_bytes: queued_bytes.clone(),
+				queued_notifications: queued_notifications.clone(),
 			}),
 			metrics: None,
 		};
 
 		handler.protocols[0].state = State::Open {
 			notifications_sink_rx: stream::select(async_rx.fuse(), sync_rx.fuse()).peekable(),
+			queued_bytes,
+			queued_notifications,
 			out_substream: Some(NotificationsOutSubstream::new(Framed::new(io, codec))),
 			in_substream: None,
 		};
@@ -18,6 +18,68 @@
 
 use crate::{service::metrics::NotificationMetrics, types::ProtocolName};
 
+use parking_lot::Mutex;
+
+use std::{
+	collections::VecDeque,
+	sync::Weak,
+	time::{Duration, Instant},
+};
+
+/// Window over which the churn of the substreams of a protocol is measured.
+const CHURN_WINDOW: Duration = Duration::from_secs(60);
+
+/// Interval at which the churn of the substreams of a protocol is refreshed.
+const CHURN_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Substreams of a protocol opened and closed within the last [`CHURN_WINDOW`].
+#[derive(Debug, Clone, Default)]
+pub struct SubstreamChurn {
+	/// When the substreams were opened or closed, oldest first.
+	events: VecDeque<Instant>,
+}
+
+impl SubstreamChurn {
+	/// Record a substream opened or closed at `now`.
+	///
+	/// Returns the number of substreams opened and closed within the window ending at `now`.
+	pub fn record(&mut self, now: Instant) -> usize {
+		self.events.push_back(now);
+		self.count(now)
+	}
+
+	/// Get the number of substreams opened and closed within the window ending at `now`.
+	pub fn count(&mut self, now: Instant) -> usize {
+		while self
+			.events
+			.front()
+			.map_or(false, |event| now.saturating_duration_since(*event) >= CHURN_WINDOW)
+		{
+			self.events.pop_front();
+		}
+
+		self.events.len()
+	}
+}
+
+/// Periodically register the substream `churn` of `protocol` to Prometheus, so the churn decays
+/// once substreams stop being opened and closed.
+///
+/// Returns when `churn` is dropped.
+pub async fn refresh_substream_churn(
+	churn: Weak<Mutex<SubstreamChurn>>,
+	metrics: NotificationMetrics,
+	protocol: ProtocolName,
+) {
+	loop {
+		futures_timer::Delay::new(CHURN_REFRESH_INTERVAL).await;
+
+		let Some(churn) = churn.upgrade() else { return };
+		let churn = churn.lock().count(Instant::now());
+		metrics.register_substream_churn(&protocol, churn);
+	}
+}
+
 /// Register opened substream to Prometheus.
 pub fn register_substream_opened(metrics: &Option<NotificationMetrics>, protocol: &ProtocolName) {
 	if let Some(metrics) = metrics {
@@ -32,6 +94,17 @@ pub fn register_substream_closed(metrics: &Option<NotificationMetrics>, protocol
 	}
 }
 
+/// Register substream churn to Prometheus.
+pub fn register_substream_churn(
+	metrics: &Option<NotificationMetrics>,
+	protocol: &ProtocolName,
+	churn: usize,
+) {
+	if let Some(metrics) = metrics {
+		metrics.register_substream_churn(protocol, churn);
+	}
+}
+
 /// Register sent notification to Prometheus.
 pub fn register_notification_sent(
 	metrics: &Option<std::sync::Arc<NotificationMetrics>>,
@@ -53,3 +126,60 @@ pub fn register_notification_received(
 		metrics.register_notification_received(protocol, size);
 	}
 }
+
+/// Register received notification which failed authentication to Prometheus.
+pub fn register_notification_unauthenticated(
+	metrics: &Option<std::sync::Arc<NotificationMetrics>>,
+	protocol: &ProtocolName,
+) {
+	if let Some(metrics) = metrics {
+		metrics.register_notification_unauthenticated(protocol);
+	}
+}
+
+/// Register received notification dropped because of the inbound rate limit to Prometheus.
+pub fn register_notification_dropped_rate_limited(
+	metrics: &Option<NotificationMetrics>,
+	protocol: &ProtocolName,
+) {
+	if let Some(metrics) = metrics {
+		metrics.register_notification_dropped_rate_limited(protocol);
+	}
+}
+
+/// Register received notifications dropped while a subscriber paused their delivery to
+/// Prometheus.
+pub fn register_notification_dropped_paused(
+	metrics: &Option<NotificationMetrics>,
+	protocol: &ProtocolName,
+	dropped: usize,
+) {
+	if let Some(metrics) = metrics {
+		if dropped > 0 {
+			metrics.register_notification_dropped_paused(protocol, dropped);
+		}
+	}
+}
+
+/// Register subscribers pruned because their event stream was closed to Prometheus.
+pub fn register_subscribers_pruned(
+	metrics: &Option<NotificationMetrics>,
+	protocol: &ProtocolName,
+	pruned: usize,
+) {
+	if let Some(metrics) = metrics {
+		if pruned > 0 {
+			metrics.register_subscribers_pruned(protocol, pruned);
+		}
+	}
+}
+
+/// Register sent notification dropped because the channel to the peer is full to Prometheus.
+pub fn register_notification_dropped(
+	metrics: &Option<std::sync::Arc<NotificationMetrics>>,
+	protocol: &ProtocolName,
+) {
+	if let Some(metrics) = metrics {
+		metrics.register_notification_dropped(protocol);
+	}
+}
@@ -24,7 +24,9 @@ use crate::{
 	service::{
 		metrics::NotificationMetrics,
 		traits::{
-			Direction, MessageSink, NotificationEvent, NotificationService, ValidationResult,
+			AuthMode, CoalesceConfig, CompressionConfig, Direction, MessageSink, NotificationEvent,
+			NotificationService, PeerDiagnostic, PeerStats, SendStatus, SinkState,
+			ValidationResult,
 		},
 	},
 	types::ProtocolName,
@@ -32,16 +34,27 @@ use crate::{
 
 use futures::{
 	stream::{FuturesUnordered, Stream},
-	StreamExt,
+	FutureExt, StreamExt,
 };
 use libp2p::PeerId;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use schnellru::{ByLength, LruMap};
 use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::ReceiverStream;
 
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
 
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{
+	any::Any,
+	collections::{HashMap, VecDeque},
+	fmt,
+	fmt::Debug,
+	future::Future,
+	pin::Pin,
+	sync::{Arc, Weak},
+	task::{Context, Poll},
+	time::{Duration, Instant},
+};
 
 pub(crate) mod metrics;
 
@@ -54,8 +67,26 @@ const LOG_TARGET: &str = "sub-libp2p::notification::service";
 /// Default command queue size.
 const COMMAND_QUEUE_SIZE: usize = 64;
 
+/// Minimum capacity of the peer map before it's considered for shrinking.
+const PEERS_MIN_SHRINK_CAPACITY: usize = 32;
+
+/// The peer map is shrunk when its capacity is at least this many times its length.
+const PEERS_SHRINK_FACTOR: usize = 4;
+
+/// Maximum number of remembered outcomes of inbound substream validations.
+const MAX_VALIDATION_OUTCOMES: u32 = 1024;
+
+/// Maximum number of received notifications held back while delivery is paused.
+const MAX_HELD_NOTIFICATIONS: usize = 1024;
+
+/// Time without notifications from a peer after which its health score is halved.
+const PEER_HEALTH_IDLE_PERIOD: Duration = Duration::from_secs(60);
+
 /// Type representing subscribers of a notification protocol.
-type Subscribers = Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>;
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+/// Type representing the flow control state shared by a handle and its [`Subscriber`].
+type SharedFlowControl = Arc<Mutex<FlowControl>>;
 
 /// Type representing a distributable message sink.
 /// Detached message sink must carry the protocol name for registering metrics.
@@ -63,6 +94,184 @@ type Subscribers = Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>
 /// See documentation for [`PeerContext`] for more details.
 type NotificationSink = Arc<Mutex<(NotificationsSink, ProtocolName)>>;
 
+/// Type representing the connected peers of a notification protocol.
+///
+/// Owned by a single [`NotificationHandle`] unless the handle was created using
+/// [`NotificationService::clone_shared()`], in which case all shared handles use the same map.
+type Peers = Arc<RwLock<HashMap<PeerId, PeerContext>>>;
+
+/// Type representing the encoder applied to notifications sent to peers using a fallback protocol.
+type FallbackEncoder = Arc<dyn Fn(&[u8], &ProtocolName) -> Vec<u8> + Send + Sync>;
+
+/// Type representing the interceptor applied to received notifications.
+type ReceiveInterceptor =
+	Arc<dyn Fn(sc_network_types::PeerId, Vec<u8>) -> Option<Vec<u8>> + Send + Sync>;
+
+/// Type representing the callback framing a batch of coalesced notifications.
+type CoalesceFramer = Arc<dyn Fn(Vec<Vec<u8>>) -> Vec<u8> + Send + Sync>;
+
+/// Type representing the outcomes of the most recent inbound substream validations, `true` if the
+/// substream was accepted.
+type ValidationOutcomes = Arc<Mutex<LruMap<PeerId, bool>>>;
+
+/// Create empty [`ValidationOutcomes`].
+fn validation_outcomes() -> ValidationOutcomes {
+	Arc::new(Mutex::new(LruMap::new(ByLength::new(MAX_VALIDATION_OUTCOMES))))
+}
+
+/// Type representing the callback checking whether a peer supports compression from its
+/// handshake.
+type CompressionSupport = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Type representing application-defined metadata attached to a peer.
+type PeerMetadata = Arc<dyn Any + Send + Sync>;
+
+/// Type representing the callback computing the authentication tag of a sent notification.
+type AuthSigner = Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>;
+
+/// Type representing the callback verifying the authentication tag of a received notification.
+type AuthVerifier = Arc<dyn Fn(&[u8], &[u8]) -> bool + Send + Sync>;
+
+/// Type representing the callback checking whether a peer supports authentication tags from its
+/// handshake.
+type AuthSupport = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Size of the trailer holding the length of the authentication tag appended to notifications.
+const AUTH_TAG_LEN_SIZE: usize = core::mem::size_of::<u32>();
+
+/// Append authentication `tag` to `notification`, followed by the length of the tag.
+fn append_auth_tag(mut notification: Vec<u8>, tag: Vec<u8>) -> Vec<u8> {
+	notification.reserve(tag.len() + AUTH_TAG_LEN_SIZE);
+	notification.extend_from_slice(&tag);
+	notification.extend_from_slice(&(tag.len() as u32).to_le_bytes());
+	notification
+}
+
+/// Split `notification` into the payload and the authentication tag appended to it.
+///
+/// Returns `None` if `notification` is too short to carry the tag it claims.
+fn split_auth_tag(notification: &[u8]) -> Option<(&[u8], &[u8])> {
+	let (rest, tag_len) = notification.split_at(notification.len().checked_sub(AUTH_TAG_LEN_SIZE)?);
+	let tag_len = u32::from_le_bytes(tag_len.try_into().ok()?) as usize;
+
+	Some(rest.split_at(rest.len().checked_sub(tag_len)?))
+}
+
+/// What happens to a subscriber whose event channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOverflow {
+	/// Events which don't fit in the channel are queued until the subscriber catches up. Once
+	/// more than `capacity` events are queued that way, the oldest received notification among
+	/// them is dropped for that subscriber. Substream events are never dropped.
+	Drop,
+
+	/// The subscriber is disconnected: its event stream ends.
+	Disconnect,
+}
+
+/// Capacity and overflow policy of the event channels of the subscribers of a protocol.
+///
+/// Applies to all handles of the protocol, including the ones created with
+/// [`NotificationService::clone()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationChannelConfig {
+	/// Maximum number of events queued in the channel of a subscriber.
+	pub capacity: usize,
+
+	/// What happens to a subscriber with `capacity` events queued.
+	pub overflow: ChannelOverflow,
+}
+
+impl Default for NotificationChannelConfig {
+	/// Effectively unbounded channels.
+	fn default() -> Self {
+		Self { capacity: usize::MAX, overflow: ChannelOverflow::Drop }
+	}
+}
+
+impl NotificationChannelConfig {
+	/// Send `event` to the subscriber with channel `tx` and `flow_control`, applying the overflow
+	/// policy if its channel is full.
+	///
+	/// Returns `false` if the subscriber is gone or was disconnected and must be pruned.
+	fn send(
+		&self,
+		tx: &TracingUnboundedSender<InnerNotificationEvent>,
+		flow_control: &mut FlowControl,
+		event: InnerNotificationEvent,
+	) -> bool {
+		if tx.is_closed() {
+			return false
+		}
+
+		match self.overflow {
+			ChannelOverflow::Drop => {
+				flow_control.overflow.push_back(event);
+
+				// keep at least one event in the channel so the subscriber is woken up and
+				// collects the overflowing events
+				while tx.len() < self.capacity.max(1) {
+					let Some(event) = flow_control.overflow.pop_front() else { break };
+					if tx.unbounded_send(event).is_err() {
+						return false
+					}
+				}
+
+				if flow_control.overflow.len() > self.capacity {
+					if let Some(index) = flow_control.overflow.iter().position(|event| {
+						matches!(event, InnerNotificationEvent::NotificationReceived { .. })
+					}) {
+						flow_control.overflow.remove(index);
+					}
+				}
+
+				true
+			},
+			ChannelOverflow::Disconnect if tx.len() >= self.capacity => {
+				tx.close();
+				false
+			},
+			ChannelOverflow::Disconnect => tx.unbounded_send(event).is_ok(),
+		}
+	}
+}
+
+/// Flow control of the notifications received by a subscriber of a protocol.
+#[derive(Debug, Default)]
+struct FlowControl {
+	/// Whether the delivery of received notifications is paused.
+	paused: bool,
+
+	/// Notifications received while paused, in the order they were received.
+	held: VecDeque<(PeerId, Vec<u8>)>,
+
+	/// Events which didn't fit in the channel of the subscriber, in the order they were sent.
+	overflow: VecDeque<InnerNotificationEvent>,
+}
+
+impl FlowControl {
+	/// Drop the notifications held for `peer`, returning how many were dropped.
+	fn drop_held(&mut self, peer: &PeerId) -> usize {
+		let held = self.held.len();
+		self.held.retain(|(from, _)| from != peer);
+		held - self.held.len()
+	}
+}
+
+/// Subscriber of the events of a notification protocol.
+#[derive(Debug)]
+struct Subscriber {
+	/// Channel the events are sent to.
+	tx: TracingUnboundedSender<InnerNotificationEvent>,
+
+	/// Flow control of the notifications received by the subscriber, shared with its handle.
+	flow_control: SharedFlowControl,
+
+	/// Outcomes of the inbound substream validations done by the subscriber, shared with its
+	/// handle.
+	validation_outcomes: ValidationOutcomes,
+}
+
 #[async_trait::async_trait]
 impl MessageSink for NotificationSink {
 	/// Send synchronous `notification` to the peer associated with this [`MessageSink`].
@@ -73,6 +282,24 @@ impl MessageSink for NotificationSink {
 		sink.0.send_sync_notification(notification);
 	}
 
+	/// Send synchronous `notification` to the peer associated with this [`MessageSink`],
+	/// reporting whether it was queued or dropped because the sync channel is full.
+	fn try_send_sync_notification(&self, notification: Vec<u8>) -> SendStatus {
+		let sink = self.lock();
+		let size = notification.len();
+
+		match sink.0.try_send_sync_notification(notification) {
+			Ok(()) => {
+				metrics::register_notification_sent(sink.0.metrics(), &sink.1, size);
+				SendStatus::Queued
+			},
+			Err(()) => {
+				metrics::register_notification_dropped(sink.0.metrics(), &sink.1);
+				SendStatus::Dropped
+			},
+		}
+	}
+
 	/// Send an asynchronous `notification` to the peer associated with this [`MessageSink`],
 	/// allowing sender to exercise backpressure.
 	///
@@ -154,17 +381,57 @@ enum InnerNotificationEvent {
 	},
 }
 
+impl InnerNotificationEvent {
+	/// Copy of the event for replaying it to a new subscriber, if it changes the set of
+	/// connected peers or their sinks.
+	fn replay(&self) -> Option<Self> {
+		match self {
+			Self::NotificationStreamOpened {
+				peer,
+				direction,
+				handshake,
+				negotiated_fallback,
+				sink,
+			} => Some(Self::NotificationStreamOpened {
+				peer: *peer,
+				direction: *direction,
+				handshake: handshake.clone(),
+				negotiated_fallback: negotiated_fallback.clone(),
+				sink: sink.clone(),
+			}),
+			Self::NotificationStreamClosed { peer } =>
+				Some(Self::NotificationStreamClosed { peer: *peer }),
+			Self::NotificationSinkReplaced { peer, sink } =>
+				Some(Self::NotificationSinkReplaced { peer: *peer, sink: sink.clone() }),
+			Self::ValidateInboundSubstream { .. } | Self::NotificationReceived { .. } => None,
+		}
+	}
+}
+
 /// Notification commands.
 ///
 /// Sent by the installed protocols to `Notifications` to open/close/modify substreams.
 #[derive(Debug)]
 pub enum NotificationCommand {
 	/// Instruct `Notifications` to open a substream to peer.
-	#[allow(unused)]
-	OpenSubstream(PeerId),
+	///
+	/// The sender receives `Err(())` if the protocol controller refused to connect the peer.
+	OpenSubstream(PeerId, oneshot::Sender<Result<(), ()>>),
+
+	/// Instruct `Notifications` to open a substream to peer, offering `fallbacks` in the given
+	/// order if the peer doesn't support the main protocol.
+	OpenSubstreamWithFallbacks {
+		/// Peer to open the substream to.
+		peer: PeerId,
+
+		/// Preferred fallback protocols, in order of preference.
+		fallbacks: Vec<ProtocolName>,
+
+		/// Receives `Err(())` if the protocol controller refused to connect the peer.
+		result_tx: oneshot::Sender<Result<(), ()>>,
+	},
 
 	/// Instruct `Notifications` to close the substream to peer.
-	#[allow(unused)]
 	CloseSubstream(PeerId),
 
 	/// Set handshake for the notifications protocol.
@@ -186,10 +453,117 @@ struct PeerContext {
 
 	/// Distributable notification sink.
 	shared_sink: NotificationSink,
+
+	/// Negotiated fallback, if any.
+	negotiated_fallback: Option<ProtocolName>,
+
+	/// Direction of the substream.
+	direction: Direction,
+
+	/// Whether the peer advertised support for compression in its handshake.
+	compression: bool,
+
+	/// Whether notifications sent to the peer are signed, i.e. the peer advertised verifying
+	/// authentication tags in its handshake.
+	sign: bool,
+
+	/// Whether notifications received from the peer are verified, i.e. the peer advertised
+	/// signing its notifications in its handshake.
+	verify: bool,
+
+	/// When the last notification was received from the peer or, if none was received, when
+	/// the substream was opened.
+	last_seen: Instant,
+
+	/// Notifications sent and received since the substream was opened.
+	stats: PeerStats,
+
+	/// Metadata attached to the peer by the protocol.
+	metadata: Option<PeerMetadata>,
+
+	/// Handle counting the received notifications of the peer, see
+	/// [`NotificationHandle::counts_received`].
+	stats_owner: Weak<()>,
+}
+
+/// Compression of notifications.
+#[derive(Clone)]
+struct Compression {
+	/// Compression configuration.
+	config: CompressionConfig,
+
+	/// Callback checking whether a peer supports compression from its handshake.
+	supports_compression: CompressionSupport,
+}
+
+/// Synchronous notifications buffered for coalescing.
+struct Coalescing {
+	/// Coalescing configuration.
+	config: CoalesceConfig,
+
+	/// Callback framing a batch of notifications into a single notification.
+	framer: CoalesceFramer,
+
+	/// Buffered notifications of each peer and the deadline for flushing them.
+	pending: HashMap<PeerId, (Instant, Vec<Vec<u8>>)>,
+}
+
+impl Coalescing {
+	/// Buffer `notification` for `peer`.
+	///
+	/// Returns the framed notifications which are ready to be sent to `peer`.
+	fn push(&mut self, peer: PeerId, notification: Vec<u8>, now: Instant) -> Vec<Vec<u8>> {
+		let mut ready = Vec::new();
+
+		if self.pending.get(&peer).map_or(false, |(deadline, _)| *deadline <= now) {
+			ready.extend(self.take(&peer));
+		}
+
+		let (_, batch) = self
+			.pending
+			.entry(peer)
+			.or_insert_with(|| (now + self.config.window, Vec::new()));
+		batch.push(notification);
+
+		if batch.len() >= self.config.max_batch {
+			ready.extend(self.take(&peer));
+		}
+
+		ready
+	}
+
+	/// Take the framed batch of `peer`, if any.
+	fn take(&mut self, peer: &PeerId) -> Option<Vec<u8>> {
+		self.pending.remove(peer).map(|(_, batch)| (self.framer)(batch))
+	}
+
+	/// Take the framed batches of all peers whose deadline has passed.
+	fn take_expired(&mut self, now: Instant) -> Vec<(PeerId, Vec<u8>)> {
+		let expired = self
+			.pending
+			.iter()
+			.filter_map(|(peer, (deadline, _))| (*deadline <= now).then_some(*peer))
+			.collect::<Vec<_>>();
+
+		expired
+			.into_iter()
+			.filter_map(|peer| self.take(&peer).map(|notification| (peer, notification)))
+			.collect()
+	}
+
+	/// Take the framed batches of all peers.
+	fn take_all(&mut self) -> Vec<(PeerId, Vec<u8>)> {
+		let framer = &self.framer;
+		self.pending.drain().map(|(peer, (_, batch))| (peer, framer(batch))).collect()
+	}
+
+	/// Get the earliest deadline of the buffered batches.
+	fn next_deadline(&self) -> Option<Instant> {
+		self.pending.values().map(|(deadline, _)| *deadline).min()
+	}
 }
 
 /// Handle that is passed on to the notifications protocol.
-#[derive(Debug)]
 pub struct NotificationHandle {
 	/// Protocol name.
 	protocol: ProtocolName,
@@ -204,7 +578,56 @@ pub struct NotificationHandle {
 	subscribers: Subscribers,
 
 	/// Connected peers.
-	peers: HashMap<PeerId, PeerContext>,
+	peers: Peers,
+
+	/// Token identifying the handle as the owner of the statistics of a peer in [`Self::peers`].
+	token: Arc<()>,
+
+	/// Encoder for notifications sent to peers using a fallback protocol.
+	fallback_encoder: Option<FallbackEncoder>,
+
+	/// Interceptor applied to received notifications.
+	receive_interceptor: Option<ReceiveInterceptor>,
+
+	/// Coalescing of synchronous notifications, if enabled.
+	coalescing: Option<Coalescing>,
+
+	/// Compression of notifications, if enabled.
+	compression: Option<Compression>,
+
+	/// Signer appending an authentication tag to sent notifications and the callback checking
+	/// whether a peer verifies the tags, if enabled.
+	auth_signer: Option<(AuthSigner, AuthSupport)>,
+
+	/// Verifier of the authentication tag of received notifications, the callback checking
+	/// whether a peer signs its notifications and whether all peers must sign them, if enabled.
+	auth_verifier: Option<(AuthVerifier, AuthSupport, AuthMode)>,
+
+	/// Outcomes of the inbound substream validations done by the protocol through this handle.
+	validation_outcomes: ValidationOutcomes,
+
+	/// Flow control of the notifications received by this handle, shared with its entry in
+	/// [`Self::subscribers`].
+	flow_control: SharedFlowControl,
+
+	/// Capacity and overflow policy of the event channels of the protocol.
+	channel_config: NotificationChannelConfig,
+
+	/// Events received from `Notifications` but not yet returned by
+	/// [`NotificationService::next_event()`].
+	buffered_events: VecDeque<InnerNotificationEvent>,
+}
+
+impl fmt::Debug for NotificationHandle {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("NotificationHandle")
+			.field("protocol", &self.protocol)
+			.field("tx", &self.tx)
+			.field("rx", &self.rx)
+			.field("subscribers", &self.subscribers)
+			.field("peers", &self.peers)
+			.finish()
+	}
 }
 
 impl NotificationHandle {
@@ -213,49 +636,384 @@ impl NotificationHandle {
 		protocol: ProtocolName,
 		tx: mpsc::Sender<NotificationCommand>,
 		rx: TracingUnboundedReceiver<InnerNotificationEvent>,
-		subscribers: Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>,
+		subscribers: Subscribers,
+		flow_control: SharedFlowControl,
+		validation_outcomes: ValidationOutcomes,
+		channel_config: NotificationChannelConfig,
 	) -> Self {
-		Self { protocol, tx, rx, subscribers, peers: HashMap::new() }
+		Self {
+			protocol,
+			tx,
+			rx,
+			subscribers,
+			peers: Arc::new(RwLock::new(HashMap::new())),
+			token: Arc::new(()),
+			fallback_encoder: None,
+			receive_interceptor: None,
+			coalescing: None,
+			compression: None,
+			auth_signer: None,
+			auth_verifier: None,
+			validation_outcomes,
+			flow_control,
+			channel_config,
+			buffered_events: VecDeque::new(),
+		}
+	}
+
+	/// Create new [`NotificationHandle`] whose commands are collected in a [`CommandBuffer`]
+	/// instead of being sent to `Notifications`.
+	#[cfg(test)]
+	pub(crate) fn with_command_buffer(protocol: ProtocolName) -> (Self, CommandBuffer) {
+		let (tx, rx) = mpsc::channel(COMMAND_QUEUE_SIZE);
+		let (event_tx, event_rx) = tracing_unbounded("mpsc-notification-command-buffer", 100_000);
+		let flow_control = SharedFlowControl::default();
+		let validation_outcomes = validation_outcomes();
+		let subscriber = Subscriber {
+			tx: event_tx,
+			flow_control: flow_control.clone(),
+			validation_outcomes: validation_outcomes.clone(),
+		};
+
+		(
+			Self::new(
+				protocol,
+				tx,
+				event_rx,
+				Arc::new(Mutex::new(vec![subscriber])),
+				flow_control,
+				validation_outcomes,
+				NotificationChannelConfig::default(),
+			),
+			CommandBuffer { rx },
+		)
+	}
+
+	/// Move the events sent to this handle to [`Self::buffered_events`], in the order they were
+	/// sent, including the events which didn't fit in its channel.
+	fn buffer_pending_events(&mut self) {
+		let mut flow_control = self.flow_control.lock();
+
+		while let Ok(event) = self.rx.try_recv() {
+			self.buffered_events.push_back(event);
+		}
+		self.buffered_events.extend(flow_control.overflow.drain(..));
+	}
+
+	/// Send synchronous `notification` to `peer` without coalescing.
+	fn send_sync_notification_inner(&self, peer: &PeerId, notification: Vec<u8>) {
+		if let Some(info) = self.peers.write().get_mut(peer) {
+			let notification = self.encode_for_peer(info, notification);
+			metrics::register_notification_sent(
+				info.sink.metrics(),
+				&self.protocol,
+				notification.len(),
+			);
+			info.stats.sent_msgs += 1;
+			info.stats.sent_bytes += notification.len() as u64;
+
+			let _ = info.sink.send_sync_notification(notification);
+		}
+	}
+
+	/// Register `notification` received from `peer`, decompress it, verify its authentication
+	/// tag and apply the receive interceptor to it.
+	///
+	/// Returns `None` if the notification couldn't be decompressed, isn't authentic or the
+	/// interceptor dropped it.
+	fn on_notification_received(&self, peer: &PeerId, notification: Vec<u8>) -> Option<Vec<u8>> {
+		let (compression, verify, metrics) = match self.peers.write().get_mut(peer) {
+			Some(context) => {
+				if self.counts_received(context) {
+					context.stats.recv_msgs += 1;
+					context.stats.recv_bytes += notification.len() as u64;
+				}
+				context.last_seen = Instant::now();
+				(context.compression, context.verify, context.sink.metrics().clone())
+			},
+			// with authentication required, notifications of unknown peers are verified too
+			None => (false, self.auth_required(), None),
+		};
+
+		let notification = match &self.compression {
+			Some(Compression { config, .. }) if compression =>
+				match sp_maybe_compressed_blob::decompress(&notification, config.max_size) {
+					Ok(decompressed) => decompressed.into_owned(),
+					Err(error) => {
+						log::debug!(
+							target: LOG_TARGET,
+							"{}: failed to decompress notification from {peer}: {error:?}",
+							self.protocol,
+						);
+						return None
+					},
+				},
+			_ => notification,
+		};
+
+		let notification = match &self.auth_verifier {
+			Some((verifier, _, _)) if verify => match split_auth_tag(&notification) {
+				Some((payload, tag)) if verifier(payload, tag) => payload.to_vec(),
+				_ => {
+					log::debug!(
+						target: LOG_TARGET,
+						"{}: dropping unauthenticated notification from {peer}",
+						self.protocol,
+					);
+					metrics::register_notification_unauthenticated(&metrics, &self.protocol);
+					return None
+				},
+			},
+			_ => notification,
+		};
+
+		match &self.receive_interceptor {
+			Some(interceptor) => interceptor((*peer).into(), notification),
+			None => Some(notification),
+		}
+	}
+
+	/// Check whether the notifications of all peers must be authenticated.
+	fn auth_required(&self) -> bool {
+		self.auth_verifier
+			.as_ref()
+			.map_or(false, |(_, _, mode)| *mode == AuthMode::Required)
+	}
+
+	/// Check whether the peer which sent `handshake` is refused because it doesn't advertise
+	/// signing its notifications while authentication is required.
+	fn refuses_unsigned(&self, handshake: &[u8]) -> bool {
+		match &self.auth_verifier {
+			Some((_, supports_auth, AuthMode::Required)) => !supports_auth(handshake),
+			_ => false,
+		}
+	}
+
+	/// Check whether this handle counts the notifications received from the peer of `context`.
+	///
+	/// Handles sharing the peer map each receive their own copy of the notifications, so only
+	/// the handle which registered the peer counts them. If that handle was dropped, the first
+	/// handle receiving a notification from the peer takes over.
+	fn counts_received(&self, context: &mut PeerContext) -> bool {
+		if context.stats_owner.strong_count() == 0 {
+			context.stats_owner = Arc::downgrade(&self.token);
+		}
+		context.stats_owner.as_ptr() == Arc::as_ptr(&self.token)
+	}
+
+	/// Send the coalesced notifications whose window has elapsed.
+	fn flush_expired_notifications(&mut self) {
+		let Some(coalescing) = self.coalescing.as_mut() else { return };
+
+		for (peer, notification) in coalescing.take_expired(Instant::now()) {
+			self.send_sync_notification_inner(&peer, notification);
+		}
+	}
+
+	/// Encode `notification` for the peer if it negotiated a fallback protocol, append its
+	/// authentication tag and compress it if the peer supports compression.
+	fn encode_for_peer(&self, context: &PeerContext, notification: Vec<u8>) -> Vec<u8> {
+		let notification = match (&context.negotiated_fallback, &self.fallback_encoder) {
+			(Some(fallback), Some(encoder)) => encoder(&notification, fallback),
+			_ => notification,
+		};
+
+		let notification = match &self.auth_signer {
+			Some((signer, _)) if context.sign => {
+				let tag = signer(&notification);
+				append_auth_tag(notification, tag)
+			},
+			_ => notification,
+		};
+
+		match &self.compression {
+			Some(Compression { config, .. })
+				if context.compression && notification.len() >= config.threshold =>
+				sp_maybe_compressed_blob::compress(&notification, config.max_size)
+					.filter(|compressed| compressed.len() < notification.len())
+					.unwrap_or(notification),
+			_ => notification,
+		}
+	}
+
+	/// Create a new handle which receives its own copy of the events.
+	///
+	/// The new handle uses `shared_peers` as its view of the connected peers or, if `None`, a
+	/// copy of the peers of `self`. The copy is taken and the subscriber registered while holding
+	/// the subscribers lock, so no event can be sent in between. Substream events `self` received
+	/// but didn't process yet are replayed to the new handle, which therefore sees a consistent
+	/// snapshot of the peers followed by all subsequent events. The new handle pauses and resumes
+	/// the delivery of its notifications independently of `self`.
+	fn clone_with_peers(&mut self, shared_peers: Option<Peers>) -> Box<dyn NotificationService> {
+		let mut subscribers = self.subscribers.lock();
+
+		let (event_tx, event_rx) = tracing_unbounded(self.rx.name(), 100_000);
+		let token = Arc::new(());
+		let peers = match shared_peers {
+			Some(peers) => peers,
+			None => {
+				self.buffer_pending_events();
+				for event in self.buffered_events.iter().filter_map(InnerNotificationEvent::replay)
+				{
+					let _ = event_tx.unbounded_send(event);
+				}
+
+				let mut peers = self.peers.read().clone();
+				for context in peers.values_mut() {
+					context.stats_owner = Arc::downgrade(&token);
+				}
+				Arc::new(RwLock::new(peers))
+			},
+		};
+		let flow_control = SharedFlowControl::default();
+		let validation_outcomes = validation_outcomes();
+		subscribers.push(Subscriber {
+			tx: event_tx,
+			flow_control: flow_control.clone(),
+			validation_outcomes: validation_outcomes.clone(),
+		});
+
+		Box::new(NotificationHandle {
+			protocol: self.protocol.clone(),
+			tx: self.tx.clone(),
+			rx: event_rx,
+			peers,
+			token,
+			subscribers: self.subscribers.clone(),
+			fallback_encoder: self.fallback_encoder.clone(),
+			receive_interceptor: self.receive_interceptor.clone(),
+			coalescing: None,
+			compression: self.compression.clone(),
+			auth_signer: self.auth_signer.clone(),
+			auth_verifier: self.auth_verifier.clone(),
+			validation_outcomes,
+			flow_control,
+			channel_config: self.channel_config,
+			buffered_events: VecDeque::new(),
+		})
 	}
 }
 
 #[async_trait::async_trait]
 impl NotificationService for NotificationHandle {
 	/// Instruct `Notifications` to open a new substream for `peer`.
-	async fn open_substream(&mut self, _peer: sc_network_types::PeerId) -> Result<(), ()> {
-		todo!("support for opening substreams not implemented yet");
+	///
+	/// The request goes through the protocol controller which connects the peer using an outgoing
+	/// slot, if one is available. Requests for peers which are already connected have no effect.
+	/// Returns `Err(())` if the protocol controller refused the request. The opening of the
+	/// substream is reported through [`NotificationService::next_event()`].
+	async fn open_substream(&mut self, peer: sc_network_types::PeerId) -> Result<(), ()> {
+		let (result_tx, result_rx) = oneshot::channel();
+
+		self.tx
+			.send(NotificationCommand::OpenSubstream(peer.into(), result_tx))
+			.await
+			.map_err(|_| ())?;
+		result_rx.await.unwrap_or(Err(()))
+	}
+
+	/// Instruct `Notifications` to open a new substream for `peer`, preferring `fallbacks`.
+	///
+	/// The preference list is carried in the command so that `Notifications` offers only the
+	/// given fallbacks, in the given order, when negotiating the substream.
+	async fn open_substream_with_fallbacks(
+		&mut self,
+		peer: sc_network_types::PeerId,
+		fallbacks: Vec<ProtocolName>,
+	) -> Result<(), ()> {
+		let (result_tx, result_rx) = oneshot::channel();
+
+		self.tx
+			.send(NotificationCommand::OpenSubstreamWithFallbacks {
+				peer: peer.into(),
+				fallbacks,
+				result_tx,
+			})
+			.await
+			.map_err(|_| ())?;
+		result_rx.await.unwrap_or(Err(()))
 	}
 
 	/// Instruct `Notifications` to close substream for `peer`.
-	async fn close_substream(&mut self, _peer: sc_network_types::PeerId) -> Result<(), ()> {
-		todo!("support for closing substreams not implemented yet, call `NetworkService::disconnect_peer()` instead");
+	///
+	/// The peer is removed from the handle once `Notifications` reports the substream closed.
+	async fn close_substream(&mut self, peer: sc_network_types::PeerId) -> Result<(), ()> {
+		let peer: PeerId = peer.into();
+
+		if !self.peers.read().contains_key(&peer) {
+			return Err(())
+		}
+
+		self.tx.send(NotificationCommand::CloseSubstream(peer)).await.map_err(|_| ())
+	}
+
+	/// Instruct `Notifications` to open a new substream for `peer` without waiting for space in
+	/// the command queue.
+	///
+	/// Whether the protocol controller accepts the request is not reported.
+	fn try_open_substream(&mut self, peer: sc_network_types::PeerId) -> Result<(), ()> {
+		let (result_tx, _) = oneshot::channel();
+
+		self.tx
+			.try_send(NotificationCommand::OpenSubstream(peer.into(), result_tx))
+			.map_err(|_| ())
+	}
+
+	/// Instruct `Notifications` to close substream for `peer` without waiting for space in the
+	/// command queue.
+	fn try_close_substream(&mut self, peer: sc_network_types::PeerId) -> Result<(), ()> {
+		let peer: PeerId = peer.into();
+
+		if !self.peers.read().contains_key(&peer) {
+			return Err(())
+		}
+
+		self.tx.try_send(NotificationCommand::CloseSubstream(peer)).map_err(|_| ())
 	}
 
 	/// Send synchronous `notification` to `peer`.
 	fn send_sync_notification(&mut self, peer: &sc_network_types::PeerId, notification: Vec<u8>) {
-		if let Some(info) = self.peers.get(&((*peer).into())) {
-			metrics::register_notification_sent(
-				info.sink.metrics(),
-				&self.protocol,
-				notification.len(),
-			);
+		let peer: PeerId = (*peer).into();
 
-			let _ = info.sink.send_sync_notification(notification);
+		let ready = match self.coalescing.as_mut() {
+			None => vec![notification],
+			Some(_) if !self.peers.read().contains_key(&peer) => return,
+			Some(coalescing) => coalescing.push(peer, notification, Instant::now()),
+		};
+
+		for notification in ready {
+			self.send_sync_notification_inner(&peer, notification);
 		}
 	}
 
+	/// Send synchronous `notification` to all connected peers.
+	fn broadcast_sync_notification(&mut self, notification: Vec<u8>) {
+		let peers = self.peers.read().keys().copied().collect::<Vec<_>>();
+		let Some((last, rest)) = peers.split_last() else { return };
+
+		// the notification is moved into the last send to avoid a needless clone
+		for peer in rest {
+			self.send_sync_notification(&(*peer).into(), notification.clone());
+		}
+		self.send_sync_notification(&(*last).into(), notification);
+	}
+
 	/// Send asynchronous `notification` to `peer`, allowing sender to exercise backpressure.
 	async fn send_async_notification(
 		&mut self,
 		peer: &sc_network_types::PeerId,
 		notification: Vec<u8>,
 	) -> Result<(), error::Error> {
+		// the sink must be cloned because the lock cannot be held across `.await`
+		let (sink, notification) = {
+			let peers = self.peers.read();
+			let context = peers
+				.get(&peer.into())
+				.ok_or_else(|| error::Error::PeerDoesntExist((*peer).into()))?;
+
+			(context.sink.clone(), self.encode_for_peer(context, notification))
+		};
 		let notification_len = notification.len();
-		let sink = &self
-			.peers
-			.get(&peer.into())
-			.ok_or_else(|| error::Error::PeerDoesntExist((*peer).into()))?
-			.sink;
 
 		sink.reserve_notification()
 			.await
@@ -268,6 +1026,10 @@ impl NotificationService for NotificationHandle {
 					&self.protocol,
 					notification_len,
 				);
+				if let Some(context) = self.peers.write().get_mut(&peer.into()) {
+					context.stats.sent_msgs += 1;
+					context.stats.sent_bytes += notification_len as u64;
+				}
 			})
 	}
 
@@ -290,13 +1052,49 @@ impl NotificationService for NotificationHandle {
 	/// Get next event from the `Notifications` event stream.
 	async fn next_event(&mut self) -> Option<NotificationEvent> {
 		loop {
-			match self.rx.next().await? {
-				InnerNotificationEvent::ValidateInboundSubstream { peer, handshake, result_tx } =>
+			if self.buffered_events.is_empty() && !self.flow_control.lock().overflow.is_empty() {
+				self.buffer_pending_events();
+			}
+
+			let deadline = self.coalescing.as_ref().and_then(Coalescing::next_deadline);
+			let event = match deadline {
+				_ if !self.buffered_events.is_empty() => self.buffered_events.pop_front(),
+				None => Some(self.rx.next().await?),
+				Some(deadline) => {
+					let delay = futures_timer::Delay::new(
+						deadline.saturating_duration_since(Instant::now()),
+					);
+
+					match futures::future::select(self.rx.next(), delay).await {
+						futures::future::Either::Left((event, _)) => Some(event?),
+						futures::future::Either::Right(_) => None,
+					}
+				},
+			};
+
+			let Some(event) = event else {
+				self.flush_expired_notifications();
+				continue
+			};
+
+			match event {
+				InnerNotificationEvent::ValidateInboundSubstream { peer, handshake, result_tx } => {
+					if self.refuses_unsigned(&handshake) {
+						log::debug!(
+							target: LOG_TARGET,
+							"{}: rejecting inbound substream of {peer}, which doesn't sign its notifications",
+							self.protocol,
+						);
+						let _ = result_tx.send(ValidationResult::Reject);
+						continue
+					}
+
 					return Some(NotificationEvent::ValidateInboundSubstream {
 						peer: peer.into(),
 						handshake,
 						result_tx,
-					}),
+					})
+				},
 				InnerNotificationEvent::NotificationStreamOpened {
 					peer,
 					handshake,
@@ -304,13 +1102,42 @@ impl NotificationService for NotificationHandle {
 					direction,
 					sink,
 				} => {
-					self.peers.insert(
-						peer,
-						PeerContext {
-							sink: sink.clone(),
-							shared_sink: Arc::new(Mutex::new((sink, self.protocol.clone()))),
-						},
-					);
+					// outbound substreams aren't validated, so close those of peers refused
+					// because they don't sign their notifications; their notifications are
+					// verified and dropped until then
+					if self.refuses_unsigned(&handshake) {
+						log::debug!(
+							target: LOG_TARGET,
+							"{}: closing substream to {peer}, which doesn't sign its notifications",
+							self.protocol,
+						);
+						let _ = self.tx.send(NotificationCommand::CloseSubstream(peer)).await;
+					}
+
+					// handles sharing the peer map may have registered the peer already
+					self.peers.write().entry(peer).or_insert_with(|| PeerContext {
+						sink: sink.clone(),
+						shared_sink: Arc::new(Mutex::new((sink, self.protocol.clone()))),
+						negotiated_fallback: negotiated_fallback.clone(),
+						direction,
+						compression: self.compression.as_ref().map_or(false, |compression| {
+							(compression.supports_compression)(&handshake)
+						}),
+						sign: self
+							.auth_signer
+							.as_ref()
+							.map_or(false, |(_, supports_auth)| supports_auth(&handshake)),
+						verify: self.auth_verifier.as_ref().map_or(
+							false,
+							|(_, supports_auth, mode)| {
+								*mode == AuthMode::Required || supports_auth(&handshake)
+							},
+						),
+						last_seen: Instant::now(),
+						stats: PeerStats::default(),
+						metadata: None,
+						stats_owner: Arc::downgrade(&self.token),
+					});
 					return Some(NotificationEvent::NotificationStreamOpened {
 						peer: peer.into(),
 						handshake,
@@ -319,16 +1146,35 @@ impl NotificationService for NotificationHandle {
 					})
 				},
 				InnerNotificationEvent::NotificationStreamClosed { peer } => {
-					self.peers.remove(&peer);
+					{
+						let mut peers = self.peers.write();
+						peers.remove(&peer);
+
+						// release the memory retained by the map after heavy churn
+						if peers.capacity() >= PEERS_MIN_SHRINK_CAPACITY &&
+							peers.len().saturating_mul(PEERS_SHRINK_FACTOR) <= peers.capacity()
+						{
+							peers.shrink_to_fit();
+						}
+					}
+					if let Some(coalescing) = self.coalescing.as_mut() {
+						coalescing.pending.remove(&peer);
+					}
 					return Some(NotificationEvent::NotificationStreamClosed { peer: peer.into() })
 				},
-				InnerNotificationEvent::NotificationReceived { peer, notification } =>
+				InnerNotificationEvent::NotificationReceived { peer, notification } => {
+					let Some(notification) = self.on_notification_received(&peer, notification)
+					else {
+						continue
+					};
+
 					return Some(NotificationEvent::NotificationReceived {
 						peer: peer.into(),
 						notification,
-					}),
+					})
+				},
 				InnerNotificationEvent::NotificationSinkReplaced { peer, sink } => {
-					match self.peers.get_mut(&peer) {
+					match self.peers.write().get_mut(&peer) {
 						None => log::error!(
 							"{}: notification sink replaced for {peer} but peer does not exist",
 							self.protocol
@@ -345,18 +1191,24 @@ impl NotificationService for NotificationHandle {
 
 	// Clone [`NotificationService`]
 	fn clone(&mut self) -> Result<Box<dyn NotificationService>, ()> {
-		let mut subscribers = self.subscribers.lock();
-
-		let (event_tx, event_rx) = tracing_unbounded(self.rx.name(), 100_000);
-		subscribers.push(event_tx);
+		Ok(self.clone_with_peers(None))
+	}
 
-		Ok(Box::new(NotificationHandle {
-			protocol: self.protocol.clone(),
-			tx: self.tx.clone(),
-			rx: event_rx,
-			peers: self.peers.clone(),
-			subscribers: self.subscribers.clone(),
-		}))
+	/// Clone [`NotificationService`] so that the clones share the same view of connected peers.
+	///
+	/// Unlike [`NotificationService::clone()`], which gives the new handle its own copy of the
+	/// peers, the returned handle shares the peer map with `self` and the map is updated by
+	/// whichever handle processes a substream event first. This avoids duplicating a large map
+	/// for protocols with many clones, at the cost of taking a lock on every send and on every
+	/// substream event. A peer is registered by the first handle processing the opening of its
+	/// substream, which also counts the notifications received from it, so the metadata and the
+	/// statistics of the peer are kept when the other handles process the same events. As each
+	/// handle still processes its own copy of the events, a handle that lags behind the others
+	/// may briefly apply an outdated substream event to the shared view.
+	fn clone_shared(&mut self) -> Result<Box<dyn NotificationService>, ()> {
+		let peers = self.peers.clone();
+
+		Ok(self.clone_with_peers(Some(peers)))
 	}
 
 	/// Get protocol name.
@@ -366,11 +1218,354 @@ impl NotificationService for NotificationHandle {
 
 	/// Get message sink of the peer.
 	fn message_sink(&self, peer: &sc_network_types::PeerId) -> Option<Box<dyn MessageSink>> {
-		match self.peers.get(&peer.into()) {
+		match self.peers.read().get(&peer.into()) {
 			Some(context) => Some(Box::new(context.shared_sink.clone())),
 			None => None,
 		}
 	}
+
+	/// Set the encoder applied to notifications sent to peers that negotiated a fallback
+	/// protocol.
+	///
+	/// Only applies to notifications sent through the handle, notifications sent through a
+	/// detached [`MessageSink`] are sent as-is.
+	fn set_fallback_encoder(&mut self, encoder: FallbackEncoder) {
+		self.fallback_encoder = Some(encoder);
+	}
+
+	/// Enable coalescing of synchronous notifications.
+	///
+	/// Coalescing is not inherited by clones of the handle. Buffered notifications are sent when
+	/// the batch is full, when a notification is sent to the peer after the window has elapsed or
+	/// when the window elapses while the handle is polled for events.
+	fn set_coalescing(
+		&mut self,
+		config: CoalesceConfig,
+		framer: Arc<dyn Fn(Vec<Vec<u8>>) -> Vec<u8> + Send + Sync>,
+	) {
+		let buffered = self.coalescing.as_mut().map(Coalescing::take_all).unwrap_or_default();
+		for (peer, notification) in buffered {
+			self.send_sync_notification_inner(&peer, notification);
+		}
+
+		self.coalescing = Some(Coalescing { config, framer, pending: HashMap::new() });
+	}
+
+	/// Take all immediately available notifications received from `peer`.
+	///
+	/// Notifications received after the substream of `peer` was closed or reopened are left
+	/// queued, together with all other events, so that they are returned by
+	/// [`NotificationService::next_event()`] in order.
+	fn drain_received(&mut self, peer: &sc_network_types::PeerId) -> Vec<Vec<u8>> {
+		let peer: PeerId = (*peer).into();
+
+		self.buffer_pending_events();
+
+		let mut received = Vec::new();
+		let mut substream_changed = false;
+
+		for event in std::mem::take(&mut self.buffered_events) {
+			match event {
+				InnerNotificationEvent::NotificationReceived { peer: from, notification }
+					if from == peer && !substream_changed =>
+				{
+					received.extend(self.on_notification_received(&peer, notification));
+				},
+				event => {
+					if let InnerNotificationEvent::NotificationStreamOpened { peer: from, .. } |
+					InnerNotificationEvent::NotificationStreamClosed { peer: from } = &event
+					{
+						substream_changed |= *from == peer;
+					}
+					self.buffered_events.push_back(event);
+				},
+			}
+		}
+
+		received
+	}
+
+	/// Check whether the substream to `peer` supports backpressure.
+	fn supports_backpressure(&self, peer: &sc_network_types::PeerId) -> Option<bool> {
+		// `NotificationsSink` always has a bounded channel for asynchronous notifications
+		self.peers.read().get(&peer.into()).map(|_| true)
+	}
+
+	/// Set the interceptor applied to received notifications.
+	///
+	/// Only applies to the notifications received by this handle.
+	fn set_receive_interceptor(&mut self, interceptor: ReceiveInterceptor) {
+		self.receive_interceptor = Some(interceptor);
+	}
+
+	/// Get the notification statistics of `peer`.
+	fn peer_stats(&self, peer: &sc_network_types::PeerId) -> Option<PeerStats> {
+		self.peers.read().get(&peer.into()).map(|context| context.stats)
+	}
+
+	/// Get the protocol negotiated with `peer`.
+	fn negotiated_protocol(&self, peer: &sc_network_types::PeerId) -> Option<ProtocolName> {
+		self.peers
+			.read()
+			.get(&peer.into())
+			.map(|context| context.negotiated_fallback.clone().unwrap_or(self.protocol.clone()))
+	}
+
+	/// Get the peers with an open substream in `direction`.
+	fn peers_by_direction(&self, direction: Direction) -> Vec<sc_network_types::PeerId> {
+		self.peers
+			.read()
+			.iter()
+			.filter_map(|(peer, context)| (context.direction == direction).then(|| (*peer).into()))
+			.collect()
+	}
+
+	/// Get the peers with an open substream.
+	fn connected_peers(&self) -> Vec<sc_network_types::PeerId> {
+		self.peers.read().keys().map(|peer| (*peer).into()).collect()
+	}
+
+	/// Check whether there is an open substream to `peer`.
+	fn is_connected(&self, peer: &sc_network_types::PeerId) -> bool {
+		self.peers.read().contains_key(&peer.into())
+	}
+
+	/// Get the total number of bytes queued for all peers.
+	fn total_queued_bytes(&self) -> Option<usize> {
+		Some(self.peers.read().values().map(|context| context.sink.queued_bytes()).sum())
+	}
+
+	/// Get the backpressure state of the sink of `peer`.
+	fn sink_backpressure(&self, peer: &sc_network_types::PeerId) -> Option<SinkState> {
+		self.peers.read().get(&peer.into()).map(|context| context.sink.state())
+	}
+
+	/// Get a diagnostic snapshot of all peers with an open substream.
+	fn peer_snapshot(&self) -> Vec<PeerDiagnostic> {
+		self.peers
+			.read()
+			.iter()
+			.map(|(peer, context)| PeerDiagnostic {
+				peer: (*peer).into(),
+				direction: context.direction,
+				negotiated_fallback: context.negotiated_fallback.clone(),
+				queued_notifications: context.sink.state().queued_notifications,
+				last_seen: context.last_seen,
+			})
+			.collect()
+	}
+
+	/// Get the health score of `peer`.
+	///
+	/// The score is the product of:
+	///  - `0` if the synchronous buffer overflowed, i.e. notifications were dropped, `1` otherwise.
+	///  - the free share of the sink, `sync_capacity / (queued_notifications + sync_capacity)`.
+	///  - `PEER_HEALTH_IDLE_PERIOD / (PEER_HEALTH_IDLE_PERIOD + idle)`, where `idle` is the time
+	///    since the peer was last seen.
+	fn peer_health(&self, peer: &sc_network_types::PeerId) -> Option<f32> {
+		let peers = self.peers.read();
+		let context = peers.get(&peer.into())?;
+		let state = context.sink.state();
+		if state.sync_overflowed {
+			return Some(0.0)
+		}
+
+		let free = match state.queued_notifications + state.sync_capacity {
+			0 => 0.0,
+			total => state.sync_capacity as f32 / total as f32,
+		};
+		let idle_period = PEER_HEALTH_IDLE_PERIOD.as_secs_f32();
+		let idle = context.last_seen.elapsed().as_secs_f32();
+
+		Some(free * idle_period / (idle_period + idle))
+	}
+
+	/// Enable compression of notifications.
+	///
+	/// Compression is inherited by clones of the handle created after this call. Notifications
+	/// sent through a detached [`MessageSink`] are not compressed.
+	fn set_compression(
+		&mut self,
+		config: CompressionConfig,
+		supports_compression: CompressionSupport,
+	) {
+		self.compression = Some(Compression { config, supports_compression });
+	}
+
+	/// Check whether the handshake of `peer` was accepted.
+	fn handshake_accepted(&self, peer: &sc_network_types::PeerId) -> Option<bool> {
+		let peer: PeerId = (*peer).into();
+
+		if self.peers.read().contains_key(&peer) {
+			return Some(true)
+		}
+
+		self.validation_outcomes.lock().get(&peer).copied()
+	}
+
+	/// Instruct `Notifications` to close the substreams of idle peers.
+	async fn close_idle(&mut self, idle_for: std::time::Duration) -> usize {
+		let idle = self
+			.peers
+			.read()
+			.iter()
+			.filter_map(|(peer, context)| {
+				(context.last_seen.elapsed() >= idle_for).then_some(*peer)
+			})
+			.collect::<Vec<_>>();
+
+		let mut closed = 0;
+		for peer in idle {
+			if self.tx.send(NotificationCommand::CloseSubstream(peer)).await.is_err() {
+				break
+			}
+			closed += 1;
+		}
+
+		closed
+	}
+
+	/// Set the signer of sent notifications.
+	///
+	/// The signer is inherited by clones of the handle created after this call. Notifications
+	/// sent through a detached [`MessageSink`] are not signed.
+	fn set_auth_signer(
+		&mut self,
+		signer: AuthSigner,
+		supports_auth: AuthSupport,
+	) -> Result<(), ()> {
+		self.auth_signer = Some((signer, supports_auth));
+		Ok(())
+	}
+
+	/// Set the verifier of received notifications.
+	///
+	/// The verifier is inherited by clones of the handle created after this call.
+	fn set_auth_verifier(
+		&mut self,
+		verifier: AuthVerifier,
+		supports_auth: AuthSupport,
+		mode: AuthMode,
+	) -> Result<(), ()> {
+		self.auth_verifier = Some((verifier, supports_auth, mode));
+		Ok(())
+	}
+
+	/// Attach `metadata` to `peer`.
+	fn set_peer_metadata(
+		&mut self,
+		peer: &sc_network_types::PeerId,
+		metadata: PeerMetadata,
+	) -> Result<(), ()> {
+		let mut peers = self.peers.write();
+		let context = peers.get_mut(&peer.into()).ok_or(())?;
+		context.metadata = Some(metadata);
+
+		Ok(())
+	}
+
+	/// Get the metadata attached to `peer`.
+	fn peer_metadata(&self, peer: &sc_network_types::PeerId) -> Option<PeerMetadata> {
+		self.peers.read().get(&peer.into()).and_then(|context| context.metadata.clone())
+	}
+
+	/// Pause the delivery of received notifications to this handle.
+	///
+	/// Other handles of the protocol keep receiving notifications.
+	fn request_pause(&mut self) {
+		self.flow_control.lock().paused = true;
+	}
+
+	/// Resume the delivery of received notifications to this handle, delivering the held
+	/// notifications first.
+	fn request_resume(&mut self) {
+		let mut subscribers = self.subscribers.lock();
+		let mut flow_control = self.flow_control.lock();
+
+		flow_control.paused = false;
+		let Some(index) = subscribers
+			.iter()
+			.position(|subscriber| Arc::ptr_eq(&subscriber.flow_control, &self.flow_control))
+		else {
+			flow_control.held.clear();
+			return
+		};
+
+		for (peer, notification) in std::mem::take(&mut flow_control.held) {
+			let event = InnerNotificationEvent::NotificationReceived { peer, notification };
+			if !self.channel_config.send(&subscribers[index].tx, &mut flow_control, event) {
+				subscribers.remove(index);
+				break
+			}
+		}
+	}
+}
+
+/// Per-peer limit of the notifications received on a protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboundRateLimit {
+	/// Maximum number of notifications per second.
+	MessagesPerSecond(u32),
+
+	/// Maximum number of notification bytes per second.
+	BytesPerSecond(u32),
+}
+
+/// Enforces an [`InboundRateLimit`] over one-second windows, separately for each peer.
+#[derive(Debug, Clone)]
+struct InboundRateLimiter {
+	/// The enforced limit.
+	limit: InboundRateLimit,
+
+	/// Start of the current window and the amount received within it, per peer.
+	windows: HashMap<PeerId, (Instant, u64)>,
+}
+
+impl InboundRateLimiter {
+	/// Create new [`InboundRateLimiter`].
+	fn new(limit: InboundRateLimit) -> Self {
+		Self { limit, windows: HashMap::new() }
+	}
+
+	/// Account a notification of `size` bytes received from `peer` at `now`.
+	///
+	/// Returns `false` if the notification exceeds the limit, in which case it's not accounted.
+	fn allow(&mut self, peer: PeerId, size: usize, now: Instant) -> bool {
+		let (cost, limit) = match self.limit {
+			InboundRateLimit::MessagesPerSecond(limit) => (1, limit),
+			InboundRateLimit::BytesPerSecond(limit) => (size as u64, limit),
+		};
+		let (start, used) = self.windows.entry(peer).or_insert((now, 0));
+		if now.saturating_duration_since(*start) >= Duration::from_secs(1) {
+			*start = now;
+			*used = 0;
+		}
+		if used.saturating_add(cost) > u64::from(limit) {
+			return false
+		}
+		*used += cost;
+		true
+	}
+
+	/// Forget the state of `peer`.
+	fn remove(&mut self, peer: &PeerId) {
+		self.windows.remove(peer);
+	}
+}
+
+/// Commands sent by a [`NotificationHandle`] created with
+/// [`NotificationHandle::with_command_buffer()`].
+#[cfg(test)]
+pub(crate) struct CommandBuffer {
+	rx: mpsc::Receiver<NotificationCommand>,
+}
+
+#[cfg(test)]
+impl CommandBuffer {
+	/// Remove and return all commands sent so far, in the order they were sent.
+	pub(crate) fn drain(&mut self) -> Vec<NotificationCommand> {
+		std::iter::from_fn(|| self.rx.try_recv().ok()).collect()
+	}
 }
 
 /// Channel pair which allows `Notifications` to interact with a protocol.
@@ -382,6 +1577,12 @@ pub struct ProtocolHandlePair {
 	/// Subscribers of the notification protocol events.
 	subscribers: Subscribers,
 
+	/// Per-peer limit of received notifications, if any.
+	inbound_rate_limit: Option<InboundRateLimit>,
+
+	/// Capacity and overflow policy of the event channels of the subscribers.
+	channel_config: NotificationChannelConfig,
+
 	// Receiver for notification commands received from the protocol implementation.
 	rx: mpsc::Receiver<NotificationCommand>,
 }
@@ -391,9 +1592,17 @@ impl ProtocolHandlePair {
 	fn new(
 		protocol: ProtocolName,
 		subscribers: Subscribers,
+		channel_config: NotificationChannelConfig,
 		rx: mpsc::Receiver<NotificationCommand>,
 	) -> Self {
-		Self { protocol, subscribers, rx }
+		Self { protocol, subscribers, inbound_rate_limit: None, channel_config, rx }
+	}
+
+	/// Limit the notifications received from each peer.
+	///
+	/// Notifications exceeding the limit are dropped before they reach the protocol.
+	pub fn set_inbound_rate_limit(&mut self, limit: InboundRateLimit) {
+		self.inbound_rate_limit = Some(limit);
 	}
 
 	/// Consume `self` and split [`ProtocolHandlePair`] into a handle which allows it to send events
@@ -402,7 +1611,12 @@ impl ProtocolHandlePair {
 		self,
 	) -> (ProtocolHandle, Box<dyn Stream<Item = NotificationCommand> + Send + Unpin>) {
 		(
-			ProtocolHandle::new(self.protocol, self.subscribers),
+			ProtocolHandle::new(
+				self.protocol,
+				self.subscribers,
+				self.inbound_rate_limit,
+				self.channel_config,
+			),
 			Box::new(ReceiverStream::new(self.rx)),
 		)
 	}
@@ -426,24 +1640,123 @@ pub(crate) struct ProtocolHandle {
 
 	/// Prometheus metrics.
 	metrics: Option<NotificationMetrics>,
+
+	/// Substreams opened and closed recently, shared with the task refreshing the churn metric.
+	churn: Arc<Mutex<metrics::SubstreamChurn>>,
+
+	/// Per-peer limiter of received notifications, if enabled.
+	rate_limiter: Option<InboundRateLimiter>,
+
+	/// Capacity and overflow policy of the event channels of the subscribers.
+	channel_config: NotificationChannelConfig,
 }
 
 pub(crate) enum ValidationCallResult {
-	WaitForValidation(oneshot::Receiver<ValidationResult>),
+	WaitForValidation(ValidationResultReceiver),
 	Delegated,
 }
 
+/// Receiver of the result of an inbound substream validation.
+///
+/// Once received, the outcome is recorded for [`NotificationService::handshake_accepted()`] of
+/// the handle which did the validation. A dropped sender counts as a rejection.
+#[derive(Debug)]
+pub(crate) struct ValidationResultReceiver {
+	/// Receiver of the validation result.
+	rx: oneshot::Receiver<ValidationResult>,
+
+	/// Validated peer and the outcomes of the validating handle, if the result comes from a
+	/// single handle.
+	recorder: Option<(PeerId, ValidationOutcomes)>,
+}
+
+impl ValidationResultReceiver {
+	/// Create new [`ValidationResultReceiver`] recording the outcome in `outcomes`.
+	fn new(
+		rx: oneshot::Receiver<ValidationResult>,
+		peer: PeerId,
+		outcomes: ValidationOutcomes,
+	) -> Self {
+		Self { rx, recorder: Some((peer, outcomes)) }
+	}
+
+	/// Record the outcome of the validation, if it's recorded.
+	fn record(&mut self, result: Option<ValidationResult>) {
+		if let Some((peer, outcomes)) = self.recorder.take() {
+			outcomes.lock().insert(peer, result == Some(ValidationResult::Accept));
+		}
+	}
+
+	/// Try to receive the validation result without waiting.
+	#[cfg(test)]
+	pub(crate) fn try_recv(&mut self) -> Result<ValidationResult, oneshot::error::TryRecvError> {
+		let result = self.rx.try_recv();
+		match result {
+			Ok(result) => self.record(Some(result)),
+			Err(oneshot::error::TryRecvError::Closed) => self.record(None),
+			Err(oneshot::error::TryRecvError::Empty) => {},
+		}
+		result
+	}
+}
+
+impl From<oneshot::Receiver<ValidationResult>> for ValidationResultReceiver {
+	fn from(rx: oneshot::Receiver<ValidationResult>) -> Self {
+		Self { rx, recorder: None }
+	}
+}
+
+impl Future for ValidationResultReceiver {
+	type Output = Result<ValidationResult, oneshot::error::RecvError>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let result = futures::ready!(self.rx.poll_unpin(cx));
+		self.record(result.ok());
+		Poll::Ready(result)
+	}
+}
+
 impl ProtocolHandle {
 	/// Create new [`ProtocolHandle`].
-	fn new(protocol: ProtocolName, subscribers: Subscribers) -> Self {
-		Self { protocol, subscribers, num_peers: 0usize, metrics: None, delegate_to_peerset: false }
+	fn new(
+		protocol: ProtocolName,
+		subscribers: Subscribers,
+		inbound_rate_limit: Option<InboundRateLimit>,
+		channel_config: NotificationChannelConfig,
+	) -> Self {
+		Self {
+			protocol,
+			subscribers,
+			num_peers: 0usize,
+			metrics: None,
+			churn: Default::default(),
+			delegate_to_peerset: false,
+			rate_limiter: inbound_rate_limit.map(InboundRateLimiter::new),
+			channel_config,
+		}
 	}
 
 	/// Set metrics.
+	///
+	/// If called within a Tokio runtime, a task is spawned to refresh the substream churn
+	/// periodically, so it doesn't stay high after a burst of substreams opened and closed.
 	pub fn set_metrics(&mut self, metrics: NotificationMetrics) {
+		if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+			runtime.spawn(metrics::refresh_substream_churn(
+				Arc::downgrade(&self.churn),
+				metrics.clone(),
+				self.protocol.clone(),
+			));
+		}
 		self.metrics = Some(metrics);
 	}
 
+	/// Record a substream opened or closed and register the churn to Prometheus.
+	fn register_substream_churn(&mut self) {
+		let churn = self.churn.lock().record(Instant::now());
+		metrics::register_substream_churn(&self.metrics, &self.protocol, churn);
+	}
+
 	/// Delegate validation to `Peerset`.
 	///
 	/// Protocols that do not do any validation themselves and only rely on `Peerset` handling
@@ -456,8 +1769,8 @@ impl ProtocolHandle {
 	/// Report to the protocol that a substream has been opened and it must be validated by the
 	/// protocol.
 	///
-	/// Return `oneshot::Receiver` which allows `Notifications` to poll for the validation result
-	/// from protocol.
+	/// Return [`ValidationResultReceiver`] which allows `Notifications` to poll for the validation
+	/// result from protocol.
 	pub fn report_incoming_substream(
 		&self,
 		peer: PeerId,
@@ -479,7 +1792,10 @@ impl ProtocolHandle {
 		// `oneshot::channel()`'s RX half without indirection
 		if subscribers.len() == 1 {
 			let (result_tx, rx) = oneshot::channel();
+			let rx =
+				ValidationResultReceiver::new(rx, peer, subscribers[0].validation_outcomes.clone());
 			return subscribers[0]
+				.tx
 				.unbounded_send(InnerNotificationEvent::ValidateInboundSubstream {
 					peer,
 					handshake,
@@ -497,30 +1813,44 @@ impl ProtocolHandle {
 				let (result_tx, rx) = oneshot::channel();
 
 				subscriber
+					.tx
 					.unbounded_send(InnerNotificationEvent::ValidateInboundSubstream {
 						peer,
 						handshake: handshake.clone(),
 						result_tx,
 					})
 					.is_ok()
-					.then_some(rx)
+					.then(|| {
+						ValidationResultReceiver::new(
+							rx,
+							peer,
+							subscriber.validation_outcomes.clone(),
+						)
+					})
 			})
 			.collect();
 
 		let (tx, rx) = oneshot::channel();
 		tokio::spawn(async move {
+			// the peer is rejected as soon as one subscriber rejects it but the remaining
+			// validations are still awaited so that their outcomes are recorded
+			let mut tx = Some(tx);
 			while let Some(event) = results.next().await {
 				match event {
 					Err(_) | Ok(ValidationResult::Reject) =>
-						return tx.send(ValidationResult::Reject),
+						if let Some(tx) = tx.take() {
+							let _ = tx.send(ValidationResult::Reject);
+						},
 					Ok(ValidationResult::Accept) => {},
 				}
 			}
 
-			return tx.send(ValidationResult::Accept)
+			if let Some(tx) = tx {
+				let _ = tx.send(ValidationResult::Accept);
+			}
 		});
 
-		Ok(ValidationCallResult::WaitForValidation(rx))
+		Ok(ValidationCallResult::WaitForValidation(rx.into()))
 	}
 
 	/// Report to the protocol that a substream has been opened and that it can now use the handle
@@ -534,44 +1864,76 @@ impl ProtocolHandle {
 		sink: NotificationsSink,
 	) -> Result<(), ()> {
 		metrics::register_substream_opened(&self.metrics, &self.protocol);
+		self.register_substream_churn();
 
 		let mut subscribers = self.subscribers.lock();
 		log::trace!(target: LOG_TARGET, "{}: substream opened for {peer:?}", self.protocol);
 
+		let num_subscribers = subscribers.len();
 		subscribers.retain(|subscriber| {
-			subscriber
-				.unbounded_send(InnerNotificationEvent::NotificationStreamOpened {
+			self.channel_config.send(
+				&subscriber.tx,
+				&mut subscriber.flow_control.lock(),
+				InnerNotificationEvent::NotificationStreamOpened {
 					peer,
 					direction,
 					handshake: handshake.clone(),
 					negotiated_fallback: negotiated_fallback.clone(),
 					sink: sink.clone(),
-				})
-				.is_ok()
+				},
+			)
 		});
+		metrics::register_subscribers_pruned(
+			&self.metrics,
+			&self.protocol,
+			num_subscribers - subscribers.len(),
+		);
 		self.num_peers += 1;
 
 		Ok(())
 	}
 
 	/// Substream was closed.
+	///
+	/// Notifications from `peer` held back by subscribers which paused their delivery are dropped,
+	/// so they're never delivered after the substream is closed.
 	pub fn report_substream_closed(&mut self, peer: PeerId) -> Result<(), ()> {
 		metrics::register_substream_closed(&self.metrics, &self.protocol);
+		self.register_substream_churn();
 
 		let mut subscribers = self.subscribers.lock();
 		log::trace!(target: LOG_TARGET, "{}: substream closed for {peer:?}", self.protocol);
 
+		let num_subscribers = subscribers.len();
+		let mut dropped = 0usize;
 		subscribers.retain(|subscriber| {
-			subscriber
-				.unbounded_send(InnerNotificationEvent::NotificationStreamClosed { peer })
-				.is_ok()
+			let mut flow_control = subscriber.flow_control.lock();
+			dropped += flow_control.drop_held(&peer);
+			self.channel_config.send(
+				&subscriber.tx,
+				&mut flow_control,
+				InnerNotificationEvent::NotificationStreamClosed { peer },
+			)
 		});
+		metrics::register_notification_dropped_paused(&self.metrics, &self.protocol, dropped);
+		metrics::register_subscribers_pruned(
+			&self.metrics,
+			&self.protocol,
+			num_subscribers - subscribers.len(),
+		);
 		self.num_peers -= 1;
+		if let Some(rate_limiter) = &mut self.rate_limiter {
+			rate_limiter.remove(&peer);
+		}
 
 		Ok(())
 	}
 
 	/// Notification was received from the substream.
+	///
+	/// Subscribers which paused the delivery of notifications hold them back until they resume,
+	/// dropping the notification if too many are already held. Returns an error and drops the
+	/// notification if `peer` exceeded the inbound rate limit of the protocol.
 	pub fn report_notification_received(
 		&mut self,
 		peer: PeerId,
@@ -579,17 +1941,54 @@ impl ProtocolHandle {
 	) -> Result<(), ()> {
 		metrics::register_notification_received(&self.metrics, &self.protocol, notification.len());
 
+		if let Some(rate_limiter) = &mut self.rate_limiter {
+			if !rate_limiter.allow(peer, notification.len(), Instant::now()) {
+				log::debug!(
+					target: LOG_TARGET,
+					"{}: {peer:?} exceeded the inbound rate limit, dropping notification",
+					self.protocol,
+				);
+				metrics::register_notification_dropped_rate_limited(&self.metrics, &self.protocol);
+				return Err(())
+			}
+		}
+
 		let mut subscribers = self.subscribers.lock();
 		log::trace!(target: LOG_TARGET, "{}: notification received from {peer:?}", self.protocol);
 
+		let num_subscribers = subscribers.len();
+		let mut dropped = 0usize;
 		subscribers.retain(|subscriber| {
-			subscriber
-				.unbounded_send(InnerNotificationEvent::NotificationReceived {
+			let mut flow_control = subscriber.flow_control.lock();
+			if flow_control.paused {
+				if flow_control.held.len() >= MAX_HELD_NOTIFICATIONS {
+					log::debug!(
+						target: LOG_TARGET,
+						"{}: delivery paused and queue full, dropping notification from {peer:?}",
+						self.protocol,
+					);
+					dropped += 1;
+				} else {
+					flow_control.held.push_back((peer, notification.clone()));
+				}
+				return !subscriber.tx.is_closed()
+			}
+
+			self.channel_config.send(
+				&subscriber.tx,
+				&mut flow_control,
+				InnerNotificationEvent::NotificationReceived {
 					peer,
 					notification: notification.clone(),
-				})
-				.is_ok()
+				},
+			)
 		});
+		metrics::register_notification_dropped_paused(&self.metrics, &self.protocol, dropped);
+		metrics::register_subscribers_pruned(
+			&self.metrics,
+			&self.protocol,
+			num_subscribers - subscribers.len(),
+		);
 
 		Ok(())
 	}
@@ -608,14 +2007,19 @@ impl ProtocolHandle {
 			self.protocol
 		);
 
+		let num_subscribers = subscribers.len();
 		subscribers.retain(|subscriber| {
-			subscriber
-				.unbounded_send(InnerNotificationEvent::NotificationSinkReplaced {
-					peer,
-					sink: sink.clone(),
-				})
-				.is_ok()
+			self.channel_config.send(
+				&subscriber.tx,
+				&mut subscriber.flow_control.lock(),
+				InnerNotificationEvent::NotificationSinkReplaced { peer, sink: sink.clone() },
+			)
 		});
+		metrics::register_subscribers_pruned(
+			&self.metrics,
+			&self.protocol,
+			num_subscribers - subscribers.len(),
+		);
 
 		Ok(())
 	}
@@ -631,16 +2035,40 @@ impl ProtocolHandle {
 /// Handle pair allows `Notifications` and the protocol to communicate with each other directly.
 pub fn notification_service(
 	protocol: ProtocolName,
+) -> (ProtocolHandlePair, Box<dyn NotificationService>) {
+	notification_service_with_config(protocol, NotificationChannelConfig::default())
+}
+
+/// Create new (protocol, notification) handle pair whose event channels use `channel_config`.
+///
+/// See [`notification_service()`].
+pub fn notification_service_with_config(
+	protocol: ProtocolName,
+	channel_config: NotificationChannelConfig,
 ) -> (ProtocolHandlePair, Box<dyn NotificationService>) {
 	let (cmd_tx, cmd_rx) = mpsc::channel(COMMAND_QUEUE_SIZE);
 
 	let (event_tx, event_rx) =
 		tracing_unbounded(metric_label_for_protocol(&protocol).leak(), 100_000);
-	let subscribers = Arc::new(Mutex::new(vec![event_tx]));
+	let flow_control = SharedFlowControl::default();
+	let validation_outcomes = validation_outcomes();
+	let subscribers = Arc::new(Mutex::new(vec![Subscriber {
+		tx: event_tx,
+		flow_control: flow_control.clone(),
+		validation_outcomes: validation_outcomes.clone(),
+	}]));
 
 	(
-		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), cmd_rx),
-		Box::new(NotificationHandle::new(protocol.clone(), cmd_tx, event_rx, subscribers)),
+		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), channel_config, cmd_rx),
+		Box::new(NotificationHandle::new(
+			protocol.clone(),
+			cmd_tx,
+			event_rx,
+			subscribers,
+			flow_control,
+			validation_outcomes,
+			channel_config,
+		)),
 	)
 }
 
@@ -17,11 +17,17 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use super::*;
-use crate::protocol::notifications::handler::{
-	NotificationsSinkMessage, ASYNC_NOTIFICATIONS_BUFFER_SIZE,
+use crate::{
+	protocol::notifications::handler::{
+		NotificationsSinkMessage, ASYNC_NOTIFICATIONS_BUFFER_SIZE, SYNC_NOTIFICATIONS_BUFFER_SIZE,
+	},
+	service::traits::{
+		tag_notification, untag_notification, CoalesceConfig, CompressionConfig,
+		NotificationServiceExt, PeerStats, TypedNotificationEvent, TypedNotificationService,
+	},
 };
 
-use std::future::Future;
+use std::{future::Future, time::Duration};
 
 #[tokio::test]
 async fn validate_and_accept_substream() {
@@ -842,3 +848,1861 @@ async fn set_handshake() {
 
 	assert!(notif.try_set_handshake(vec![1, 3, 3, 7]).is_err());
 }
+
+#[tokio::test]
+async fn fallback_encoder_applied_to_fallback_peers_only() {
+	let (proto, mut notif) = notification_service("/proto/2".into());
+	let (main_sink, _, mut main_sync_rx) = NotificationsSink::new(PeerId::random());
+	let (fallback_sink, _, mut fallback_sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let main_peer = PeerId::random();
+	let fallback_peer = PeerId::random();
+
+	notif.set_fallback_encoder(Arc::new(|notification, fallback| {
+		assert_eq!(fallback, &ProtocolName::from("/proto/1"));
+		let mut encoded = vec![0xff];
+		encoded.extend_from_slice(notification);
+		encoded
+	}));
+
+	handle
+		.report_substream_opened(main_peer, Direction::Inbound, vec![], None, main_sink)
+		.unwrap();
+	handle
+		.report_substream_opened(
+			fallback_peer,
+			Direction::Inbound,
+			vec![],
+			Some("/proto/1".into()),
+			fallback_sink,
+		)
+		.unwrap();
+
+	for _ in 0..2 {
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	notif.send_sync_notification(&main_peer.into(), vec![1, 3, 3, 7]);
+	notif.send_sync_notification(&fallback_peer.into(), vec![1, 3, 3, 7]);
+
+	assert_eq!(
+		main_sync_rx.next().await,
+		Some(NotificationsSinkMessage::Notification { message: vec![1, 3, 3, 7] })
+	);
+	assert_eq!(
+		fallback_sync_rx.next().await,
+		Some(NotificationsSinkMessage::Notification { message: vec![0xff, 1, 3, 3, 7] })
+	);
+}
+
+#[tokio::test]
+async fn shared_clone_sees_peer_opened_by_other_clone() {
+	let (proto, mut notif1) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let notif2 = notif1.clone_shared().unwrap();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+
+	// only the first clone processes the event
+	let Some(NotificationEvent::NotificationStreamOpened { peer, .. }) = notif1.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer_id, peer.into());
+
+	assert!(notif1.message_sink(&peer_id.into()).is_some());
+	assert!(notif2.message_sink(&peer_id.into()).is_some());
+
+	// a copy-based clone doesn't share the peers
+	let mut notif3 = notif1.clone().unwrap();
+	handle.report_substream_closed(peer_id).unwrap();
+
+	let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif1.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert!(notif1.message_sink(&peer_id.into()).is_none());
+	assert!(notif2.message_sink(&peer_id.into()).is_none());
+	assert!(notif3.message_sink(&peer_id.into()).is_some());
+
+	let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif3.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert!(notif3.message_sink(&peer_id.into()).is_none());
+}
+
+#[tokio::test]
+async fn shared_clones_keep_metadata_and_count_stats_once() {
+	let (proto, mut notif1) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let mut notif2 = notif1.clone_shared().unwrap();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif1.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notif1.set_peer_metadata(&peer_id.into(), Arc::new(1337u32)), Ok(()));
+
+	// the lagging clone processing the same event doesn't reset the peer
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif2.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notif2.peer_metadata_as::<u32>(&peer_id.into()).as_deref(), Some(&1337));
+
+	// the notification is counted once, although both clones receive it
+	handle.report_notification_received(peer_id, vec![1, 3, 3, 8]).unwrap();
+	for notif in [&mut notif1, &mut notif2] {
+		let Some(NotificationEvent::NotificationReceived { .. }) = notif.next_event().await else {
+			panic!("invalid event received");
+		};
+	}
+	assert_eq!(
+		notif2.peer_stats(&peer_id.into()),
+		Some(PeerStats { sent_msgs: 0, sent_bytes: 0, recv_msgs: 1, recv_bytes: 4 })
+	);
+
+	// the remaining clone takes over counting once the registering clone is dropped
+	drop(notif1);
+	handle.report_notification_received(peer_id, vec![1, 3, 3, 9]).unwrap();
+	let Some(NotificationEvent::NotificationReceived { .. }) = notif2.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notif2.peer_stats(&peer_id.into()).unwrap().recv_msgs, 2);
+}
+
+#[tokio::test]
+async fn send_and_await_ack_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, mut async_rx, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// mock peer which echoes every received notification back as an ack
+	tokio::spawn(async move {
+		while let Some(NotificationsSinkMessage::Notification { message }) = async_rx.next().await {
+			let (id, payload) = untag_notification(&message).unwrap();
+			handle
+				.report_notification_received(peer_id, tag_notification(id, payload))
+				.unwrap();
+		}
+	});
+
+	let ack = notif
+		.send_and_await_ack(&peer_id.into(), vec![1, 3, 3, 8], 1337, Duration::from_secs(5))
+		.await
+		.unwrap();
+	assert_eq!(ack, vec![1, 3, 3, 8]);
+}
+
+#[tokio::test]
+async fn send_and_await_ack_times_out() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _async_rx, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// ack with a different correlation id is ignored
+	handle
+		.report_notification_received(peer_id, tag_notification(1338, &[1]))
+		.unwrap();
+
+	assert!(matches!(
+		notif
+			.send_and_await_ack(&peer_id.into(), vec![1, 3, 3, 8], 1337, Duration::from_millis(100))
+			.await,
+		Err(error::Error::Timeout)
+	));
+}
+
+#[tokio::test]
+async fn negotiated_protocol_works() {
+	let (proto, mut notif) = notification_service("/proto/2".into());
+	let (sink1, _, _) = NotificationsSink::new(PeerId::random());
+	let (sink2, _, _) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer1 = PeerId::random();
+	let peer2 = PeerId::random();
+
+	// unknown peer
+	assert_eq!(notif.negotiated_protocol(&peer1.into()), None);
+
+	handle
+		.report_substream_opened(peer1, Direction::Inbound, vec![1, 3, 3, 7], None, sink1)
+		.unwrap();
+	handle
+		.report_substream_opened(
+			peer2,
+			Direction::Inbound,
+			vec![1, 3, 3, 7],
+			Some("/proto/1".into()),
+			sink2,
+		)
+		.unwrap();
+
+	for _ in 0..2 {
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	assert_eq!(notif.negotiated_protocol(&peer1.into()), Some("/proto/2".into()));
+	assert_eq!(notif.negotiated_protocol(&peer2.into()), Some("/proto/1".into()));
+}
+
+#[tokio::test]
+async fn coalesced_sync_notifications_are_framed() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// frame the batch by prefixing each notification with its length
+	notif.set_coalescing(
+		CoalesceConfig { window: Duration::from_secs(60), max_batch: 3 },
+		Arc::new(|batch: Vec<Vec<u8>>| {
+			batch
+				.into_iter()
+				.flat_map(|notification| {
+					std::iter::once(notification.len() as u8).chain(notification)
+				})
+				.collect()
+		}),
+	);
+
+	notif.send_sync_notification(&peer_id.into(), vec![1]);
+	notif.send_sync_notification(&peer_id.into(), vec![2, 2]);
+	assert!(sync_rx.try_next().is_err());
+
+	notif.send_sync_notification(&peer_id.into(), vec![3, 3, 3]);
+	assert_eq!(
+		sync_rx.next().await,
+		Some(NotificationsSinkMessage::Notification { message: vec![1, 1, 2, 2, 2, 3, 3, 3, 3] })
+	);
+	assert!(sync_rx.try_next().is_err());
+}
+
+#[tokio::test]
+async fn drain_received_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink1, _, _) = NotificationsSink::new(PeerId::random());
+	let (sink2, _, _) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer1 = PeerId::random();
+	let peer2 = PeerId::random();
+
+	handle
+		.report_substream_opened(peer1, Direction::Inbound, vec![1, 3, 3, 7], None, sink1)
+		.unwrap();
+	handle
+		.report_substream_opened(peer2, Direction::Inbound, vec![1, 3, 3, 7], None, sink2)
+		.unwrap();
+	for _ in 0..2 {
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	// nothing received yet
+	assert!(notif.drain_received(&peer1.into()).is_empty());
+
+	handle.report_notification_received(peer1, vec![1]).unwrap();
+	handle.report_notification_received(peer2, vec![2]).unwrap();
+	handle.report_notification_received(peer1, vec![3]).unwrap();
+	handle.report_notification_received(peer1, vec![4]).unwrap();
+
+	assert_eq!(notif.drain_received(&peer1.into()), vec![vec![1], vec![3], vec![4]]);
+	assert!(notif.drain_received(&peer1.into()).is_empty());
+
+	// notification of the other peer is left queued
+	let Some(NotificationEvent::NotificationReceived { peer, notification }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer2.into());
+	assert_eq!(notification, vec![2]);
+}
+
+#[tokio::test]
+async fn supports_backpressure_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	// unknown peer
+	assert_eq!(notif.supports_backpressure(&peer_id.into()), None);
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	assert_eq!(notif.supports_backpressure(&peer_id.into()), Some(true));
+}
+
+#[tokio::test]
+async fn peer_stats_work() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, mut async_rx, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	// unknown peer
+	assert_eq!(notif.peer_stats(&peer_id.into()), None);
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notif.peer_stats(&peer_id.into()), Some(PeerStats::default()));
+
+	notif.send_sync_notification(&peer_id.into(), vec![1, 3, 3, 7]);
+	assert!(sync_rx.next().await.is_some());
+	notif.send_async_notification(&peer_id.into(), vec![1, 3]).await.unwrap();
+	assert!(async_rx.next().await.is_some());
+
+	handle.report_notification_received(peer_id, vec![1, 3, 3]).unwrap();
+	let Some(NotificationEvent::NotificationReceived { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	assert_eq!(
+		notif.peer_stats(&peer_id.into()),
+		Some(PeerStats { sent_msgs: 2, sent_bytes: 6, recv_msgs: 1, recv_bytes: 3 })
+	);
+
+	// stats are reset when the substream is reopened
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+	handle.report_substream_closed(peer_id).unwrap();
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	for _ in 0..2 {
+		notif.next_event().await.unwrap();
+	}
+	assert_eq!(notif.peer_stats(&peer_id.into()), Some(PeerStats::default()));
+}
+
+#[tokio::test]
+async fn receive_interceptor_drops_and_rewrites_notifications() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// drop empty notifications and reverse all others
+	notif.set_receive_interceptor(Arc::new(|_peer, mut notification: Vec<u8>| {
+		if notification.is_empty() {
+			return None
+		}
+		notification.reverse();
+		Some(notification)
+	}));
+
+	handle.report_notification_received(peer_id, vec![]).unwrap();
+	handle.report_notification_received(peer_id, vec![1, 3, 3, 8]).unwrap();
+
+	let Some(NotificationEvent::NotificationReceived { peer, notification }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer_id.into());
+	assert_eq!(notification, vec![8, 3, 3, 1]);
+}
+
+#[tokio::test]
+async fn peers_by_direction_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	let inbound = [PeerId::random(), PeerId::random()];
+	let outbound = PeerId::random();
+
+	// no peers connected
+	assert!(notif.peers_by_direction(Direction::Inbound).is_empty());
+
+	for (peer, direction) in [
+		(inbound[0], Direction::Inbound),
+		(outbound, Direction::Outbound),
+		(inbound[1], Direction::Inbound),
+	] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, direction, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	let mut inbound_peers = notif.peers_by_direction(Direction::Inbound);
+	inbound_peers.sort();
+	let mut expected: Vec<sc_network_types::PeerId> =
+		inbound.iter().map(|peer| (*peer).into()).collect();
+	expected.sort();
+	assert_eq!(inbound_peers, expected);
+	assert_eq!(notif.peers_by_direction(Direction::Outbound), vec![outbound.into()]);
+
+	// closed substreams are not included
+	handle.report_substream_closed(outbound).unwrap();
+	let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert!(notif.peers_by_direction(Direction::Outbound).is_empty());
+}
+
+#[tokio::test]
+async fn peer_map_is_shrunk_after_churn() {
+	let protocol: ProtocolName = "/proto/1".into();
+	let (cmd_tx, cmd_rx) = mpsc::channel(COMMAND_QUEUE_SIZE);
+	let (event_tx, event_rx) = tracing_unbounded("mpsc-test-peers", 100_000);
+	let flow_control = SharedFlowControl::default();
+	let validation_outcomes = validation_outcomes();
+	let subscribers = Arc::new(Mutex::new(vec![Subscriber {
+		tx: event_tx,
+		flow_control: flow_control.clone(),
+		validation_outcomes: validation_outcomes.clone(),
+	}]));
+	let (mut handle, _stream) = ProtocolHandlePair::new(
+		protocol.clone(),
+		subscribers.clone(),
+		NotificationChannelConfig::default(),
+		cmd_rx,
+	)
+	.split();
+	let mut notif = NotificationHandle::new(
+		protocol,
+		cmd_tx,
+		event_rx,
+		subscribers,
+		flow_control,
+		validation_outcomes,
+		NotificationChannelConfig::default(),
+	);
+
+	let peers = (0..200).map(|_| PeerId::random()).collect::<Vec<_>>();
+	for peer in &peers {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(*peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+	let capacity = notif.peers.read().capacity();
+	assert!(capacity >= 200);
+
+	for peer in &peers[10..] {
+		handle.report_substream_closed(*peer).unwrap();
+		let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	assert_eq!(notif.peers.read().len(), 10);
+	assert!(notif.peers.read().capacity() < capacity / 2);
+}
+
+#[tokio::test]
+async fn total_queued_bytes_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink1, _async_rx1, _sync_rx1) = NotificationsSink::new(PeerId::random());
+	let (sink2, _async_rx2, _sync_rx2) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer1 = PeerId::random();
+	let peer2 = PeerId::random();
+
+	for (peer, sink) in [(peer1, sink1), (peer2, sink2)] {
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+	assert_eq!(notif.total_queued_bytes(), Some(0));
+
+	notif.send_sync_notification(&peer1.into(), vec![1, 3, 3, 7]);
+	notif
+		.send_async_notification(&peer2.into(), vec![1, 3, 3, 8, 9, 10])
+		.await
+		.unwrap();
+
+	assert_eq!(notif.total_queued_bytes(), Some(10));
+}
+
+#[tokio::test]
+async fn sink_backpressure_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _async_rx, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer = PeerId::random();
+
+	assert_eq!(notif.sink_backpressure(&peer.into()), None);
+
+	handle
+		.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	let state = notif.sink_backpressure(&peer.into()).unwrap();
+	assert_eq!(state.queued_notifications, 0);
+	assert!(!state.sync_overflowed);
+	let capacity = state.sync_capacity;
+
+	notif.send_sync_notification(&peer.into(), vec![1, 3, 3, 7]);
+	notif.send_async_notification(&peer.into(), vec![1, 3, 3, 8, 9]).await.unwrap();
+
+	assert_eq!(
+		notif.sink_backpressure(&peer.into()),
+		Some(SinkState {
+			queued_notifications: 2,
+			queued_bytes: 9,
+			sync_capacity: capacity - 2,
+			sync_overflowed: false,
+		}),
+	);
+}
+
+#[tokio::test]
+async fn compression_round_trip() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	// the last byte of the handshake advertises support for compression
+	notif.set_compression(
+		CompressionConfig { threshold: 64, max_size: 1024 * 1024 },
+		Arc::new(|handshake: &[u8]| handshake.last() == Some(&1)),
+	);
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7, 1], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// compressible notification above the threshold is compressed
+	let large = vec![1u8; 1024];
+	notif.send_sync_notification(&peer_id.into(), large.clone());
+	let Some(NotificationsSinkMessage::Notification { message: compressed }) = sync_rx.next().await
+	else {
+		panic!("invalid message received");
+	};
+	assert!(compressed.len() < large.len());
+
+	// notification below the threshold is sent as-is
+	notif.send_sync_notification(&peer_id.into(), vec![1, 3, 3, 7]);
+	assert_eq!(
+		sync_rx.next().await,
+		Some(NotificationsSinkMessage::Notification { message: vec![1, 3, 3, 7] })
+	);
+
+	// both are received as they were sent
+	handle.report_notification_received(peer_id, compressed).unwrap();
+	handle.report_notification_received(peer_id, vec![1, 3, 3, 7]).unwrap();
+	for expected in [large, vec![1, 3, 3, 7]] {
+		let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+			notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		assert_eq!(notification, expected);
+	}
+}
+
+#[tokio::test]
+async fn compression_is_not_used_for_peers_without_support() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	notif.set_compression(
+		CompressionConfig { threshold: 64, max_size: 1024 * 1024 },
+		Arc::new(|handshake: &[u8]| handshake.last() == Some(&1)),
+	);
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	notif.send_sync_notification(&peer_id.into(), vec![1u8; 1024]);
+	assert_eq!(
+		sync_rx.next().await,
+		Some(NotificationsSinkMessage::Notification { message: vec![1u8; 1024] })
+	);
+}
+
+#[tokio::test]
+async fn handshake_accepted_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (handle, _stream) = proto.split();
+	let accepted = PeerId::random();
+	let rejected = PeerId::random();
+
+	// unknown peer
+	assert_eq!(notif.handshake_accepted(&accepted.into()), None);
+
+	for (peer, accept) in [(accepted, true), (rejected, false)] {
+		let result = || if accept { ValidationResult::Accept } else { ValidationResult::Reject };
+		let ValidationCallResult::WaitForValidation(result_rx) =
+			handle.report_incoming_substream(peer, vec![1, 3, 3, 7]).unwrap()
+		else {
+			panic!("peerset not enabled");
+		};
+		let Some(NotificationEvent::ValidateInboundSubstream { result_tx, .. }) =
+			notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		let _ = result_tx.send(result());
+		assert_eq!(result_rx.await.unwrap(), result());
+	}
+
+	assert_eq!(notif.handshake_accepted(&accepted.into()), Some(true));
+	assert_eq!(notif.handshake_accepted(&rejected.into()), Some(false));
+}
+
+#[tokio::test]
+async fn handshake_accepted_is_recorded_per_clone() {
+	let (proto, mut notif1) = notification_service("/proto/1".into());
+	let (handle, _stream) = proto.split();
+	let mut notif2 = notif1.clone().unwrap();
+	let peer_id = PeerId::random();
+
+	let ValidationCallResult::WaitForValidation(result_rx) =
+		handle.report_incoming_substream(peer_id, vec![1, 3, 3, 7]).unwrap()
+	else {
+		panic!("peerset not enabled");
+	};
+
+	for (notif, result) in
+		[(&mut notif1, ValidationResult::Reject), (&mut notif2, ValidationResult::Accept)]
+	{
+		let Some(NotificationEvent::ValidateInboundSubstream { result_tx, .. }) =
+			notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		let _ = result_tx.send(result);
+	}
+	assert_eq!(result_rx.await.unwrap(), ValidationResult::Reject);
+
+	// each handle remembers its own outcome
+	assert_eq!(notif1.handshake_accepted(&peer_id.into()), Some(false));
+	assert_eq!(notif2.handshake_accepted(&peer_id.into()), Some(true));
+}
+
+#[tokio::test]
+async fn close_idle_closes_only_idle_peers() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, mut stream) = proto.split();
+	let idle = PeerId::random();
+	let active = PeerId::random();
+
+	for peer in [idle, active] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	futures_timer::Delay::new(Duration::from_millis(200)).await;
+
+	// a notification from `active` marks it as seen
+	handle.report_notification_received(active, vec![1, 3, 3, 8]).unwrap();
+	let Some(NotificationEvent::NotificationReceived { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	assert_eq!(notif.close_idle(Duration::from_millis(100)).await, 1);
+	match stream.next().await {
+		Some(NotificationCommand::CloseSubstream(peer)) => assert_eq!(peer, idle),
+		_ => panic!("invalid command received"),
+	}
+	assert!(futures::poll!(stream.next()).is_pending());
+}
+
+/// Checksum of `notification` used as its authentication tag in tests.
+fn test_auth_tag(notification: &[u8]) -> Vec<u8> {
+	vec![notification.iter().fold(0u8, |tag, byte| tag ^ byte), 0xff]
+}
+
+/// Peers advertise support for authentication tags with a trailing `2` in their handshake.
+fn test_supports_auth() -> Arc<dyn Fn(&[u8]) -> bool + Send + Sync> {
+	Arc::new(|handshake: &[u8]| handshake.last() == Some(&2))
+}
+
+#[tokio::test]
+async fn auth_tag_round_trip() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	notif.set_auth_signer(Arc::new(test_auth_tag), test_supports_auth()).unwrap();
+	notif
+		.set_auth_verifier(
+			Arc::new(|notification: &[u8], tag: &[u8]| test_auth_tag(notification) == tag),
+			test_supports_auth(),
+			AuthMode::Optional,
+		)
+		.unwrap();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7, 2], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// the tag and its length are appended to the sent notification
+	notif.send_sync_notification(&peer_id.into(), vec![1, 3, 3, 7]);
+	let Some(NotificationsSinkMessage::Notification { message }) = sync_rx.next().await else {
+		panic!("invalid message received");
+	};
+	assert_eq!(message, vec![1, 3, 3, 7, 6, 0xff, 2, 0, 0, 0]);
+
+	// and stripped from the received notification
+	handle.report_notification_received(peer_id, message).unwrap();
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![1, 3, 3, 7]);
+}
+
+#[tokio::test]
+async fn unauthenticated_notifications_are_dropped() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	notif
+		.set_auth_verifier(
+			Arc::new(|notification: &[u8], tag: &[u8]| test_auth_tag(notification) == tag),
+			test_supports_auth(),
+			AuthMode::Optional,
+		)
+		.unwrap();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7, 2], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// tampered payload
+	handle
+		.report_notification_received(peer_id, vec![1, 3, 3, 8, 6, 0xff, 2, 0, 0, 0])
+		.unwrap();
+	// missing tag
+	handle.report_notification_received(peer_id, vec![1, 3]).unwrap();
+	// authentic notification
+	handle
+		.report_notification_received(peer_id, vec![1, 3, 3, 7, 6, 0xff, 2, 0, 0, 0])
+		.unwrap();
+
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![1, 3, 3, 7]);
+	assert_eq!(notif.peer_stats(&peer_id.into()).unwrap().recv_msgs, 3);
+}
+
+#[tokio::test]
+async fn auth_tags_are_not_used_with_peers_not_supporting_them() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	notif.set_auth_signer(Arc::new(test_auth_tag), test_supports_auth()).unwrap();
+	notif
+		.set_auth_verifier(
+			Arc::new(|notification: &[u8], tag: &[u8]| test_auth_tag(notification) == tag),
+			test_supports_auth(),
+			AuthMode::Optional,
+		)
+		.unwrap();
+
+	// the handshake doesn't advertise support for authentication tags
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// sent notifications are not signed
+	notif.send_sync_notification(&peer_id.into(), vec![1, 3, 3, 7]);
+	let Some(NotificationsSinkMessage::Notification { message }) = sync_rx.next().await else {
+		panic!("invalid message received");
+	};
+	assert_eq!(message, vec![1, 3, 3, 7]);
+
+	// and received notifications are not verified
+	handle.report_notification_received(peer_id, vec![1, 3, 3, 8]).unwrap();
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![1, 3, 3, 8]);
+}
+
+#[tokio::test]
+async fn required_auth_rejects_inbound_substreams_of_peers_not_signing() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (handle, _stream) = proto.split();
+	let unsigned = PeerId::random();
+	let signed = PeerId::random();
+
+	notif
+		.set_auth_verifier(
+			Arc::new(|notification: &[u8], tag: &[u8]| test_auth_tag(notification) == tag),
+			test_supports_auth(),
+			AuthMode::Required,
+		)
+		.unwrap();
+
+	let ValidationCallResult::WaitForValidation(unsigned_rx) =
+		handle.report_incoming_substream(unsigned, vec![1, 3, 3, 7]).unwrap()
+	else {
+		panic!("peerset not enabled");
+	};
+	let ValidationCallResult::WaitForValidation(signed_rx) =
+		handle.report_incoming_substream(signed, vec![1, 3, 3, 7, 2]).unwrap()
+	else {
+		panic!("peerset not enabled");
+	};
+
+	// only the substream of the peer advertising signing its notifications is validated
+	let Some(NotificationEvent::ValidateInboundSubstream { peer, result_tx, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, signed.into());
+	let _ = result_tx.send(ValidationResult::Accept);
+
+	assert_eq!(unsigned_rx.await.unwrap(), ValidationResult::Reject);
+	assert_eq!(signed_rx.await.unwrap(), ValidationResult::Accept);
+	assert_eq!(notif.handshake_accepted(&unsigned.into()), Some(false));
+}
+
+#[tokio::test]
+async fn required_auth_closes_outbound_substreams_to_peers_not_signing() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, mut stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	notif
+		.set_auth_verifier(
+			Arc::new(|notification: &[u8], tag: &[u8]| test_auth_tag(notification) == tag),
+			test_supports_auth(),
+			AuthMode::Required,
+		)
+		.unwrap();
+
+	// the handshake doesn't advertise signing notifications
+	handle
+		.report_substream_opened(peer_id, Direction::Outbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	match stream.next().await {
+		Some(NotificationCommand::CloseSubstream(peer)) => assert_eq!(peer, peer_id),
+		_ => panic!("invalid command received"),
+	}
+
+	// until the substream is closed, notifications are verified regardless of the handshake
+	handle.report_notification_received(peer_id, vec![1, 3, 3, 8]).unwrap();
+	handle
+		.report_notification_received(peer_id, vec![1, 3, 3, 7, 6, 0xff, 2, 0, 0, 0])
+		.unwrap();
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![1, 3, 3, 7]);
+}
+
+fn dropped_paused(registry: &prometheus_endpoint::Registry) -> f64 {
+	registry
+		.gather()
+		.iter()
+		.find(|family| {
+			family.get_name() == "substrate_sub_libp2p_notifications_dropped_paused_total"
+		})
+		.map_or(0.0, |family| family.get_metric()[0].get_counter().get_value())
+}
+
+#[tokio::test]
+async fn received_notifications_are_held_while_paused() {
+	let registry = prometheus_endpoint::Registry::new();
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	handle.set_metrics(NotificationMetrics::new(Some(&registry)));
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	notif.request_pause();
+	handle.report_notification_received(peer_id, vec![1]).unwrap();
+	handle.report_notification_received(peer_id, vec![2]).unwrap();
+
+	// nothing is delivered while paused
+	assert!(futures::poll!(notif.next_event()).is_pending());
+
+	// until the queue is full
+	for _ in 2..MAX_HELD_NOTIFICATIONS {
+		handle.report_notification_received(peer_id, vec![3]).unwrap();
+	}
+	handle.report_notification_received(peer_id, vec![4]).unwrap();
+	assert_eq!(dropped_paused(&registry), 1.0);
+
+	// held notifications flow in order after resuming
+	notif.request_resume();
+	for expected in [vec![1], vec![2], vec![3]] {
+		let Some(NotificationEvent::NotificationReceived { peer, notification }) =
+			notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		assert_eq!(peer, peer_id.into());
+		assert_eq!(notification, expected);
+	}
+}
+
+#[tokio::test]
+async fn pausing_a_clone_does_not_pause_other_handles() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let mut cloned = notif.clone().unwrap();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	for notif in [&mut notif, &mut cloned] {
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	cloned.request_pause();
+	handle.report_notification_received(peer_id, vec![1]).unwrap();
+
+	// the paused clone holds the notification while the original handle receives it
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![1]);
+	assert!(futures::poll!(cloned.next_event()).is_pending());
+
+	cloned.request_resume();
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		cloned.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![1]);
+}
+
+#[tokio::test]
+async fn held_notifications_are_dropped_on_close() {
+	let registry = prometheus_endpoint::Registry::new();
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	handle.set_metrics(NotificationMetrics::new(Some(&registry)));
+	let (peer1, peer2) = (PeerId::random(), PeerId::random());
+
+	for peer in [peer1, peer2] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	notif.request_pause();
+	handle.report_notification_received(peer1, vec![1]).unwrap();
+	handle.report_notification_received(peer2, vec![2]).unwrap();
+	handle.report_notification_received(peer1, vec![3]).unwrap();
+	handle.report_substream_closed(peer1).unwrap();
+	assert_eq!(dropped_paused(&registry), 2.0);
+
+	let Some(NotificationEvent::NotificationStreamClosed { peer }) = notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer1.into());
+
+	// only the notification of the connected peer is delivered after resuming
+	notif.request_resume();
+	let Some(NotificationEvent::NotificationReceived { peer, notification }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!((peer, notification), (peer2.into(), vec![2]));
+	assert!(futures::poll!(notif.next_event()).is_pending());
+}
+
+#[tokio::test]
+async fn substream_churn_is_registered() {
+	let registry = prometheus_endpoint::Registry::new();
+	let (proto, _notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	handle.set_metrics(NotificationMetrics::new(Some(&registry)));
+
+	let churn = || {
+		registry
+			.gather()
+			.iter()
+			.find(|family| {
+				family.get_name() == "substrate_sub_libp2p_notifications_substream_churn"
+			})
+			.map_or(0.0, |family| family.get_metric()[0].get_gauge().get_value())
+	};
+	assert_eq!(churn(), 0.0);
+
+	// flapping peer
+	let peer_id = PeerId::random();
+	for _ in 0..5 {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		handle.report_substream_closed(peer_id).unwrap();
+	}
+	assert_eq!(churn(), 10.0);
+}
+
+#[test]
+fn substream_churn_is_measured_over_window() {
+	let mut churn = metrics::SubstreamChurn::default();
+	let now = Instant::now();
+
+	assert_eq!(churn.record(now), 1);
+	assert_eq!(churn.record(now + Duration::from_secs(30)), 2);
+	// the first event falls out of the window
+	assert_eq!(churn.record(now + Duration::from_secs(60)), 2);
+	assert_eq!(churn.record(now + Duration::from_secs(200)), 1);
+	// the churn decays without new events
+	assert_eq!(churn.count(now + Duration::from_secs(230)), 1);
+	assert_eq!(churn.count(now + Duration::from_secs(260)), 0);
+}
+
+#[derive(Debug, PartialEq, codec::Encode, codec::Decode)]
+struct TestMessage {
+	id: u32,
+	payload: Vec<u8>,
+}
+
+#[tokio::test]
+async fn typed_notification_round_trip() {
+	let (proto, notif) = notification_service("/proto/1".into());
+	let (sink, _, mut sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let mut typed = TypedNotificationService::<TestMessage>::new(notif);
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(TypedNotificationEvent::Other(NotificationEvent::NotificationStreamOpened { .. })) =
+		typed.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+
+	let message = TestMessage { id: 1337, payload: vec![1, 2, 3] };
+	typed.send_sync_notification(&peer_id.into(), &message);
+	let Some(NotificationsSinkMessage::Notification { message: encoded }) = sync_rx.next().await
+	else {
+		panic!("invalid message received");
+	};
+	assert_eq!(encoded, codec::Encode::encode(&message));
+
+	handle.report_notification_received(peer_id, encoded).unwrap();
+	let Some(TypedNotificationEvent::NotificationReceived { peer, notification }) =
+		typed.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer_id.into());
+	assert_eq!(notification, message);
+}
+
+#[tokio::test]
+async fn typed_notification_decode_error() {
+	let (proto, notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let mut typed = TypedNotificationService::<TestMessage>::new(notif);
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(TypedNotificationEvent::Other(NotificationEvent::NotificationStreamOpened { .. })) =
+		typed.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+
+	handle.report_notification_received(peer_id, vec![1, 3]).unwrap();
+	let Some(TypedNotificationEvent::DecodeError { peer, notification, .. }) =
+		typed.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer_id.into());
+	assert_eq!(notification, vec![1, 3]);
+}
+
+#[tokio::test]
+async fn open_substream_sends_command() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (_handle, mut stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	// the result of the request is relayed back from the protocol controller
+	for expected in [Ok(()), Err(())] {
+		let (result, ()) = futures::join!(notif.open_substream(peer_id.into()), async {
+			match stream.next().await {
+				Some(NotificationCommand::OpenSubstream(peer, result_tx)) => {
+					assert_eq!(peer, peer_id);
+					result_tx.send(expected).unwrap();
+				},
+				_ => panic!("invalid command received"),
+			}
+		});
+		assert_eq!(result, expected);
+	}
+
+	// the command can't be sent once `Notifications` has shut down
+	drop(stream);
+	assert_eq!(notif.open_substream(peer_id.into()).await, Err(()));
+}
+
+#[tokio::test]
+async fn open_substream_for_open_peer_does_not_bump_num_peers() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, mut stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Outbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert_eq!(handle.num_peers(), 1);
+
+	// the protocol controller accepts requests for known peers without opening a substream
+	tokio::spawn(async move {
+		while let Some(command) = stream.next().await {
+			if let NotificationCommand::OpenSubstream(_, result_tx) = command {
+				let _ = result_tx.send(Ok(()));
+			}
+		}
+	});
+
+	for _ in 0..2 {
+		assert_eq!(notif.open_substream(peer_id.into()).await, Ok(()));
+	}
+	assert_eq!(handle.num_peers(), 1);
+	assert!(futures::poll!(notif.next_event()).is_pending());
+}
+
+#[tokio::test]
+async fn open_substream_with_fallbacks_sends_preference() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (_handle, mut stream) = proto.split();
+	let peer_id = PeerId::random();
+	let fallbacks: Vec<ProtocolName> = vec!["/proto/0.2".into(), "/proto/0.1".into()];
+
+	let (result, ()) = futures::join!(
+		notif.open_substream_with_fallbacks(peer_id.into(), fallbacks.clone()),
+		async {
+			match stream.next().await {
+				Some(NotificationCommand::OpenSubstreamWithFallbacks {
+					peer,
+					fallbacks: sent,
+					result_tx,
+				}) => {
+					assert_eq!(peer, peer_id);
+					assert_eq!(sent, fallbacks);
+					result_tx.send(Ok(())).unwrap();
+				},
+				_ => panic!("invalid command received"),
+			}
+		}
+	);
+	assert_eq!(result, Ok(()));
+}
+
+#[tokio::test]
+async fn close_substream_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, mut stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	// unknown peer
+	assert_eq!(notif.close_substream(peer_id.into()).await, Err(()));
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	assert_eq!(notif.close_substream(peer_id.into()).await, Ok(()));
+	match stream.next().await {
+		Some(NotificationCommand::CloseSubstream(peer)) => assert_eq!(peer, peer_id),
+		_ => panic!("invalid command received"),
+	}
+
+	// the peer is removed once `Notifications` reports the substream closed
+	assert!(notif.message_sink(&peer_id.into()).is_some());
+	handle.report_substream_closed(peer_id).unwrap();
+	let Some(NotificationEvent::NotificationStreamClosed { peer }) = notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer_id.into());
+	assert!(notif.message_sink(&peer_id.into()).is_none());
+	assert_eq!(notif.close_substream(peer_id.into()).await, Err(()));
+}
+
+#[tokio::test]
+async fn broadcast_sync_notification_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+
+	// no peers
+	notif.broadcast_sync_notification(vec![1, 3, 3, 7]);
+
+	let mut sync_rxs = Vec::new();
+	for _ in 0..3 {
+		let peer = PeerId::random();
+		let (sink, _, sync_rx) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		sync_rxs.push((peer, sync_rx));
+	}
+
+	notif.broadcast_sync_notification(vec![1, 3, 3, 8]);
+	for (peer, mut sync_rx) in sync_rxs {
+		assert_eq!(
+			sync_rx.next().await,
+			Some(NotificationsSinkMessage::Notification { message: vec![1, 3, 3, 8] })
+		);
+		assert_eq!(notif.peer_stats(&peer.into()).unwrap().sent_msgs, 1);
+	}
+}
+
+#[tokio::test]
+async fn peer_metadata_is_cleared_on_close() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	// unknown peer
+	assert_eq!(notif.set_peer_metadata(&peer_id.into(), Arc::new(1337u32)), Err(()));
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert!(notif.peer_metadata(&peer_id.into()).is_none());
+
+	assert_eq!(notif.set_peer_metadata(&peer_id.into(), Arc::new(1337u32)), Ok(()));
+	assert_eq!(notif.peer_metadata_as::<u32>(&peer_id.into()).as_deref(), Some(&1337));
+	assert!(notif.peer_metadata_as::<u64>(&peer_id.into()).is_none());
+
+	handle.report_substream_closed(peer_id).unwrap();
+	let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert!(notif.peer_metadata(&peer_id.into()).is_none());
+}
+
+#[tokio::test]
+async fn wait_for_skips_non_matching_events() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	let (peer1, peer2) = (PeerId::random(), PeerId::random());
+
+	for peer in [peer1, peer2] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		handle.report_notification_received(peer, vec![1, 3, 3, 8]).unwrap();
+	}
+
+	let event = notif
+		.wait_for(|event| {
+			matches!(
+				event,
+				NotificationEvent::NotificationStreamOpened { peer, .. } if *peer == peer2.into()
+			)
+		})
+		.await;
+	let Some(NotificationEvent::NotificationStreamOpened { peer, handshake, .. }) = event else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer2.into());
+	assert_eq!(handshake, vec![1, 3, 3, 7]);
+
+	// the skipped events were consumed but `peer1` is still tracked
+	assert!(notif.is_connected(&peer1.into()));
+	let Some(NotificationEvent::NotificationReceived { peer, .. }) = notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer2.into());
+}
+
+#[tokio::test]
+async fn connected_peers_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	let (peer1, peer2) = (PeerId::random(), PeerId::random());
+
+	assert!(notif.connected_peers().is_empty());
+	assert!(!notif.is_connected(&peer1.into()));
+
+	for peer in [peer1, peer2] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	let mut connected = notif.connected_peers();
+	connected.sort();
+	let mut expected: Vec<sc_network_types::PeerId> = vec![peer1.into(), peer2.into()];
+	expected.sort();
+	assert_eq!(connected, expected);
+	assert!(notif.is_connected(&peer1.into()));
+
+	handle.report_substream_closed(peer1).unwrap();
+	let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notif.connected_peers(), vec![peer2.into()]);
+	assert!(!notif.is_connected(&peer1.into()));
+}
+
+#[tokio::test]
+async fn peer_snapshot_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink1, _async_rx1, _sync_rx1) = NotificationsSink::new(PeerId::random());
+	let (sink2, _async_rx2, _sync_rx2) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer1 = PeerId::random();
+	let peer2 = PeerId::random();
+
+	assert!(notif.peer_snapshot().is_empty());
+
+	handle
+		.report_substream_opened(peer1, Direction::Inbound, vec![1, 3, 3, 7], None, sink1)
+		.unwrap();
+	handle
+		.report_substream_opened(
+			peer2,
+			Direction::Outbound,
+			vec![1, 3, 3, 7],
+			Some("/proto/0".into()),
+			sink2,
+		)
+		.unwrap();
+	for _ in 0..2 {
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+	notif.send_sync_notification(&peer2.into(), vec![1, 3, 3, 8]);
+
+	let mut snapshot = notif
+		.peer_snapshot()
+		.into_iter()
+		.map(|diagnostic| {
+			(
+				diagnostic.peer,
+				diagnostic.direction,
+				diagnostic.negotiated_fallback,
+				diagnostic.queued_notifications,
+			)
+		})
+		.collect::<Vec<_>>();
+	snapshot.sort_by_key(|(peer, ..)| *peer);
+	let mut expected: Vec<(sc_network_types::PeerId, Direction, Option<ProtocolName>, usize)> = vec![
+		(peer1.into(), Direction::Inbound, None, 0),
+		(peer2.into(), Direction::Outbound, Some("/proto/0".into()), 1),
+	];
+	expected.sort_by_key(|(peer, ..)| *peer);
+	assert_eq!(snapshot, expected);
+}
+
+#[tokio::test]
+async fn peer_health_works() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (healthy_sink, _async_rx1, _sync_rx1) = NotificationsSink::new(PeerId::random());
+	let (lagging_sink, _async_rx2, _sync_rx2) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let healthy = PeerId::random();
+	let lagging = PeerId::random();
+
+	assert_eq!(notif.peer_health(&healthy.into()), None);
+
+	handle
+		.report_substream_opened(healthy, Direction::Inbound, vec![1, 3, 3, 7], None, healthy_sink)
+		.unwrap();
+	handle
+		.report_substream_opened(lagging, Direction::Inbound, vec![1, 3, 3, 7], None, lagging_sink)
+		.unwrap();
+	for _ in 0..2 {
+		let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+	}
+
+	// fill half of the sink of the lagging peer
+	let capacity = notif.sink_backpressure(&lagging.into()).unwrap().sync_capacity;
+	for _ in 0..capacity / 2 {
+		notif.send_sync_notification(&lagging.into(), vec![1, 3, 3, 7]);
+	}
+
+	let healthy_score = notif.peer_health(&healthy.into()).unwrap();
+	let lagging_score = notif.peer_health(&lagging.into()).unwrap();
+	assert!(healthy_score > 0.99, "healthy score: {healthy_score}");
+	assert!((0.45..0.51).contains(&lagging_score), "lagging score: {lagging_score}");
+
+	// dropping notifications makes the peer unhealthy
+	for _ in 0..capacity {
+		notif.send_sync_notification(&lagging.into(), vec![1, 3, 3, 7]);
+	}
+	assert_eq!(notif.peer_health(&lagging.into()), Some(0.0));
+}
+
+#[test]
+fn try_set_handshake_enqueues_command() {
+	let (mut handle, mut commands) =
+		NotificationHandle::with_command_buffer(ProtocolName::from("/proto/1"));
+	assert!(commands.drain().is_empty());
+
+	assert_eq!(handle.try_set_handshake(vec![1, 3, 3, 7]), Ok(()));
+
+	let commands = commands.drain();
+	assert_eq!(commands.len(), 1);
+	assert!(
+		matches!(&commands[0], NotificationCommand::SetHandshake(handshake) if handshake == &vec![1, 3, 3, 7])
+	);
+}
+
+#[tokio::test]
+async fn inbound_rate_limit_drops_excess_notifications() {
+	let (mut proto, mut notif) = notification_service("/proto/1".into());
+	proto.set_inbound_rate_limit(InboundRateLimit::MessagesPerSecond(2));
+	let (mut handle, _stream) = proto.split();
+	let (peer1, peer2) = (PeerId::random(), PeerId::random());
+
+	for peer in [peer1, peer2] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+	}
+
+	// the limit applies to each peer separately
+	assert_eq!(handle.report_notification_received(peer1, vec![1]), Ok(()));
+	assert_eq!(handle.report_notification_received(peer1, vec![2]), Ok(()));
+	assert_eq!(handle.report_notification_received(peer1, vec![3]), Err(()));
+	assert_eq!(handle.report_notification_received(peer2, vec![4]), Ok(()));
+
+	// closing the substream resets the state of the peer
+	handle.report_substream_closed(peer1).unwrap();
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+	handle
+		.report_substream_opened(peer1, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	assert_eq!(handle.report_notification_received(peer1, vec![5]), Ok(()));
+
+	let mut received = Vec::new();
+	while received.len() < 4 {
+		if let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+			notif.next_event().await
+		{
+			received.push(notification);
+		}
+	}
+	assert_eq!(received, vec![vec![1], vec![2], vec![4], vec![5]]);
+}
+
+#[tokio::test]
+async fn full_subscriber_channel_drops_events() {
+	let (proto, mut notif) = notification_service_with_config(
+		"/proto/1".into(),
+		NotificationChannelConfig { capacity: 2, overflow: ChannelOverflow::Drop },
+	);
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	for notification in [vec![1], vec![2], vec![3], vec![4]] {
+		handle.report_notification_received(peer_id, notification).unwrap();
+	}
+	handle.report_substream_closed(peer_id).unwrap();
+
+	// the oldest notifications which didn't fit in the channel were dropped but the substream
+	// events weren't
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	for expected in [vec![1], vec![4]] {
+		let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+			notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		assert_eq!(notification, expected);
+	}
+	let Some(NotificationEvent::NotificationStreamClosed { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// events fit again once the subscriber catches up
+	handle.report_notification_received(peer_id, vec![5]).unwrap();
+	let Some(NotificationEvent::NotificationReceived { notification, .. }) =
+		notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(notification, vec![5]);
+}
+
+#[tokio::test]
+async fn full_subscriber_channel_keeps_substream_events() {
+	let (proto, mut notif) = notification_service_with_config(
+		"/proto/1".into(),
+		NotificationChannelConfig { capacity: 1, overflow: ChannelOverflow::Drop },
+	);
+	let (mut handle, _stream) = proto.split();
+	let peers = (0..3).map(|_| PeerId::random()).collect::<Vec<_>>();
+
+	for peer in &peers {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(*peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+	}
+
+	// substream events are queued beyond the capacity
+	for expected in &peers {
+		let Some(NotificationEvent::NotificationStreamOpened { peer, .. }) =
+			notif.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		assert_eq!(peer, (*expected).into());
+	}
+	assert!(futures::poll!(notif.next_event()).is_pending());
+}
+
+#[tokio::test]
+async fn dropped_subscribers_are_pruned() {
+	let registry = prometheus_endpoint::Registry::new();
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	handle.set_metrics(NotificationMetrics::new(Some(&registry)));
+	let peer_id = PeerId::random();
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+
+	let pruned = || {
+		registry
+			.gather()
+			.iter()
+			.find(|family| {
+				family.get_name() == "substrate_sub_libp2p_notifications_subscribers_pruned_total"
+			})
+			.map_or(0.0, |family| family.get_metric()[0].get_counter().get_value())
+	};
+
+	// dropping the clone drops the receiver of its event stream
+	drop(notif.clone().unwrap());
+	assert_eq!(pruned(), 0.0);
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	assert_eq!(pruned(), 1.0);
+
+	// the remaining subscriber still receives events and nothing else is pruned
+	handle.report_notification_received(peer_id, vec![1]).unwrap();
+	assert_eq!(pruned(), 1.0);
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+}
+
+#[tokio::test]
+async fn full_subscriber_channel_disconnects_subscriber() {
+	let (proto, mut notif) = notification_service_with_config(
+		"/proto/1".into(),
+		NotificationChannelConfig { capacity: 1, overflow: ChannelOverflow::Disconnect },
+	);
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+	let (sink, _, _) = NotificationsSink::new(PeerId::random());
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	handle.report_notification_received(peer_id, vec![1]).unwrap();
+
+	// the queued event is still delivered, then the stream ends
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	assert!(notif.next_event().await.is_none());
+}
+
+#[test]
+fn try_open_and_close_substream_enqueue_commands() {
+	let (mut handle, mut commands) =
+		NotificationHandle::with_command_buffer(ProtocolName::from("/proto/1"));
+	let peer = PeerId::random();
+
+	assert_eq!(handle.try_open_substream(peer.into()), Ok(()));
+	let commands_sent = commands.drain();
+	assert_eq!(commands_sent.len(), 1);
+	assert!(matches!(commands_sent[0], NotificationCommand::OpenSubstream(p, _) if p == peer));
+
+	// no substream is open to `peer`
+	assert_eq!(handle.try_close_substream(peer.into()), Err(()));
+	assert!(commands.drain().is_empty());
+
+	// a full command queue is reported instead of waiting
+	for _ in 0..COMMAND_QUEUE_SIZE {
+		assert_eq!(handle.try_open_substream(peer.into()), Ok(()));
+	}
+	assert_eq!(handle.try_open_substream(peer.into()), Err(()));
+}
+
+#[tokio::test]
+async fn scoped_message_sink_expires() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, mut async_rx, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	assert!(notif.message_sink_scoped(&peer_id.into(), Duration::from_secs(5)).is_none());
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	let sink = notif.message_sink_scoped(&peer_id.into(), Duration::from_millis(100)).unwrap();
+	sink.send_async_notification(vec![1, 3, 3, 7]).await.unwrap();
+	assert_eq!(
+		async_rx.next().await,
+		Some(NotificationsSinkMessage::Notification { message: vec![1, 3, 3, 7] }),
+	);
+
+	futures_timer::Delay::new(Duration::from_millis(150)).await;
+	assert!(matches!(
+		sink.send_async_notification(vec![1, 3, 3, 8]).await,
+		Err(error::Error::Expired)
+	));
+}
+
+#[tokio::test]
+async fn clone_replays_pending_substream_events() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (mut handle, _stream) = proto.split();
+	let (peer1, peer2) = (PeerId::random(), PeerId::random());
+
+	for peer in [peer1, peer2] {
+		let (sink, _, _) = NotificationsSink::new(PeerId::random());
+		handle
+			.report_substream_opened(peer, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+			.unwrap();
+	}
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+
+	// `peer2` is still connecting and `peer1` disconnecting from the point of view of `notif`
+	handle.report_substream_closed(peer1).unwrap();
+	let mut cloned = notif.clone().unwrap();
+
+	let Some(NotificationEvent::NotificationStreamOpened { peer, .. }) = cloned.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer2.into());
+	let Some(NotificationEvent::NotificationStreamClosed { peer }) = cloned.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer1.into());
+	assert_eq!(cloned.connected_peers(), vec![peer2.into()]);
+
+	// `notif` still receives the events it hadn't processed yet
+	let Some(NotificationEvent::NotificationStreamOpened { peer, .. }) = notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer2.into());
+	let Some(NotificationEvent::NotificationStreamClosed { peer }) = notif.next_event().await
+	else {
+		panic!("invalid event received");
+	};
+	assert_eq!(peer, peer1.into());
+
+	// and both see subsequent events
+	handle.report_notification_received(peer2, vec![1, 3, 3, 8]).unwrap();
+	for service in [&mut notif, &mut cloned] {
+		let Some(NotificationEvent::NotificationReceived { peer, .. }) = service.next_event().await
+		else {
+			panic!("invalid event received");
+		};
+		assert_eq!(peer, peer2.into());
+	}
+}
+
+#[tokio::test]
+async fn try_send_sync_notification_reports_dropped_notifications() {
+	let (proto, mut notif) = notification_service("/proto/1".into());
+	let (sink, _async_rx, _sync_rx) = NotificationsSink::new(PeerId::random());
+	let (mut handle, _stream) = proto.split();
+	let peer_id = PeerId::random();
+
+	handle
+		.report_substream_opened(peer_id, Direction::Inbound, vec![1, 3, 3, 7], None, sink)
+		.unwrap();
+	let Some(NotificationEvent::NotificationStreamOpened { .. }) = notif.next_event().await else {
+		panic!("invalid event received");
+	};
+	let sink = notif.message_sink(&peer_id.into()).unwrap();
+
+	// nothing drains the sync channel so it eventually overflows
+	let statuses = (0..SYNC_NOTIFICATIONS_BUFFER_SIZE + 16)
+		.map(|_| sink.try_send_sync_notification(vec![1, 3, 3, 8]))
+		.collect::<Vec<_>>();
+	let queued = statuses.iter().take_while(|status| **status == SendStatus::Queued).count();
+	assert!(queued >= SYNC_NOTIFICATIONS_BUFFER_SIZE);
+	assert!(statuses[queued..].iter().all(|status| *status == SendStatus::Dropped));
+	assert!(queued < statuses.len());
+}
@@ -154,6 +154,8 @@ enum Action {
 	SetReservedOnly(bool),
 	/// Disconnect a peer.
 	DisconnectPeer(PeerId),
+	/// Connect to a peer using an outgoing slot.
+	ConnectPeer(PeerId, oneshot::Sender<Result<(), ()>>),
 	/// Get the list of reserved peers.
 	GetReservedPeers(oneshot::Sender<Vec<PeerId>>),
 }
@@ -213,6 +215,15 @@ impl ProtocolHandle {
 		let _ = self.actions_tx.unbounded_send(Action::DisconnectPeer(peer_id));
 	}
 
+	/// Connect to a peer using one of the outgoing slots.
+	///
+	/// `pending_response` receives `Ok(())` if the peer is being connected or is already known,
+	/// and `Err(())` if the peer is banned, the set is reserved-only or no outgoing slot is
+	/// available.
+	pub fn connect_peer(&self, peer_id: PeerId, pending_response: oneshot::Sender<Result<(), ()>>) {
+		let _ = self.actions_tx.unbounded_send(Action::ConnectPeer(peer_id, pending_response));
+	}
+
 	/// Get the list of reserved peers.
 	pub fn reserved_peers(&self, pending_response: oneshot::Sender<Vec<PeerId>>) {
 		let _ = self.actions_tx.unbounded_send(Action::GetReservedPeers(pending_response));
@@ -386,6 +397,9 @@ impl ProtocolController {
 			Action::SetReservedPeers(peer_ids) => self.on_set_reserved_peers(peer_ids),
 			Action::SetReservedOnly(reserved_only) => self.on_set_reserved_only(reserved_only),
 			Action::DisconnectPeer(peer_id) => self.on_disconnect_peer(peer_id),
+			Action::ConnectPeer(peer_id, pending_response) => {
+				let _ = pending_response.send(self.on_connect_peer(peer_id));
+			},
 			Action::GetReservedPeers(pending_response) =>
 				self.on_get_reserved_peers(pending_response),
 		}
@@ -639,6 +653,38 @@ impl ProtocolController {
 		}
 	}
 
+	/// Connect to the peer using an outgoing slot.
+	///
+	/// Returns `Err(())` if the peer can't be connected.
+	fn on_connect_peer(&mut self, peer_id: PeerId) -> Result<(), ()> {
+		if self.reserved_nodes.contains_key(&peer_id) || self.nodes.contains_key(&peer_id) {
+			debug!(
+				target: LOG_TARGET,
+				"Ignoring request to connect already known peer {peer_id} on {:?}.", self.set_id,
+			);
+			return Ok(())
+		}
+
+		if self.reserved_only || self.num_out >= self.max_out || self.is_banned(&peer_id) {
+			debug!(
+				target: LOG_TARGET,
+				"Ignoring request to connect peer {peer_id} on {:?}: no slot available or banned \
+				({}/{} num_out/max_out, reserved only: {}).",
+				self.set_id,
+				self.num_out,
+				self.max_out,
+				self.reserved_only,
+			);
+			return Err(())
+		}
+
+		self.num_out += 1;
+		self.nodes.insert(peer_id, Direction::Outbound);
+		self.start_connection(peer_id);
+
+		Ok(())
+	}
+
 	/// Indicate that we received an incoming connection. Must be answered either with
 	/// a corresponding `Accept` or `Reject`, except if we were already connected to this peer.
 	///
@@ -1609,6 +1655,45 @@ mod tests {
 		assert_eq!(controller.num_out, 0);
 	}
 
+	#[test]
+	fn connecting_regular_peer_works() {
+		let peer = PeerId::random();
+
+		let config = ProtoSetConfig {
+			in_peers: 10,
+			out_peers: 1,
+			reserved_nodes: HashSet::new(),
+			reserved_only: false,
+		};
+		let (tx, mut rx) = tracing_unbounded("mpsc_test_to_notifications", 100);
+
+		let mut peer_store = MockPeerStoreHandle::new();
+		peer_store.expect_register_protocol().once().return_const(());
+		peer_store.expect_is_banned().once().return_const(false);
+
+		let (_handle, mut controller) =
+			ProtocolController::new(SetId::from(0), config, tx, Arc::new(peer_store));
+
+		assert_eq!(controller.on_connect_peer(peer), Ok(()));
+		assert_eq!(
+			rx.try_recv().unwrap(),
+			Message::Connect { set_id: SetId::from(0), peer_id: peer }
+		);
+		assert!(matches!(controller.nodes.get(&peer), Some(Direction::Outbound)));
+		assert_eq!(controller.num_out, 1);
+
+		// Connecting an already connected peer is a no-op.
+		assert_eq!(controller.on_connect_peer(peer), Ok(()));
+		assert_eq!(rx.try_recv().unwrap_err(), TryRecvError::Empty);
+		assert_eq!(controller.num_out, 1);
+
+		// No outgoing slots left.
+		assert_eq!(controller.on_connect_peer(PeerId::random()), Err(()));
+		assert_eq!(rx.try_recv().unwrap_err(), TryRecvError::Empty);
+		assert_eq!(controller.nodes.len(), 1);
+		assert_eq!(controller.num_out, 1);
+	}
+
 	#[test]
 	fn disconnecting_reserved_peers_is_a_noop() {
 		let reserved1 = PeerId::random();
@@ -338,6 +338,16 @@ impl NotificationMetrics {
 		}
 	}
 
+	/// Register the number of substreams opened and closed within the last minute to Prometheus.
+	pub fn register_substream_churn(&self, protocol: &ProtocolName, churn: usize) {
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.notifications_substream_churn
+				.with_label_values(&[protocol])
+				.set(churn as u64);
+		}
+	}
+
 	/// Register sent notification to Prometheus.
 	pub fn register_notification_sent(&self, protocol: &ProtocolName, size: usize) {
 		if let Some(metrics) = &self.metrics {
@@ -357,6 +367,51 @@ impl NotificationMetrics {
 				.observe(size as f64);
 		}
 	}
+
+	/// Register received notification which failed authentication to Prometheus.
+	pub fn register_notification_unauthenticated(&self, protocol: &ProtocolName) {
+		if let Some(metrics) = &self.metrics {
+			metrics.notifications_unauthenticated_total.with_label_values(&[protocol]).inc();
+		}
+	}
+
+	/// Register received notification dropped because of the inbound rate limit to Prometheus.
+	pub fn register_notification_dropped_rate_limited(&self, protocol: &ProtocolName) {
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.notifications_dropped_rate_limited_total
+				.with_label_values(&[protocol])
+				.inc();
+		}
+	}
+
+	/// Register received notifications dropped while a subscriber paused their delivery to
+	/// Prometheus.
+	pub fn register_notification_dropped_paused(&self, protocol: &ProtocolName, dropped: usize) {
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.notifications_dropped_paused_total
+				.with_label_values(&[protocol])
+				.inc_by(dropped as u64);
+		}
+	}
+
+	/// Register sent notification dropped because the channel to the peer is full to Prometheus.
+	pub fn register_notification_dropped(&self, protocol: &ProtocolName) {
+		if let Some(metrics) = &self.metrics {
+			metrics.notifications_sync_dropped_total.with_label_values(&[protocol]).inc();
+		}
+	}
+
+	/// Register subscribers pruned because their event stream was closed to Prometheus.
+	pub fn register_subscribers_pruned(&self, protocol: &ProtocolName, pruned: usize) {
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.subscribers_pruned_total
+				.with_label_values(&[protocol])
+				.inc_by(pruned as u64);
+		}
+	}
 }
 
 /// Notification metrics.
@@ -368,8 +423,26 @@ struct InnerNotificationMetrics {
 	/// Total number of closed substreams.
 	pub notifications_streams_closed_total: CounterVec<U64>,
 
+	/// Number of substreams opened and closed within the last minute.
+	pub notifications_substream_churn: GaugeVec<U64>,
+
 	/// In/outbound notification sizes.
 	pub notifications_sizes: HistogramVec,
+
+	/// Total number of received notifications dropped because they failed authentication.
+	pub notifications_unauthenticated_total: CounterVec<U64>,
+
+	/// Total number of received notifications dropped because of the inbound rate limit.
+	pub notifications_dropped_rate_limited_total: CounterVec<U64>,
+
+	/// Total number of received notifications dropped while a subscriber paused their delivery.
+	pub notifications_dropped_paused_total: CounterVec<U64>,
+
+	/// Total number of subscribers pruned because their event stream was closed.
+	pub subscribers_pruned_total: CounterVec<U64>,
+
+	/// Total number of synchronous notifications dropped because the channel to the peer is full.
+	pub notifications_sync_dropped_total: CounterVec<U64>,
 }
 
 impl InnerNotificationMetrics {
@@ -399,6 +472,16 @@ impl InnerNotificationMetrics {
 				)?,
 				registry,
 			)?,
+			notifications_substream_churn: prometheus::register(
+				GaugeVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_substream_churn",
+						"Number of notification substreams that have been opened and closed within the last minute",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
 			notifications_streams_opened_total: prometheus::register(
 				CounterVec::new(
 					Opts::new(
@@ -409,6 +492,56 @@ impl InnerNotificationMetrics {
 				)?,
 				registry,
 			)?,
+			notifications_unauthenticated_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_unauthenticated_total",
+						"Total number of received notifications dropped because they failed authentication",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
+			notifications_dropped_rate_limited_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_dropped_rate_limited_total",
+						"Total number of received notifications dropped because of the inbound rate limit",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
+			notifications_dropped_paused_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_dropped_paused_total",
+						"Total number of received notifications dropped while a subscriber paused their delivery",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
+			subscribers_pruned_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_subscribers_pruned_total",
+						"Total number of notification protocol subscribers pruned because their event stream was closed",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
+			notifications_sync_dropped_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_sync_dropped_total",
+						"Total number of synchronous notifications dropped because the channel to the peer is full",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -31,6 +31,7 @@ use crate::{
 	ReputationChange,
 };
 
+use codec::{Codec, DecodeAll};
 use futures::{channel::oneshot, Stream};
 use prometheus_endpoint::Registry;
 
@@ -44,9 +45,11 @@ pub use sc_network_types::{
 use sp_runtime::traits::Block as BlockT;
 
 use std::{
+	any::Any,
 	collections::HashSet,
 	fmt::Debug,
 	future::Future,
+	marker::PhantomData,
 	pin::Pin,
 	sync::Arc,
 	time::{Duration, Instant},
@@ -843,6 +846,115 @@ pub enum NotificationEvent {
 	},
 }
 
+/// Configuration for coalescing synchronous notifications.
+///
+/// See [`NotificationService::set_coalescing()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoalesceConfig {
+	/// How long notifications sent to a peer are buffered before they are flushed.
+	pub window: Duration,
+
+	/// Maximum number of notifications buffered for a peer before they are flushed.
+	pub max_batch: usize,
+}
+
+/// Configuration for compressing notifications.
+///
+/// See [`NotificationService::set_compression()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionConfig {
+	/// Minimum size of a notification for it to be compressed.
+	pub threshold: usize,
+
+	/// Maximum size of a notification before compression and after decompression.
+	///
+	/// Received notifications which decompress to more than this are dropped.
+	pub max_size: usize,
+}
+
+/// Whether the notifications received from all peers must be authenticated.
+///
+/// See [`NotificationService::set_auth_verifier()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMode {
+	/// Only the notifications of peers advertising signing them in their handshake are
+	/// verified, the notifications of other peers are returned as-is.
+	///
+	/// Keeps the protocol backwards compatible, but a peer can skip the verification of its
+	/// notifications by not advertising signing them.
+	#[default]
+	Optional,
+
+	/// The notifications of all peers are verified.
+	///
+	/// Inbound substreams of peers not advertising signing their notifications are rejected
+	/// and outbound substreams to them are closed. Notifications received from them before the
+	/// substream is closed are dropped.
+	Required,
+}
+
+/// Notifications sent to and received from a peer since the substream was opened.
+///
+/// See [`NotificationService::peer_stats()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PeerStats {
+	/// Number of notifications sent.
+	pub sent_msgs: u64,
+
+	/// Number of bytes sent.
+	pub sent_bytes: u64,
+
+	/// Number of notifications received.
+	pub recv_msgs: u64,
+
+	/// Number of bytes received.
+	pub recv_bytes: u64,
+}
+
+/// Snapshot of the backpressure state of the notification sink of a peer.
+///
+/// See [`NotificationService::sink_backpressure()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SinkState {
+	/// Number of notifications queued and not yet written to the substream.
+	pub queued_notifications: usize,
+
+	/// Number of bytes of the queued notifications.
+	pub queued_bytes: usize,
+
+	/// Lower bound of the number of notifications which can still be sent with
+	/// [`NotificationService::send_sync_notification()`] before they start being dropped.
+	pub sync_capacity: usize,
+
+	/// Whether the synchronous buffer overflowed.
+	///
+	/// If set, notifications sent with [`NotificationService::send_sync_notification()`] are
+	/// dropped and the substream is about to be closed.
+	pub sync_overflowed: bool,
+}
+
+/// Diagnostic snapshot of a peer with an open substream.
+///
+/// See [`NotificationService::peer_snapshot()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerDiagnostic {
+	/// Peer ID.
+	pub peer: PeerId,
+
+	/// Direction of the substream.
+	pub direction: Direction,
+
+	/// Negotiated fallback, if any.
+	pub negotiated_fallback: Option<ProtocolName>,
+
+	/// Number of notifications queued and not yet written to the substream.
+	pub queued_notifications: usize,
+
+	/// When the last notification was received from the peer or, if none was received, when
+	/// the substream was opened.
+	pub last_seen: Instant,
+}
+
 /// Notification service
 ///
 /// Defines behaviors that both the protocol implementations and `Notifications` can expect from
@@ -886,20 +998,75 @@ pub enum NotificationEvent {
 pub trait NotificationService: Debug + Send {
 	/// Instruct `Notifications` to open a new substream for `peer`.
 	///
-	/// `dial_if_disconnected` informs `Notifications` whether to dial
-	// the peer if there is currently no active connection to it.
-	//
-	// NOTE: not offered by the current implementation
+	/// Returns `Ok(())` once the request has been accepted. The peer is dialed if there is
+	/// currently no active connection to it. The opening of the substream is reported through
+	/// [`NotificationService::next_event()`]. Requests for peers to which a substream is
+	/// already open have no effect. Returns `Err(())` if the request was refused, for example
+	/// because no outgoing slot is available or the peer is banned.
 	async fn open_substream(&mut self, peer: PeerId) -> Result<(), ()>;
 
+	/// Instruct `Notifications` to open a new substream for `peer`, preferring `fallbacks`.
+	///
+	/// Behaves like [`NotificationService::open_substream()`], but if `peer` doesn't support the
+	/// main protocol, the fallbacks are offered in the order given in `fallbacks`. Names which
+	/// aren't fallbacks of the protocol are ignored and fallbacks which aren't listed aren't
+	/// offered. The preference is kept until the substream is closed with
+	/// [`NotificationService::close_substream()`] or the last connection to `peer` is closed.
+	///
+	/// By default the fallbacks are ignored and the substream is opened with
+	/// [`NotificationService::open_substream()`].
+	async fn open_substream_with_fallbacks(
+		&mut self,
+		peer: PeerId,
+		_fallbacks: Vec<ProtocolName>,
+	) -> Result<(), ()> {
+		self.open_substream(peer).await
+	}
+
 	/// Instruct `Notifications` to close substream for `peer`.
-	//
-	// NOTE: not offered by the current implementation
+	///
+	/// Only the substream of this protocol is closed, the connection to `peer` and the substreams
+	/// of other protocols are kept alive. The closing of the substream is reported through
+	/// [`NotificationService::next_event()`]. Returns `Err(())` if there is no substream open
+	/// to `peer`.
 	async fn close_substream(&mut self, peer: PeerId) -> Result<(), ()>;
 
+	/// Non-blocking variant of [`NotificationService::open_substream()`].
+	///
+	/// Returns `Err(())` if the request couldn't be queued because the channel to
+	/// `Notifications` is full or closed. The opening of the substream is still reported
+	/// asynchronously through [`NotificationService::next_event()`], but a refused request is not
+	/// reported.
+	///
+	/// By default every request is refused.
+	fn try_open_substream(&mut self, _peer: PeerId) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Non-blocking variant of [`NotificationService::close_substream()`].
+	///
+	/// Returns `Err(())` if there is no substream open to `peer` or if the request couldn't be
+	/// queued because the channel to `Notifications` is full or closed. The closing of the
+	/// substream is still reported asynchronously through [`NotificationService::next_event()`].
+	///
+	/// By default every request is refused.
+	fn try_close_substream(&mut self, _peer: PeerId) -> Result<(), ()> {
+		Err(())
+	}
+
 	/// Send synchronous `notification` to `peer`.
 	fn send_sync_notification(&mut self, peer: &PeerId, notification: Vec<u8>);
 
+	/// Send synchronous `notification` to all peers with an open substream.
+	///
+	/// Equivalent to calling [`NotificationService::send_sync_notification()`] for each peer,
+	/// so notifications to peers whose sink is full are dropped.
+	fn broadcast_sync_notification(&mut self, notification: Vec<u8>) {
+		for peer in self.connected_peers() {
+			self.send_sync_notification(&peer, notification.clone());
+		}
+	}
+
 	/// Send asynchronous `notification` to `peer`, allowing sender to exercise backpressure.
 	///
 	/// Returns an error if the peer doesn't exist.
@@ -926,11 +1093,544 @@ pub trait NotificationService: Debug + Send {
 	/// who wish to have access to the same underlying notification protocol.
 	fn clone(&mut self) -> Result<Box<dyn NotificationService>, ()>;
 
+	/// Make a copy of the object which shares the view of connected peers with `self`.
+	///
+	/// Compared to [`NotificationService::clone()`], the peers are not copied to the new object
+	/// but shared between all objects created this way, which keeps their views consistent at the
+	/// cost of synchronizing access to the peers.
+	///
+	/// Returns `Err(())` if sharing the peers is not supported, which is the default.
+	fn clone_shared(&mut self) -> Result<Box<dyn NotificationService>, ()> {
+		Err(())
+	}
+
 	/// Get protocol name of the `NotificationService`.
 	fn protocol(&self) -> &ProtocolName;
 
 	/// Get message sink of the peer.
 	fn message_sink(&self, peer: &PeerId) -> Option<Box<dyn MessageSink>>;
+
+	/// Get message sink of the peer which expires after `ttl`.
+	///
+	/// Once expired, the sink refuses to send: asynchronous sends fail with [`Error::Expired`]
+	/// and synchronous notifications are dropped with a warning. This catches sinks which are
+	/// kept around after the connection to the peer they were obtained for is gone.
+	fn message_sink_scoped(&self, peer: &PeerId, ttl: Duration) -> Option<Box<dyn MessageSink>> {
+		self.message_sink(peer)
+			.map(|sink| Box::new(ScopedMessageSink::new(sink, ttl)) as Box<dyn MessageSink>)
+	}
+
+	/// Set the encoder applied to notifications sent to peers that negotiated a fallback
+	/// protocol.
+	///
+	/// The encoder receives the notification and the name of the negotiated fallback and returns
+	/// the payload that is sent to the peer. Peers using the main protocol are not affected.
+	///
+	/// By default notifications are sent as-is and the encoder is ignored.
+	fn set_fallback_encoder(
+		&mut self,
+		_encoder: Arc<dyn Fn(&[u8], &ProtocolName) -> Vec<u8> + Send + Sync>,
+	) {
+	}
+
+	/// Enable coalescing of synchronous notifications.
+	///
+	/// Notifications sent to a peer using [`NotificationService::send_sync_notification()`] are
+	/// buffered for up to [`CoalesceConfig::window`] or until [`CoalesceConfig::max_batch`]
+	/// notifications have been buffered, after which `framer` is called to frame the batch into a
+	/// single notification which is then sent to the peer. The remote must be able to split the
+	/// framed notification using the same framing.
+	///
+	/// Coalescing reduces the per-notification overhead for protocols sending many small
+	/// notifications, at the cost of delaying each notification by up to the configured window.
+	/// Protocols with latency-sensitive notifications should use a short window or not enable
+	/// coalescing at all.
+	///
+	/// Notifications sent with [`NotificationService::send_async_notification()`] or through a
+	/// [`MessageSink`] are not coalesced.
+	///
+	/// Coalescing is not supported by default, notifications are sent one by one.
+	fn set_coalescing(
+		&mut self,
+		_config: CoalesceConfig,
+		_framer: Arc<dyn Fn(Vec<Vec<u8>>) -> Vec<u8> + Send + Sync>,
+	) {
+	}
+
+	/// Take all notifications received from `peer` which are immediately available, without
+	/// waiting for new ones.
+	///
+	/// All other events are left queued and are returned by
+	/// [`NotificationService::next_event()`]. By default nothing is drained.
+	fn drain_received(&mut self, _peer: &PeerId) -> Vec<Vec<u8>> {
+		Vec::new()
+	}
+
+	/// Check whether the substream to `peer` supports backpressure.
+	///
+	/// If it does, the protocol can use [`NotificationService::send_async_notification()`] to
+	/// wait until the substream is ready to accept the notification. Otherwise notifications
+	/// should be sent with [`NotificationService::send_sync_notification()`].
+	///
+	/// Returns `None` if the substream to `peer` is not open. By default backpressure is reported
+	/// as unsupported.
+	fn supports_backpressure(&self, peer: &PeerId) -> Option<bool> {
+		self.is_connected(peer).then_some(false)
+	}
+
+	/// Set the interceptor applied to every notification received by this handle before it's
+	/// returned by [`NotificationService::next_event()`] or
+	/// [`NotificationService::drain_received()`].
+	///
+	/// The interceptor receives the peer and the notification and returns `None` to drop the
+	/// notification or `Some` with the notification that is passed to the protocol instead. This
+	/// allows, e.g., validating or decompressing notifications in one place.
+	///
+	/// The interceptor is inherited by clones of the handle created after this call.
+	///
+	/// Interceptors are not supported by default and the interceptor is ignored, so protocols
+	/// relying on it for validation must not run on backends which don't implement it.
+	fn set_receive_interceptor(
+		&mut self,
+		_interceptor: Arc<dyn Fn(PeerId, Vec<u8>) -> Option<Vec<u8>> + Send + Sync>,
+	) {
+	}
+
+	/// Get the statistics of the notifications sent to and received from `peer` since the
+	/// substream was opened.
+	///
+	/// Notifications sent through a [`MessageSink`] are not included.
+	///
+	/// Returns `None` if the substream to `peer` is not open or if the statistics are not
+	/// tracked, which is the default.
+	fn peer_stats(&self, _peer: &PeerId) -> Option<PeerStats> {
+		None
+	}
+
+	/// Get the protocol negotiated with `peer`.
+	///
+	/// Returns the negotiated fallback if `peer` negotiated one and the main protocol name
+	/// otherwise. Returns `None` if the substream to `peer` is not open or if the negotiated
+	/// protocol is not tracked, which is the default.
+	fn negotiated_protocol(&self, _peer: &PeerId) -> Option<ProtocolName> {
+		None
+	}
+
+	/// Get the peers with an open substream in `direction`.
+	///
+	/// This allows, e.g., broadcasting a notification only to outbound peers so it's not echoed
+	/// back to the inbound peers it was received from.
+	///
+	/// By default the peers are not tracked and none are returned.
+	fn peers_by_direction(&self, _direction: Direction) -> Vec<PeerId> {
+		Vec::new()
+	}
+
+	/// Get the peers with an open substream.
+	///
+	/// By default the peers are not tracked and none are returned.
+	fn connected_peers(&self) -> Vec<PeerId> {
+		Vec::new()
+	}
+
+	/// Check whether there is an open substream to `peer`.
+	///
+	/// This allows checking that notifications can be sent to `peer` before sending them.
+	fn is_connected(&self, peer: &PeerId) -> bool {
+		self.connected_peers().contains(peer)
+	}
+
+	/// Get the total number of bytes of notifications queued for all peers and not yet written
+	/// to their substreams.
+	///
+	/// This can be used as a memory pressure signal, e.g. to shed load when the notification
+	/// buffers grow. Notifications sent through a [`MessageSink`] are included.
+	///
+	/// Returns `None` if the networking backend doesn't report the queued bytes, which is the
+	/// default.
+	fn total_queued_bytes(&self) -> Option<usize> {
+		None
+	}
+
+	/// Get a snapshot of the backpressure state of the notification sink of `peer`.
+	///
+	/// This allows switching from [`NotificationService::send_sync_notification()`] to
+	/// [`NotificationService::send_async_notification()`] before the sink overflows and starts
+	/// dropping notifications.
+	///
+	/// Returns `None` if the substream to `peer` is not open or if the state of the sink is not
+	/// reported, which is the default.
+	fn sink_backpressure(&self, _peer: &PeerId) -> Option<SinkState> {
+		None
+	}
+
+	/// Get a diagnostic snapshot of all peers with an open substream, e.g. to dump the state of
+	/// the protocol for debugging.
+	///
+	/// By default the peers are not tracked and the snapshot is empty.
+	fn peer_snapshot(&self) -> Vec<PeerDiagnostic> {
+		Vec::new()
+	}
+
+	/// Get the health score of `peer`, between `0.0` (unhealthy) and `1.0` (healthy).
+	///
+	/// The score is cheap to compute and combines dropped notifications, the backlog of the
+	/// notification sink and the time since the peer was last seen, so that protocols can close
+	/// the least healthy peers first under resource pressure. Scores are only comparable between
+	/// peers of the same protocol.
+	///
+	/// Returns `None` if the substream to `peer` is not open or if the score is not computed,
+	/// which is the default.
+	fn peer_health(&self, _peer: &PeerId) -> Option<f32> {
+		None
+	}
+
+	/// Enable transparent compression of notifications.
+	///
+	/// `supports_compression` is called with the handshake received from a peer when the
+	/// substream is opened and returns whether the peer advertises support for compression,
+	/// which the protocol is responsible for encoding in its handshake. Notifications sent to
+	/// supporting peers are compressed if they are at least `config.threshold` bytes long and
+	/// notifications received from them are decompressed before they're returned by
+	/// [`NotificationService::next_event()`]. Peers which don't advertise support are sent
+	/// notifications as-is, keeping the protocol backwards compatible.
+	///
+	/// Only applies to substreams opened after the call. Compression is not supported by
+	/// default, notifications are then sent and received as-is.
+	fn set_compression(
+		&mut self,
+		_config: CompressionConfig,
+		_supports_compression: Arc<dyn Fn(&[u8]) -> bool + Send + Sync>,
+	) {
+	}
+
+	/// Check whether the handshake of `peer` was accepted.
+	///
+	/// Returns `Some(true)` if the substream to `peer` is open, i.e., the handshakes were accepted
+	/// by both sides, and otherwise the outcome of the most recent validation of an inbound
+	/// substream of `peer` done through this handle. This helps diagnosing, e.g., mismatching
+	/// handshake versions. Returns `None` if there is no substream to `peer` and none was
+	/// validated recently.
+	///
+	/// By default only open substreams are reported.
+	fn handshake_accepted(&self, peer: &PeerId) -> Option<bool> {
+		self.is_connected(peer).then_some(true)
+	}
+
+	/// Instruct `Notifications` to close the substreams of all peers from which no notification
+	/// was received within `idle_for`.
+	///
+	/// Peers from which no notification was received are considered seen when their substream
+	/// was opened. Returns the number of peers whose substream is closed. The closing of each
+	/// substream is reported through [`NotificationService::next_event()`].
+	///
+	/// By default the time peers were last seen is not tracked and no substream is closed.
+	async fn close_idle(&mut self, _idle_for: Duration) -> usize {
+		0
+	}
+
+	/// Set the signer computing the authentication tag of sent notifications.
+	///
+	/// The signer is called with every notification sent to a peer and the returned tag is
+	/// appended to it, which allows protocols to authenticate notifications beyond what the
+	/// transport provides. The receiving side must set a matching verifier with
+	/// [`NotificationService::set_auth_verifier()`].
+	///
+	/// `supports_auth` is called with the handshake received from a peer when the substream is
+	/// opened and returns whether the peer advertises verifying authentication tags, which the
+	/// protocol is responsible for encoding in its handshake. Only notifications sent to such
+	/// peers are signed, the others are sent as-is, keeping the protocol backwards compatible.
+	///
+	/// By default notifications are sent without a tag. Only applies to substreams opened after
+	/// the call.
+	///
+	/// Returns `Err(())` if the networking backend doesn't support authentication tags, in which
+	/// case notifications keep being sent without a tag. This is the default.
+	fn set_auth_signer(
+		&mut self,
+		_signer: Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>,
+		_supports_auth: Arc<dyn Fn(&[u8]) -> bool + Send + Sync>,
+	) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Set the verifier of the authentication tag of received notifications.
+	///
+	/// The verifier is called with the notification and the tag appended to it by the signer set
+	/// with [`NotificationService::set_auth_signer()`]. Notifications whose tag is missing or
+	/// rejected by the verifier are dropped, the others are returned by
+	/// [`NotificationService::next_event()`] with the tag stripped.
+	///
+	/// `supports_auth` is called with the handshake received from a peer when the substream is
+	/// opened and returns whether the peer advertises signing its notifications. With
+	/// [`AuthMode::Optional`], notifications received from other peers are returned as-is,
+	/// keeping the protocol backwards compatible. As the handshake is controlled by the peer,
+	/// protocols which must not accept unauthenticated notifications use [`AuthMode::Required`],
+	/// which refuses the substreams of such peers and drops their notifications.
+	///
+	/// By default received notifications are not verified. Only applies to substreams opened
+	/// after the call.
+	///
+	/// Returns `Err(())` if the networking backend doesn't support authentication tags, in which
+	/// case received notifications are not verified. Protocols requiring authentication must not
+	/// run on such a backend. This is the default.
+	fn set_auth_verifier(
+		&mut self,
+		_verifier: Arc<dyn Fn(&[u8], &[u8]) -> bool + Send + Sync>,
+		_supports_auth: Arc<dyn Fn(&[u8]) -> bool + Send + Sync>,
+		_mode: AuthMode,
+	) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Attach application-defined `metadata` to `peer`, replacing any metadata attached
+	/// before.
+	///
+	/// The metadata is dropped when the substream to `peer` is closed, so protocols don't need
+	/// to clean up per-peer state of disconnected peers. See
+	/// [`NotificationServiceExt::peer_metadata_as()`] for retrieving typed metadata.
+	///
+	/// Returns `Err(())` if there is no substream open to `peer` or if metadata is not
+	/// supported, which is the default.
+	fn set_peer_metadata(
+		&mut self,
+		_peer: &PeerId,
+		_metadata: Arc<dyn Any + Send + Sync>,
+	) -> Result<(), ()> {
+		Err(())
+	}
+
+	/// Get the metadata attached to `peer`.
+	///
+	/// Returns `None` if there is no substream open to `peer` or no metadata was attached.
+	fn peer_metadata(&self, _peer: &PeerId) -> Option<Arc<dyn Any + Send + Sync>> {
+		None
+	}
+
+	/// Pause the delivery of received notifications.
+	///
+	/// Allows a slow protocol to apply backpressure instead of lagging behind. While paused,
+	/// received notifications are held back up to a bounded limit, after which further
+	/// notifications are dropped. Notifications held back from a peer whose substream closes are
+	/// dropped as well. The pause only applies to this handle: other handles of the protocol,
+	/// including its clones, keep receiving notifications.
+	///
+	/// Pausing is not supported by default and the request is ignored.
+	fn request_pause(&mut self) {}
+
+	/// Resume the delivery of received notifications paused with
+	/// [`NotificationService::request_pause()`].
+	///
+	/// The notifications held back while paused are delivered first, in the order they were
+	/// received.
+	fn request_resume(&mut self) {}
+}
+
+/// Length of the correlation id prefix used by [`NotificationServiceExt::send_and_await_ack()`].
+const CORRELATION_ID_LEN: usize = core::mem::size_of::<u64>();
+
+/// Tag `notification` with `correlation_id`.
+///
+/// The correlation id is prepended to the notification as a little-endian `u64`.
+pub fn tag_notification(correlation_id: u64, notification: &[u8]) -> Vec<u8> {
+	let mut tagged = Vec::with_capacity(CORRELATION_ID_LEN + notification.len());
+	tagged.extend_from_slice(&correlation_id.to_le_bytes());
+	tagged.extend_from_slice(notification);
+	tagged
+}
+
+/// Split a notification tagged with [`tag_notification()`] into the correlation id and the
+/// payload.
+///
+/// Returns `None` if the notification is too short to carry a correlation id.
+pub fn untag_notification(notification: &[u8]) -> Option<(u64, &[u8])> {
+	if notification.len() < CORRELATION_ID_LEN {
+		return None
+	}
+	let (id, payload) = notification.split_at(CORRELATION_ID_LEN);
+	Some((u64::from_le_bytes(id.try_into().ok()?), payload))
+}
+
+/// Helpers built on top of [`NotificationService`].
+#[async_trait::async_trait]
+pub trait NotificationServiceExt: NotificationService {
+	/// Send `notification` to `peer` tagged with `correlation_id` and wait until the peer
+	/// acknowledges it.
+	///
+	/// The acknowledgement is a notification from `peer` tagged with the same `correlation_id`,
+	/// see [`tag_notification()`]. Both sides of the protocol must use this tagging convention:
+	/// the remote has to strip the tag from the received notification with
+	/// [`untag_notification()`] and tag its acknowledgement with the received correlation id.
+	///
+	/// Returns the payload of the acknowledgement, or [`Error::Timeout`] if no acknowledgement
+	/// was received within `timeout`.
+	///
+	/// Other events received while waiting for the acknowledgement are discarded so the call
+	/// should be made on a dedicated handle, obtained through [`NotificationService::clone()`].
+	async fn send_and_await_ack(
+		&mut self,
+		peer: &PeerId,
+		notification: Vec<u8>,
+		correlation_id: u64,
+		timeout: Duration,
+	) -> Result<Vec<u8>, error::Error>;
+
+	/// Get the metadata attached to `peer` if it's of type `T`.
+	fn peer_metadata_as<T: Any + Send + Sync>(&self, peer: &PeerId) -> Option<Arc<T>>;
+
+	/// Wait for the next event matching `pred`.
+	///
+	/// Events are consumed until one matches: the events received before it are processed like
+	/// with [`NotificationService::next_event()`], e.g. connected peers are still tracked, but
+	/// are not returned. Use a dedicated handle, obtained through
+	/// [`NotificationService::clone()`], if they are needed elsewhere.
+	///
+	/// Returns `None` if the event stream is closed before a matching event is received.
+	async fn wait_for<F>(&mut self, pred: F) -> Option<NotificationEvent>
+	where
+		F: Fn(&NotificationEvent) -> bool + Send;
+}
+
+#[async_trait::async_trait]
+impl<T: NotificationService + ?Sized> NotificationServiceExt for T {
+	async fn send_and_await_ack(
+		&mut self,
+		peer: &PeerId,
+		notification: Vec<u8>,
+		correlation_id: u64,
+		timeout: Duration,
+	) -> Result<Vec<u8>, error::Error> {
+		self.send_async_notification(peer, tag_notification(correlation_id, &notification))
+			.await?;
+
+		let ack = async {
+			loop {
+				match self.next_event().await {
+					Some(NotificationEvent::NotificationReceived { peer: from, notification })
+						if from == *peer =>
+						match untag_notification(&notification) {
+							Some((id, payload)) if id == correlation_id =>
+								return Ok(payload.to_vec()),
+							_ => {},
+						},
+					Some(NotificationEvent::NotificationStreamClosed { peer: from })
+						if from == *peer =>
+						return Err(Error::ConnectionClosed),
+					Some(_) => {},
+					None => return Err(Error::ChannelClosed),
+				}
+			}
+		};
+		futures::pin_mut!(ack);
+
+		match futures::future::select(ack, futures_timer::Delay::new(timeout)).await {
+			futures::future::Either::Left((result, _)) => result,
+			futures::future::Either::Right(_) => Err(Error::Timeout),
+		}
+	}
+
+	fn peer_metadata_as<M: Any + Send + Sync>(&self, peer: &PeerId) -> Option<Arc<M>> {
+		self.peer_metadata(peer)?.downcast().ok()
+	}
+
+	async fn wait_for<F>(&mut self, pred: F) -> Option<NotificationEvent>
+	where
+		F: Fn(&NotificationEvent) -> bool + Send,
+	{
+		loop {
+			let event = self.next_event().await?;
+			if pred(&event) {
+				return Some(event)
+			}
+		}
+	}
+}
+
+/// Events received by a [`TypedNotificationService`].
+#[derive(Debug)]
+pub enum TypedNotificationEvent<T> {
+	/// Event which doesn't carry a notification.
+	///
+	/// Never contains [`NotificationEvent::NotificationReceived`].
+	Other(NotificationEvent),
+
+	/// Notification was received from the substream and decoded.
+	NotificationReceived {
+		/// Peer ID.
+		peer: PeerId,
+
+		/// Decoded notification.
+		notification: T,
+	},
+
+	/// Notification was received from the substream but couldn't be decoded.
+	DecodeError {
+		/// Peer ID.
+		peer: PeerId,
+
+		/// Received notification.
+		notification: Vec<u8>,
+
+		/// Decoding error.
+		error: codec::Error,
+	},
+}
+
+/// Wrapper around [`NotificationService`] which sends and receives SCALE-encoded notifications
+/// of type `T`, saving protocols from encoding and decoding them by hand.
+///
+/// Methods not concerned with the notifications themselves are available through
+/// [`TypedNotificationService::inner()`].
+pub struct TypedNotificationService<T> {
+	/// Wrapped notification service.
+	inner: Box<dyn NotificationService>,
+
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Codec> TypedNotificationService<T> {
+	/// Create new [`TypedNotificationService`] wrapping `inner`.
+	pub fn new(inner: Box<dyn NotificationService>) -> Self {
+		Self { inner, _marker: PhantomData }
+	}
+
+	/// Get the wrapped notification service.
+	pub fn inner(&mut self) -> &mut dyn NotificationService {
+		&mut *self.inner
+	}
+
+	/// Consume `self` and return the wrapped notification service.
+	pub fn into_inner(self) -> Box<dyn NotificationService> {
+		self.inner
+	}
+
+	/// Encode `notification` and send it synchronously to `peer`.
+	pub fn send_sync_notification(&mut self, peer: &PeerId, notification: &T) {
+		self.inner.send_sync_notification(peer, notification.encode())
+	}
+
+	/// Encode `notification` and send it asynchronously to `peer`, allowing sender to exercise
+	/// backpressure.
+	pub async fn send_async_notification(
+		&mut self,
+		peer: &PeerId,
+		notification: &T,
+	) -> Result<(), error::Error> {
+		self.inner.send_async_notification(peer, notification.encode()).await
+	}
+
+	/// Get next event from the `Notifications` event stream, decoding received notifications.
+	pub async fn next_event(&mut self) -> Option<TypedNotificationEvent<T>> {
+		match self.inner.next_event().await? {
+			NotificationEvent::NotificationReceived { peer, notification } =>
+				Some(match T::decode_all(&mut &notification[..]) {
+					Ok(decoded) =>
+						TypedNotificationEvent::NotificationReceived { peer, notification: decoded },
+					Err(error) => TypedNotificationEvent::DecodeError { peer, notification, error },
+				}),
+			event => Some(TypedNotificationEvent::Other(event)),
+		}
+	}
 }
 
 /// Message sink for peers.
@@ -948,6 +1648,16 @@ pub trait MessageSink: Send + Sync {
 	/// Send synchronous `notification` to the peer associated with this [`MessageSink`].
 	fn send_sync_notification(&self, notification: Vec<u8>);
 
+	/// Send synchronous `notification` to the peer associated with this [`MessageSink`],
+	/// reporting whether it was queued or dropped.
+	///
+	/// By default the notification is sent with [`MessageSink::send_sync_notification()`] and
+	/// reported as queued, as that doesn't report dropped notifications.
+	fn try_send_sync_notification(&self, notification: Vec<u8>) -> SendStatus {
+		self.send_sync_notification(notification);
+		SendStatus::Queued
+	}
+
 	/// Send an asynchronous `notification` to to the peer associated with this [`MessageSink`],
 	/// allowing sender to exercise backpressure.
 	///
@@ -955,6 +1665,74 @@ pub trait MessageSink: Send + Sync {
 	async fn send_async_notification(&self, notification: Vec<u8>) -> Result<(), error::Error>;
 }
 
+/// Outcome of [`MessageSink::try_send_sync_notification()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendStatus {
+	/// The notification was queued for sending.
+	Queued,
+
+	/// The notification was dropped because the channel to the peer is full or closed.
+	Dropped,
+}
+
+/// [`MessageSink`] which refuses to send after its time-to-live.
+///
+/// See [`NotificationService::message_sink_scoped()`].
+pub struct ScopedMessageSink {
+	/// The wrapped sink.
+	inner: Box<dyn MessageSink>,
+
+	/// When the sink expires.
+	expires_at: Instant,
+}
+
+impl ScopedMessageSink {
+	/// Create new [`ScopedMessageSink`] wrapping `inner` which expires `ttl` from now.
+	pub fn new(inner: Box<dyn MessageSink>, ttl: Duration) -> Self {
+		Self { inner, expires_at: Instant::now() + ttl }
+	}
+
+	/// Check if the sink expired.
+	pub fn is_expired(&self) -> bool {
+		Instant::now() >= self.expires_at
+	}
+}
+
+#[async_trait::async_trait]
+impl MessageSink for ScopedMessageSink {
+	/// Send synchronous `notification` unless the sink expired.
+	fn send_sync_notification(&self, notification: Vec<u8>) {
+		if self.is_expired() {
+			log::warn!(target: "sub-libp2p", "message sink expired, dropping notification");
+			return
+		}
+
+		self.inner.send_sync_notification(notification)
+	}
+
+	/// Send synchronous `notification` unless the sink expired.
+	///
+	/// Returns [`SendStatus::Dropped`] if it did.
+	fn try_send_sync_notification(&self, notification: Vec<u8>) -> SendStatus {
+		if self.is_expired() {
+			return SendStatus::Dropped
+		}
+
+		self.inner.try_send_sync_notification(notification)
+	}
+
+	/// Send an asynchronous `notification` unless the sink expired.
+	///
+	/// Returns [`Error::Expired`] if it did.
+	async fn send_async_notification(&self, notification: Vec<u8>) -> Result<(), error::Error> {
+		if self.is_expired() {
+			return Err(Error::Expired)
+		}
+
+		self.inner.send_async_notification(notification).await
+	}
+}
+
 /// Trait defining the behavior of a bandwidth sink.
 pub trait BandwidthSink: Send + Sync {
 	/// Get the number of bytes received.